@@ -1,3 +1,14 @@
 fn main() {
+    // Embed the commit so version/About info identifies the exact build
+    let commit = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=STRATMASTER_GIT_COMMIT={}", commit);
+
     tauri_build::build()
-}
\ No newline at end of file
+}
@@ -0,0 +1,34 @@
+// Webview bridge diagnostics
+// Lets the frontend measure raw IPC round-trip cost, separate from any
+// network or backend latency.
+
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Payloads above this are rejected so the echo measures the bridge, not serialisation of huge strings.
+pub const MAX_ECHO_PAYLOAD_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeEcho {
+    pub echoed: String,
+    /// Unix time in nanoseconds when the Rust side received the call.
+    pub server_time_ns: u128,
+}
+
+pub fn echo(payload: String) -> Result<BridgeEcho, String> {
+    let server_time_ns = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    if payload.len() > MAX_ECHO_PAYLOAD_BYTES {
+        return Err(format!(
+            "Echo payload is {} bytes; the limit is {} bytes",
+            payload.len(),
+            MAX_ECHO_PAYLOAD_BYTES
+        ));
+    }
+    Ok(BridgeEcho {
+        echoed: payload,
+        server_time_ns,
+    })
+}
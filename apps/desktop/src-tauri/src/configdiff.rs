@@ -0,0 +1,104 @@
+// Config diffs
+// Compares configs through their serialized form, so every field (including
+// maps, lists and enum payloads such as a custom hardware profile) is covered
+// without a hand-written comparison that drifts as fields are added.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    /// Dotted field path, e.g. `hardware_profile.Custom.cpu_threads` or
+    /// `services[name=api].args[1]`.
+    pub path: String,
+    /// `None` when the field is absent or unset on that side.
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+/// Differences between `left` and `right`, sorted by path.
+pub fn diff<T: Serialize>(left: &T, right: &T) -> Result<Vec<ConfigDiff>, String> {
+    let to_value = |config: &T| serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {}", e));
+    let mut diffs = Vec::new();
+    diff_values("", Some(&to_value(left)?), Some(&to_value(right)?), &mut diffs);
+    diffs.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(diffs)
+}
+
+fn render(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+// Lists of named entries (services, environments, backends) are matched by
+// that name, so removing one entry doesn't shift every entry after it
+fn entry_key(left: &[Value], right: &[Value]) -> Option<&'static str> {
+    if left.is_empty() && right.is_empty() {
+        return None;
+    }
+    let unique = |items: &[Value], key: &str| {
+        let mut seen = BTreeSet::new();
+        items
+            .iter()
+            .all(|item| item.get(key).and_then(Value::as_str).is_some_and(|name| seen.insert(name)))
+    };
+    ["name", "id"].into_iter().find(|key| unique(left, key) && unique(right, key))
+}
+
+fn entry_name<'a>(item: &'a Value, key: &str) -> &'a str {
+    item.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+fn find_entry<'a>(items: &'a [Value], key: &str, name: &str) -> Option<&'a Value> {
+    items.iter().find(|item| entry_name(item, key) == name)
+}
+
+fn diff_values(path: &str, left: Option<&Value>, right: Option<&Value>, diffs: &mut Vec<ConfigDiff>) {
+    match (left, right) {
+        (Some(Value::Object(left)), Some(Value::Object(right))) => {
+            let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+            for key in keys {
+                diff_values(&join(path, key), left.get(key), right.get(key), diffs);
+            }
+        }
+        (Some(Value::Array(left)), Some(Value::Array(right))) => match entry_key(left, right) {
+            Some(key) => {
+                let mut names: Vec<&str> = Vec::new();
+                for item in left.iter().chain(right) {
+                    let name = entry_name(item, key);
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                for name in names {
+                    let entry_path = format!("{}[{}={}]", path, key, name);
+                    diff_values(&entry_path, find_entry(left, key, name), find_entry(right, key, name), diffs);
+                }
+            }
+            None => {
+                for index in 0..left.len().max(right.len()) {
+                    diff_values(&format!("{}[{}]", path, index), left.get(index), right.get(index), diffs);
+                }
+            }
+        },
+        (left, right) if left == right => {}
+        (left, right) => {
+            let (left, right) = (render(left), render(right));
+            if left != right {
+                diffs.push(ConfigDiff { path: path.to_string(), left, right });
+            }
+        }
+    }
+}
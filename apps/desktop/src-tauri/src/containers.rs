@@ -0,0 +1,266 @@
+// Container runtimes
+// Docker and Podman are driven through their CLIs, which already know how
+// to find the daemon (contexts, DOCKER_HOST, Podman machines), rather than
+// by talking to sockets directly. Sockets are reported for information only.
+// Containers the app starts are named and labelled so they can be cleaned up.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Command;
+
+pub const RUNTIME_NAMES: &[&str] = &["docker", "podman"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerRuntime {
+    pub name: String,
+    /// Client version.
+    pub version: String,
+    /// Whether the daemon (or Podman machine) answers.
+    pub running: bool,
+    pub socket: Option<String>,
+}
+
+// stdout of a successful run, trimmed; `None` if missing or failed
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn docker_socket() -> Option<String> {
+    if let Ok(host) = std::env::var("DOCKER_HOST") {
+        return Some(host);
+    }
+    if cfg!(windows) {
+        return Some("npipe:////./pipe/docker_engine".to_string());
+    }
+    let candidates = [
+        dirs::home_dir().map(|home| home.join(".docker/run/docker.sock")),
+        Some(std::path::PathBuf::from("/var/run/docker.sock")),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .find(|path| path.exists())
+        .map(|path| format!("unix://{}", path.display()))
+}
+
+fn probe(name: &str) -> Option<ContainerRuntime> {
+    let version = run(name, &["version", "--format", "{{.Client.Version}}"])
+        // Old clients and a stopped daemon can make `version` fail
+        .or_else(|| run(name, &["--version"]))?;
+    let (running, socket) = match name {
+        "podman" => {
+            let socket = run(name, &["info", "--format", "{{.Host.RemoteSocket.Path}}"]);
+            (socket.is_some(), socket.filter(|path| !path.is_empty()))
+        }
+        _ => (run(name, &["info", "--format", "{{.ServerVersion}}"]).is_some(), docker_socket()),
+    };
+    Some(ContainerRuntime {
+        name: name.to_string(),
+        version,
+        running,
+        socket,
+    })
+}
+
+/// Installed runtimes, running ones first; empty when none are installed.
+pub fn detect() -> Vec<ContainerRuntime> {
+    let mut runtimes: Vec<ContainerRuntime> = RUNTIME_NAMES.iter().filter_map(|name| probe(name)).collect();
+    runtimes.sort_by_key(|runtime| !runtime.running);
+    runtimes
+}
+
+// Launching
+/// Container names are prefixed so ours are recognisable and never collide
+/// with the user's own containers.
+pub const CONTAINER_NAME_PREFIX: &str = "stratmaster-";
+const MANAGED_LABEL: &str = "com.stratmaster.managed=true";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerSpec {
+    pub name: String,
+    pub image: String,
+    /// `host:container` port pairs; published on 127.0.0.1 unless an address is given.
+    #[serde(default)]
+    pub ports: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// `host_path:container_path[:ro]` bind mounts.
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    #[serde(default)]
+    pub ready_check: crate::services::ReadyCheck,
+    #[serde(default = "default_ready_timeout_ms")]
+    pub ready_timeout_ms: u64,
+}
+
+fn default_ready_timeout_ms() -> u64 {
+    120_000
+}
+
+/// A container started by the app, tracked so it can be stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningContainer {
+    pub runtime: String,
+    pub id: String,
+}
+
+impl ContainerSpec {
+    pub fn container_name(&self) -> String {
+        format!("{}{}", CONTAINER_NAME_PREFIX, self.name)
+    }
+
+    /// Arguments after `docker`/`podman`, validated so nothing in the spec is
+    /// read as an option.
+    pub fn run_args(&self) -> Result<Vec<String>, String> {
+        let valid_name = !self.name.is_empty()
+            && self.name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid_name {
+            return Err(format!("Invalid container name {:?}: use letters, digits, '-', '_' or '.'", self.name));
+        }
+        if self.image.trim().is_empty() || self.image.starts_with('-') || self.image.contains(char::is_whitespace) {
+            return Err(format!("Invalid image {:?}", self.image));
+        }
+        let mut args = vec![
+            "run".to_string(),
+            "--detach".to_string(),
+            "--name".to_string(),
+            self.container_name(),
+            "--label".to_string(),
+            MANAGED_LABEL.to_string(),
+        ];
+        for port in &self.ports {
+            let parts: Vec<&str> = port.split(':').collect();
+            let numeric = |part: &&str| part.split('/').next().is_some_and(|n| n.parse::<u16>().is_ok());
+            let published = match parts.as_slice() {
+                [host, container] if numeric(host) && numeric(container) => format!("127.0.0.1:{}", port),
+                [address, host, container] if !address.is_empty() && numeric(host) && numeric(container) => port.clone(),
+                _ => return Err(format!("Invalid port mapping {:?}: expected host:container", port)),
+            };
+            args.push("--publish".to_string());
+            args.push(published);
+        }
+        for key in self.env.keys() {
+            if key.is_empty() || key.contains('=') {
+                return Err(format!("Invalid environment variable name {:?}", key));
+            }
+            // Only the name: the value reaches the runtime through its own
+            // environment (see run_detached), not argv where `ps` shows it
+            args.push("--env".to_string());
+            args.push(key.clone());
+        }
+        for volume in &self.volumes {
+            if !volume.contains(':') || volume.starts_with('-') {
+                return Err(format!("Invalid volume {:?}: expected host_path:container_path", volume));
+            }
+            args.push("--volume".to_string());
+            args.push(volume.clone());
+        }
+        args.push(self.image.trim().to_string());
+        Ok(args)
+    }
+}
+
+/// The first installed runtime whose daemon answers.
+pub fn available_runtime() -> Option<ContainerRuntime> {
+    detect().into_iter().find(|runtime| runtime.running)
+}
+
+pub fn is_running(runtime: &str, container: &str) -> bool {
+    run(runtime, &["inspect", "--format", "{{.State.Running}}", container]).is_some_and(|state| state == "true")
+}
+
+/// Combined stdout and stderr, as `docker logs` writes the container's streams to both.
+pub fn logs(runtime: &str, container: &str) -> Option<String> {
+    let output = Command::new(runtime)
+        .args(["logs", "--tail", "500", container])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Some(text)
+}
+
+/// Starts the container detached and returns its ID. A stopped container
+/// left over under the same name is removed first; a running one is an error.
+pub fn run_detached(runtime: &str, spec: &ContainerSpec) -> Result<String, String> {
+    let args = spec.run_args()?;
+    let name = spec.container_name();
+    if is_running(runtime, &name) {
+        return Err(format!("Container {} is already running", name));
+    }
+    let _ = run(runtime, &["rm", "--force", &name]);
+
+    let output = Command::new(runtime)
+        .args(&args)
+        .envs(&spec.env)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", runtime, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} run failed for {}: {}",
+            runtime,
+            spec.image,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // A pull prints progress first; the ID is the last line
+    id.lines()
+        .last()
+        .map(str::to_string)
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| format!("{} run printed no container ID", runtime))
+}
+
+pub fn remove(container: &RunningContainer) -> Result<(), String> {
+    run(&container.runtime, &["rm", "--force", &container.id])
+        .map(|_| ())
+        .ok_or_else(|| format!("Failed to remove container {}", container.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> ContainerSpec {
+        serde_json::from_value(serde_json::json!({
+            "name": "vector-db",
+            "image": "qdrant/qdrant:latest",
+            "ports": ["6333:6333"],
+            "env": { "API_KEY": "s3cret" },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn env_values_stay_out_of_argv() {
+        let args = spec().run_args().unwrap();
+        let env = args.iter().position(|arg| arg == "--env").unwrap();
+        assert_eq!(args[env + 1], "API_KEY");
+        assert!(args.iter().all(|arg| !arg.contains("s3cret")));
+    }
+
+    #[test]
+    fn ports_publish_on_loopback_by_default() {
+        let args = spec().run_args().unwrap();
+        assert!(args.contains(&"127.0.0.1:6333:6333".to_string()));
+        assert_eq!(args.last().map(String::as_str), Some("qdrant/qdrant:latest"));
+    }
+
+    #[test]
+    fn env_names_with_equals_are_rejected() {
+        let mut spec = spec();
+        spec.env.insert("A=B".to_string(), "c".to_string());
+        assert!(spec.run_args().is_err());
+    }
+}
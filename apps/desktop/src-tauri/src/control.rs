@@ -0,0 +1,93 @@
+// Control socket
+// An opt-in local socket (a Unix domain socket, or a named pipe on Windows)
+// that scripts can drive with newline-delimited JSON. Only a few safe
+// commands are exposed, and a connection is dropped as soon as a request
+// carries the wrong session token.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Tokens shorter than this are refused when the socket is enabled.
+pub const MIN_TOKEN_LEN: usize = 16;
+/// Longest request line accepted; longer lines close the connection.
+pub const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlCommand {
+    HealthCheck,
+    SwitchEnvironment { name: String },
+    StartServices,
+    StopServices,
+}
+
+impl ControlCommand {
+    /// The app command this maps to, for kiosk checks and logging.
+    pub fn command_name(&self) -> &'static str {
+        match self {
+            ControlCommand::HealthCheck => "get_local_server_status",
+            ControlCommand::SwitchEnvironment { .. } => "switch_environment",
+            ControlCommand::StartServices => "start_local_services",
+            ControlCommand::StopServices => "stop_local_services",
+        }
+    }
+}
+
+/// One line of the protocol, e.g. `{"token": "...", "command": "health_check"}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ControlRequest {
+    pub token: String,
+    #[serde(flatten)]
+    pub command: ControlCommand,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ControlResponse {
+    pub fn success(result: Value) -> Self {
+        ControlResponse { ok: true, result: Some(result), error: None }
+    }
+
+    pub fn failure(error: impl Into<String>) -> Self {
+        ControlResponse { ok: false, result: None, error: Some(error.into()) }
+    }
+}
+
+pub fn validate_token(token: &str) -> Result<(), String> {
+    if token.len() < MIN_TOKEN_LEN {
+        return Err(format!("Control token must be at least {} characters", MIN_TOKEN_LEN));
+    }
+    Ok(())
+}
+
+/// Compares in constant time so response timing doesn't leak the token.
+pub fn token_matches(expected: &str, presented: &str) -> bool {
+    let (expected, presented) = (expected.as_bytes(), presented.as_bytes());
+    expected.len() == presented.len() && expected.iter().zip(presented).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Per-user, so two accounts on one machine don't collide.
+#[cfg(windows)]
+pub fn socket_path(_data_dir: &std::path::Path) -> String {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    let user: String = user.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')).collect();
+    format!(r"\\.\pipe\stratmaster-control-{}", user)
+}
+
+/// The XDG runtime directory when there is one (private to the user and
+/// cleared on logout), else the data dir.
+#[cfg(not(windows))]
+pub fn socket_path(data_dir: &std::path::Path) -> String {
+    dirs::runtime_dir()
+        .map(|dir| dir.join("stratmaster-control.sock"))
+        .unwrap_or_else(|| data_dir.join("control.sock"))
+        .to_string_lossy()
+        .to_string()
+}
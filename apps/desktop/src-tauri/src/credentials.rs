@@ -0,0 +1,215 @@
+// Stored credentials
+// Secrets live in the OS keychain (Keychain, Credential Manager, Secret
+// Service); only their names are kept in the config. Backups are encrypted
+// with a passphrase-derived key (Argon2id + ChaCha20-Poly1305) so plaintext
+// never reaches disk.
+
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+const KEYCHAIN_SERVICE: &str = "com.stratmaster.app";
+const BACKUP_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, name).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+pub fn store(name: &str, secret: &str) -> Result<(), String> {
+    entry(name)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store credential {}: {}", name, e))
+}
+
+pub fn load(name: &str) -> Result<Option<String>, String> {
+    match read(name) {
+        Ok(secret) => Ok(Some(secret)),
+        Err(e) if e.kind == CredentialErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.message),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialErrorKind {
+    NotFound,
+    /// The entry exists but its access control no longer admits this app,
+    /// typically after an OS migration or re-signed build.
+    AccessDenied,
+    Locked,
+    Unavailable,
+}
+
+/// Keychain failure the frontend can act on rather than just display.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialError {
+    pub kind: CredentialErrorKind,
+    pub name: String,
+    pub message: String,
+    pub guidance: Option<String>,
+}
+
+impl std::fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.guidance {
+            Some(guidance) => write!(f, "{}. {}", self.message, guidance),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl CredentialError {
+    fn new(name: &str, error: &keyring::Error) -> Self {
+        let kind = classify(error);
+        let message = match kind {
+            CredentialErrorKind::NotFound => format!("Credential {} is not in the keychain", name),
+            CredentialErrorKind::AccessDenied => format!("Access to credential {} was denied", name),
+            CredentialErrorKind::Locked => format!("The keychain holding credential {} is locked", name),
+            CredentialErrorKind::Unavailable => format!("Failed to read credential {}: {}", name, error),
+        };
+        CredentialError {
+            kind,
+            name: name.to_string(),
+            message,
+            guidance: guidance(kind).map(str::to_string),
+        }
+    }
+}
+
+// The keyring crate passes platform errors through opaquely, so the codes and
+// phrases of each backend are matched in their text
+fn classify(error: &keyring::Error) -> CredentialErrorKind {
+    let text = match error {
+        keyring::Error::NoEntry => return CredentialErrorKind::NotFound,
+        keyring::Error::PlatformFailure(inner) | keyring::Error::NoStorageAccess(inner) => inner.to_string().to_lowercase(),
+        _ => return CredentialErrorKind::Unavailable,
+    };
+    // macOS errSecInteractionNotAllowed, or a locked Secret Service collection
+    if text.contains("-25308") || text.contains("interaction is not allowed") || text.contains("locked") {
+        CredentialErrorKind::Locked
+    // macOS errSecAuthFailed and user cancellation; Windows ERROR_ACCESS_DENIED
+    } else if text.contains("-25293") || text.contains("-128") || text.contains("denied") || text.contains("not permitted") {
+        CredentialErrorKind::AccessDenied
+    } else {
+        CredentialErrorKind::Unavailable
+    }
+}
+
+fn guidance(kind: CredentialErrorKind) -> Option<&'static str> {
+    match kind {
+        CredentialErrorKind::NotFound | CredentialErrorKind::AccessDenied => {
+            Some("Repair the credential and enter it again")
+        }
+        CredentialErrorKind::Locked if cfg!(target_os = "macos") => Some(
+            "Unlock the login keychain in Keychain Access (File > Unlock Keychain) or run `security unlock-keychain`, then try again",
+        ),
+        CredentialErrorKind::Locked => Some("Unlock your keyring, for example by signing in to the desktop session, then try again"),
+        CredentialErrorKind::Unavailable => None,
+    }
+}
+
+pub fn read(name: &str) -> Result<String, CredentialError> {
+    let entry = entry(name).map_err(|message| CredentialError {
+        kind: CredentialErrorKind::Unavailable,
+        name: name.to_string(),
+        message,
+        guidance: None,
+    })?;
+    entry.get_password().map_err(|e| CredentialError::new(name, &e))
+}
+
+/// Deletes an entry that can no longer be read so it can be stored afresh.
+/// A locked keychain can't be repaired this way and is reported instead.
+pub fn repair(name: &str) -> Result<(), CredentialError> {
+    let entry = entry(name).map_err(|message| CredentialError {
+        kind: CredentialErrorKind::Unavailable,
+        name: name.to_string(),
+        message,
+        guidance: None,
+    })?;
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(CredentialError::new(name, &e)),
+    }
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete credential {}: {}", name, e)),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Backup {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, String> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Encrypts `credentials` into a base64 blob that only `passphrase` opens.
+pub fn encrypt_backup(credentials: &BTreeMap<String, String>, passphrase: &str) -> Result<String, String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt)?;
+    let plaintext = Zeroizing::new(
+        serde_json::to_vec(credentials).map_err(|e| format!("Failed to serialize credentials: {}", e))?,
+    );
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let backup = Backup {
+        version: BACKUP_VERSION,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    let json = serde_json::to_vec(&backup).map_err(|e| format!("Failed to serialize backup: {}", e))?;
+    Ok(BASE64.encode(json))
+}
+
+/// Opens a blob from [`encrypt_backup`]. A wrong passphrase fails AEAD
+/// authentication and is reported as such, never as garbage credentials.
+pub fn decrypt_backup(blob: &str, passphrase: &str) -> Result<BTreeMap<String, String>, String> {
+    let invalid = || "Not a StratMaster credentials backup".to_string();
+    let json = BASE64.decode(blob.trim()).map_err(|_| invalid())?;
+    let backup: Backup = serde_json::from_slice(&json).map_err(|_| invalid())?;
+    if backup.version != BACKUP_VERSION {
+        return Err(format!("Unsupported credentials backup version {}", backup.version));
+    }
+    let salt = BASE64.decode(&backup.salt).map_err(|_| invalid())?;
+    let nonce = BASE64.decode(&backup.nonce).map_err(|_| invalid())?;
+    let ciphertext = BASE64.decode(&backup.ciphertext).map_err(|_| invalid())?;
+    if nonce.len() != NONCE_LEN {
+        return Err(invalid());
+    }
+
+    let key = derive_key(passphrase, &salt)?;
+    let plaintext = Zeroizing::new(
+        ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| "Wrong passphrase, or the backup has been modified".to_string())?,
+    );
+    serde_json::from_slice(&plaintext).map_err(|_| invalid())
+}
@@ -0,0 +1,111 @@
+// Content security policy
+// Narrows the webview's `connect-src` to the origins of configured
+// environments and backends, plus explicit extras, so a compromised frontend
+// cannot reach arbitrary hosts. Tauri fixes the policy at launch; origins
+// added later take effect after a restart.
+
+use crate::AppConfig;
+use serde::{Deserialize, Serialize};
+
+/// The origin (`scheme://host[:port]`) of an http(s) or ws(s) URL.
+pub fn origin(url: &str) -> Option<String> {
+    let url = url::Url::parse(url.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https" | "ws" | "wss") {
+        return None;
+    }
+    let origin = url.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// Every origin the frontend may connect to. Each http(s) origin is paired
+/// with its ws(s) counterpart so live channels to the same backend work.
+pub fn connect_src_origins(config: &AppConfig) -> Vec<String> {
+    let urls = std::iter::once(config.api_base_url.as_str())
+        .chain(config.environments.iter().map(|env| env.base_url.as_str()))
+        .chain(config.backends.iter().map(|backend| backend.base_url.as_str()))
+        .chain(config.csp_connect_src.iter().map(String::as_str));
+
+    let mut origins: Vec<String> = Vec::new();
+    for origin in urls.filter_map(origin) {
+        let websocket = origin
+            .strip_prefix("http")
+            .map(|rest| format!("ws{}", rest));
+        for origin in std::iter::once(origin).chain(websocket) {
+            if !origins.contains(&origin) {
+                origins.push(origin);
+            }
+        }
+    }
+    origins
+}
+
+/// Replaces (or appends) the `connect-src` directive of `policy` with
+/// `'self'` plus `origins`, leaving every other directive untouched.
+pub fn with_connect_src(policy: &str, origins: &[String]) -> String {
+    let connect_src = std::iter::once("connect-src 'self'".to_string())
+        .chain(origins.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut replaced = false;
+    let mut directives: Vec<String> = policy
+        .split(';')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| {
+            let name = directive.split_whitespace().next().unwrap_or("");
+            if name.eq_ignore_ascii_case("connect-src") {
+                replaced = true;
+                connect_src.clone()
+            } else {
+                directive.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        directives.push(connect_src);
+    }
+    format!("{};", directives.join("; "))
+}
+
+/// Whether a page at `frontend_url` would be blocked from reaching `backend_url`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixedContentCheck {
+    pub frontend_origin: String,
+    pub backend_url: String,
+    pub mixed_content: bool,
+    pub remediation: Option<String>,
+}
+
+// Pages from the bundled assets (tauri://, https://tauri.localhost) are secure
+// contexts just like https ones
+fn is_secure_page(url: &url::Url) -> bool {
+    matches!(url.scheme(), "https" | "tauri")
+}
+
+/// Loopback http counts as potentially trustworthy in browsers, so only
+/// plain http(s)/ws to other hosts is mixed content.
+pub fn check_mixed_content(frontend_url: &str, backend_url: &str) -> Result<MixedContentCheck, String> {
+    let frontend = url::Url::parse(frontend_url.trim()).map_err(|e| format!("Invalid frontend URL {}: {}", frontend_url, e))?;
+    let backend = url::Url::parse(backend_url.trim()).map_err(|e| format!("Invalid backend URL {}: {}", backend_url, e))?;
+    let insecure_backend = matches!(backend.scheme(), "http" | "ws") && !crate::is_local_url(backend.as_str());
+    let mixed_content = is_secure_page(&frontend) && insecure_backend;
+    let remediation = mixed_content.then(|| {
+        let https = backend.as_str().replacen("http://", "https://", 1).replacen("ws://", "wss://", 1);
+        format!(
+            "Serve the backend over TLS and use {} instead, or in development load the frontend over http",
+            https
+        )
+    });
+    Ok(MixedContentCheck {
+        // url's origin() is opaque ("null") for custom schemes like tauri://
+        frontend_origin: match (frontend.host_str(), frontend.port()) {
+            (Some(host), Some(port)) => format!("{}://{}:{}", frontend.scheme(), host, port),
+            (Some(host), None) => format!("{}://{}", frontend.scheme(), host),
+            (None, _) => frontend.as_str().to_string(),
+        },
+        backend_url: backend_url.trim().to_string(),
+        mixed_content,
+        remediation,
+    })
+}
@@ -0,0 +1,245 @@
+// Data directory location
+// The data directory can live anywhere; a small pointer file in the config
+// directory records where. Moving it copies file-by-file through `.partial`
+// temporaries so an interrupted move can be resumed and never leaves a
+// half-written file under its real name. The old copy is removed on the
+// next launch, once nothing in the running app has files open in it.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const POINTER_FILE_NAME: &str = "data-location.json";
+/// Records a data directory left behind by a move, for removal at next launch.
+pub const CLEANUP_FILE_NAME: &str = "data-location-cleanup.json";
+/// Marks a destination a relocation has started writing into, so it may be resumed.
+pub const RELOCATION_MARKER: &str = ".stratmaster-relocation";
+const PARTIAL_SUFFIX: &str = ".partial";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DataPointer {
+    path: PathBuf,
+}
+
+/// The relocated data directory, or `None` when it was never moved. A pointer
+/// whose target is gone (an unmounted drive, say) is an error rather than a
+/// silent fall back to an empty default directory.
+pub fn read_pointer(config_dir: &Path) -> Result<Option<PathBuf>, String> {
+    let path = config_dir.join(POINTER_FILE_NAME);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let pointer: DataPointer =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid data location file {}: {}", path.display(), e))?;
+    if !pointer.path.is_dir() {
+        return Err(format!(
+            "Data directory {} is missing; reconnect the drive it is on or move the data again",
+            pointer.path.display()
+        ));
+    }
+    Ok(Some(pointer.path))
+}
+
+/// Replaces the pointer atomically (write to a temporary, then rename).
+pub fn write_pointer(config_dir: &Path, data_dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(config_dir)?;
+    let contents = serde_json::to_string_pretty(&DataPointer {
+        path: data_dir.to_path_buf(),
+    })
+    .map_err(io::Error::from)?;
+    let temp = config_dir.join(format!("{}{}", POINTER_FILE_NAME, PARTIAL_SUFFIX));
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, config_dir.join(POINTER_FILE_NAME))
+}
+
+pub fn remove_pointer(config_dir: &Path) -> io::Result<()> {
+    match fs::remove_file(config_dir.join(POINTER_FILE_NAME)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+pub fn schedule_removal(config_dir: &Path, old_dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(config_dir)?;
+    let contents = serde_json::to_string_pretty(&DataPointer {
+        path: old_dir.to_path_buf(),
+    })
+    .map_err(io::Error::from)?;
+    fs::write(config_dir.join(CLEANUP_FILE_NAME), contents)
+}
+
+/// The directory a previous move left behind, clearing the record so it is
+/// handed out once.
+pub fn take_scheduled_removal(config_dir: &Path) -> Option<PathBuf> {
+    let path = config_dir.join(CLEANUP_FILE_NAME);
+    let contents = fs::read_to_string(&path).ok()?;
+    let _ = fs::remove_file(&path);
+    serde_json::from_str::<DataPointer>(&contents).ok().map(|pointer| pointer.path)
+}
+
+/// Copies everything under `src` into `dst`, skipping `skip` and files that
+/// already exist in `dst` with the same size (left by an earlier attempt).
+/// `progress` receives the running byte count after each file.
+pub fn copy_tree_resumable(
+    src: &Path,
+    dst: &Path,
+    skip: &[PathBuf],
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<u64> {
+    let mut copied = 0;
+    copy_dir(src, dst, skip, &mut copied, progress)?;
+    Ok(copied)
+}
+
+fn copy_dir(
+    src: &Path,
+    dst: &Path,
+    skip: &[PathBuf],
+    copied: &mut u64,
+    progress: &mut dyn FnMut(u64),
+) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        if skip.iter().any(|skipped| skipped == &path) {
+            continue;
+        }
+        let target = dst.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&path, &target, skip, copied, progress)?;
+        } else if file_type.is_file() {
+            let len = entry.metadata()?.len();
+            let already_copied = fs::metadata(&target).map(|m| m.len() == len).unwrap_or(false);
+            if !already_copied {
+                let partial = PathBuf::from(format!("{}{}", target.display(), PARTIAL_SUFFIX));
+                fs::copy(&path, &partial)?;
+                fs::File::open(&partial)?.sync_all()?;
+                fs::rename(&partial, &target)?;
+            }
+            *copied += len;
+            progress(*copied);
+        }
+    }
+    Ok(())
+}
+
+/// Removes everything under `dir` except the paths in `keep`, then `dir`
+/// itself if nothing was kept. Returns the number of entries left behind.
+pub fn remove_tree_except(dir: &Path, keep: &[PathBuf]) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut remaining = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if keep.iter().any(|kept| kept == &path) {
+            remaining += 1;
+            continue;
+        }
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        if result.is_err() {
+            remaining += 1;
+        }
+    }
+    if remaining == 0 {
+        let _ = fs::remove_dir(dir);
+    }
+    remaining
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh directory under the system temp dir, removed on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("stratmaster-datadir-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn missing_pointer_means_not_relocated() {
+        let config = TempDir::new("no-pointer");
+        assert_eq!(read_pointer(&config.0), Ok(None));
+    }
+
+    #[test]
+    fn pointer_round_trips() {
+        let config = TempDir::new("pointer-config");
+        let data = TempDir::new("pointer-data");
+        write_pointer(&config.0, &data.0).unwrap();
+        assert_eq!(read_pointer(&config.0), Ok(Some(data.0.clone())));
+    }
+
+    #[test]
+    fn pointer_to_missing_directory_is_an_error() {
+        let config = TempDir::new("dangling-config");
+        write_pointer(&config.0, &config.0.join("unmounted")).unwrap();
+        let error = read_pointer(&config.0).unwrap_err();
+        assert!(error.contains("unmounted"), "{}", error);
+    }
+
+    #[test]
+    fn copy_resumes_and_skips() {
+        let src = TempDir::new("copy-src");
+        let dst = TempDir::new("copy-dst");
+        fs::create_dir_all(src.0.join("models")).unwrap();
+        fs::write(src.0.join("models/a.bin"), b"model").unwrap();
+        fs::write(src.0.join("config.json"), b"{}").unwrap();
+        fs::write(src.0.join("stratmaster.lock"), b"1").unwrap();
+        // Left by an interrupted earlier attempt
+        fs::create_dir_all(dst.0.join("models")).unwrap();
+        fs::write(dst.0.join("models/a.bin"), b"model").unwrap();
+
+        let mut reported = Vec::new();
+        let copied = copy_tree_resumable(&src.0, &dst.0, &[src.0.join("stratmaster.lock")], &mut |bytes| reported.push(bytes))
+            .unwrap();
+
+        assert_eq!(copied, 7);
+        assert_eq!(reported.last(), Some(&7));
+        assert_eq!(fs::read(dst.0.join("config.json")).unwrap(), b"{}");
+        assert!(!dst.0.join("stratmaster.lock").exists());
+        assert!(!dst.0.join(format!("config.json{}", PARTIAL_SUFFIX)).exists());
+    }
+
+    #[test]
+    fn removal_keeps_listed_paths() {
+        let dir = TempDir::new("remove");
+        fs::create_dir_all(dir.0.join("cache")).unwrap();
+        fs::write(dir.0.join("cache/entry"), b"x").unwrap();
+        fs::write(dir.0.join(POINTER_FILE_NAME), b"{}").unwrap();
+
+        assert_eq!(remove_tree_except(&dir.0, &[dir.0.join(POINTER_FILE_NAME)]), 1);
+        assert!(!dir.0.join("cache").exists());
+        assert!(dir.0.join(POINTER_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn scheduled_removal_is_taken_once() {
+        let config = TempDir::new("cleanup");
+        schedule_removal(&config.0, Path::new("/old/data")).unwrap();
+        assert_eq!(take_scheduled_removal(&config.0), Some(PathBuf::from("/old/data")));
+        assert_eq!(take_scheduled_removal(&config.0), None);
+    }
+}
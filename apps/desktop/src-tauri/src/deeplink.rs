@@ -0,0 +1,90 @@
+// Deep links
+// Parses `stratmaster://` URLs opened from a browser or passed on the command
+// line into a validated action. Anything we don't recognise is rejected rather
+// than forwarded to the frontend.
+
+use serde::{Deserialize, Serialize};
+
+pub const URL_SCHEME: &str = "stratmaster";
+
+const MAX_PARAM_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeepLinkAction {
+    /// `stratmaster://open?env=prod&view=reports`
+    Open,
+    /// `stratmaster://support?d=<code>` from `create_support_link`
+    Support,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepLink {
+    pub action: DeepLinkAction,
+    pub environment: Option<String>,
+    pub view: Option<String>,
+    /// Encoded diagnostics summary of a support link.
+    #[serde(default)]
+    pub support_code: Option<String>,
+}
+
+/// Parses and validates a deep link. Unknown query parameters are ignored;
+/// known ones must be short identifiers (letters, digits, `-`, `_`, `.`).
+pub fn parse_deep_link(raw: &str) -> Result<DeepLink, String> {
+    let url = url::Url::parse(raw.trim()).map_err(|e| format!("Invalid deep link: {}", e))?;
+    if url.scheme() != URL_SCHEME {
+        return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+    }
+
+    // `stratmaster://open` puts the action in the host, `stratmaster:open` in the path
+    let action = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| url.path().trim_matches('/'));
+    let action = match action.to_ascii_lowercase().as_str() {
+        "open" | "" => DeepLinkAction::Open,
+        "support" => DeepLinkAction::Support,
+        other => return Err(format!("Unsupported deep link action: {}", other)),
+    };
+
+    let mut link = DeepLink {
+        action,
+        environment: None,
+        view: None,
+        support_code: None,
+    };
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "env" | "environment" => link.environment = Some(sanitize_param(&key, &value)?),
+            "view" => link.view = Some(sanitize_param(&key, &value)?),
+            "d" if link.action == DeepLinkAction::Support => link.support_code = Some(sanitize_code(&value)?),
+            _ => {}
+        }
+    }
+    Ok(link)
+}
+
+// URL-safe base64 only; the code is decoded and validated separately
+fn sanitize_code(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let valid = !value.is_empty()
+        && value.len() <= crate::support::MAX_SUPPORT_CODE_LEN
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        return Err("Invalid support code in deep link".to_string());
+    }
+    Ok(value.to_string())
+}
+
+fn sanitize_param(key: &str, value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_PARAM_LEN
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(format!("Invalid value for deep link parameter {}", key));
+    }
+    Ok(value.to_string())
+}
@@ -0,0 +1,67 @@
+// Backend discovery
+// Browses the local network over mDNS for StratMaster backends advertising
+// `_stratmaster._tcp`, e.g. one running on a separate GPU box.
+
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+pub const SERVICE_TYPE: &str = "_stratmaster._tcp.local.";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveredBackend {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    /// From the `version` TXT record, when the backend advertises it.
+    pub version: Option<String>,
+}
+
+/// Collects every backend resolved within `timeout`. Blocks the calling
+/// thread; run it off the async runtime. Networks that drop multicast simply
+/// produce no answers, so that case yields an empty list rather than an error.
+pub fn browse(timeout: Duration) -> Result<Vec<DiscoveredBackend>, String> {
+    let daemon = ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS daemon: {}", e))?;
+    let receiver = daemon
+        .browse(SERVICE_TYPE)
+        .map_err(|e| format!("Failed to browse {}: {}", SERVICE_TYPE, e))?;
+
+    let deadline = Instant::now() + timeout;
+    let mut found: Vec<DiscoveredBackend> = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match receiver.recv_timeout(remaining) {
+            Ok(ServiceEvent::ServiceResolved(info)) => {
+                let name = info
+                    .get_fullname()
+                    .strip_suffix(&format!(".{}", SERVICE_TYPE))
+                    .unwrap_or(info.get_fullname())
+                    .to_string();
+                // Prefer an IPv4 address; fall back to the advertised hostname
+                let mut addresses: Vec<_> = info.get_addresses().iter().collect();
+                addresses.sort_by_key(|address| !address.is_ipv4());
+                let host = addresses
+                    .first()
+                    .map(|address| address.to_string())
+                    .unwrap_or_else(|| info.get_hostname().trim_end_matches('.').to_string());
+                let backend = DiscoveredBackend {
+                    name,
+                    host,
+                    port: info.get_port(),
+                    version: info.get_property_val_str("version").map(str::to_string),
+                };
+                if !found.contains(&backend) {
+                    found.push(backend);
+                }
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    let _ = daemon.shutdown();
+    Ok(found)
+}
@@ -0,0 +1,194 @@
+// Kiosk mode
+// Locks the app down for unattended displays: only allow-listed commands
+// get past the invoke handler and external links are limited to an
+// allowlist of origins. Leaving needs the passphrase set on entry, kept only
+// as an Argon2id hash.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+
+/// Error returned for commands refused while kiosk mode is on.
+pub const KIOSK_ERROR: &str = "kiosk_mode";
+
+/// Commands that still run while kiosk mode is on: reads, status and the
+/// request path the UI itself needs. Everything else, including commands
+/// added later, is refused until it is listed here.
+pub const ALLOWED_COMMANDS: &[&str] = &[
+    "get_system_info",
+    "get_hardware_survey",
+    "get_cpu_features",
+    "list_gpus",
+    "check_api_health",
+    "get_backend_warnings",
+    "acknowledge_warning",
+    "get_retry_policy",
+    "get_logs",
+    "follow_log_file",
+    "stop_following_log",
+    "proxy_api_request",
+    "get_cache_stats",
+    "get_inflight_request_count",
+    "get_client_id",
+    "warm_connection",
+    "profile_request",
+    "inspect_tls_certificate",
+    "resolve_host",
+    "inspect_resolved_addresses",
+    "get_http_client_config",
+    "get_app_data_dir",
+    "open_external_url",
+    "get_data_dir_filesystem",
+    "is_elevated",
+    "get_machine_fingerprint",
+    "check_port_firewall",
+    "check_data_dir_permissions",
+    "get_diagnostics",
+    "copy_diagnostics",
+    "get_webview_cache_size",
+    "list_backends",
+    "discover_backends_mdns",
+    "list_environments",
+    "check_mixed_content",
+    "cancel_all_requests",
+    "get_effective_csp",
+    "get_webview_flags",
+    "load_app_config",
+    "list_config_backups",
+    "get_rate_limit_state",
+    "diff_configs",
+    "diff_config_against_default",
+    "get_managed_config_status",
+    "get_feature_flag",
+    "list_feature_flags",
+    "refresh_feature_flags",
+    "get_backend_openapi",
+    "list_backend_endpoints",
+    "fetch_headers",
+    "estimate_model_fit",
+    "list_models",
+    "estimate_request_cost",
+    "get_pricing_table",
+    "list_credentials",
+    "get_local_server_status",
+    "start_health_monitor",
+    "stop_health_monitor",
+    "list_services",
+    "get_metrics_prometheus",
+    "get_cpu_temperature",
+    "get_power_mode",
+    "get_swap_info",
+    "get_command_metrics",
+    "report_frame_timing",
+    "get_render_stats",
+    "get_gpu_driver_info",
+    "get_vram_headroom",
+    "get_pending_os_updates",
+    "get_system_locale",
+    "get_accept_language",
+    "validate_service_definitions",
+    "detect_container_runtimes",
+    "measure_service_chain_latency",
+    "follow_container_logs",
+    "stop_following_container_logs",
+    "get_container_log_backfill",
+    "identify_port_owner",
+    "is_always_on_top",
+    "connect_health_ws",
+    "get_recommended_window_size",
+    "get_accessibility",
+    "check_stale_locks",
+    "restore_workspace_layout",
+    "get_system_theme",
+    "list_system_fonts",
+    "get_app_version",
+    "show_about_dialog",
+    "get_startup_metrics",
+    "wait_for_backend_ready",
+    "bridge_echo",
+    "take_pending_deep_link",
+    "get_kiosk_status",
+    "set_kiosk_mode",
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KioskSettings {
+    /// Origins `open_external_url` may still open, e.g. `https://example.com`.
+    pub url_allowlist: Vec<String>,
+    pub passphrase_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KioskStatus {
+    pub enabled: bool,
+    /// For the frontend to hide controls for every other command.
+    pub allowed_commands: Vec<String>,
+}
+
+impl KioskStatus {
+    pub fn new(enabled: bool) -> Self {
+        KioskStatus {
+            enabled,
+            allowed_commands: if enabled {
+                ALLOWED_COMMANDS.iter().map(|command| command.to_string()).collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+pub fn is_restricted(command: &str) -> bool {
+    !ALLOWED_COMMANDS.contains(&command)
+}
+
+/// Whether `url`'s origin is on the allowlist; non-web URLs never are.
+pub fn is_url_allowed(settings: &KioskSettings, url: &str) -> bool {
+    let Some(origin) = crate::csp::origin(url) else {
+        return false;
+    };
+    settings
+        .url_allowlist
+        .iter()
+        .filter_map(|allowed| crate::csp::origin(allowed))
+        .any(|allowed| allowed == origin)
+}
+
+pub fn hash_passphrase(passphrase: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash kiosk passphrase: {}", e))
+}
+
+pub fn verify_passphrase(hash: &str, passphrase: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| Argon2::default().verify_password(passphrase.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configuration_commands_are_restricted() {
+        for command in ["set_log_level", "set_request_logging", "select_gpu", "set_maintenance_policy", "exit_app"] {
+            assert!(is_restricted(command), "{} should be restricted", command);
+        }
+    }
+
+    #[test]
+    fn unknown_commands_are_restricted() {
+        assert!(is_restricted("some_future_command"));
+    }
+
+    #[test]
+    fn kiosk_can_still_be_left() {
+        assert!(!is_restricted("get_kiosk_status"));
+        assert!(!is_restricted("set_kiosk_mode"));
+    }
+}
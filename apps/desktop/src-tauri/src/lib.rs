@@ -3,16 +3,308 @@
 
 pub mod system;
 pub mod bridge;
+pub mod configdiff;
+pub mod containers;
+pub mod control;
+pub mod credentials;
+pub mod csp;
+pub mod datadir;
+pub mod deeplink;
+pub mod discovery;
+pub mod kiosk;
+pub mod locks;
+pub mod logging;
+pub mod maintenance;
+pub mod metrics;
+pub mod models;
+pub mod net;
+pub mod power;
+pub mod provisioning;
+pub mod ratelimit;
+pub mod redact;
+pub mod services;
+pub mod sse;
+pub mod support;
+pub mod updates;
+pub mod webview;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Appended to clipboard text cut at `max_clipboard_bytes`, which must leave room for it.
+pub const CLIPBOARD_TRUNCATION_MARKER: &str = "\n... truncated";
 
 // Application configuration structures
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub api_base_url: String,
     pub auto_start_services: bool,
     pub theme: String,
     pub hardware_profile: HardwareProfile,
+    pub log_level: String,
+    pub backends: Vec<BackendConfig>,
+    pub active_backend: Option<String>,
+    pub acknowledged_warnings: Vec<String>,
+    /// Anonymous per-install identifier sent as `X-Client-Id`; generated on first run.
+    pub client_id: Option<String>,
+    pub http: HttpClientConfig,
+    /// Local feature flag overrides; these win over flags from the backend manifest.
+    pub feature_flags: HashMap<String, bool>,
+    pub environments: Vec<ApiEnvironment>,
+    pub active_environment: Option<String>,
+    /// Larger clipboard payloads are truncated; some clipboard managers hang on huge text.
+    pub max_clipboard_bytes: usize,
+    /// When set (e.g. "127.0.0.1:9464"), serves Prometheus metrics at `/metrics` on this address.
+    pub metrics_listen_addr: Option<String>,
+    /// Per-model prices for paid remote backends; the `"*"` entry applies to unlisted models.
+    pub pricing: HashMap<String, ModelPricing>,
+    /// The monitoring loop emits `cpu-temp-warning` at or above this CPU temperature (°C).
+    pub cpu_temp_warning_celsius: f64,
+    /// Names of credentials stored in the OS keychain; the secrets themselves never touch this file.
+    pub credentials: Vec<String>,
+    /// Font family for the UI; the frontend's default stack when unset.
+    pub ui_font: Option<String>,
+    /// Minimum milliseconds between emits of high-frequency events; payloads in
+    /// between are delivered together as one array. 0 disables batching, and
+    /// each payload is emitted on its own, unwrapped.
+    pub event_batch_intervals_ms: HashMap<String, u64>,
+    pub health_transport: HealthTransport,
+    /// Path of the backend's websocket health channel, used with [`HealthTransport::WebSocket`].
+    pub health_ws_path: String,
+    /// Origins the webview may reach besides `api_base_url`, environments and
+    /// backends. Merged into the CSP `connect-src` at launch.
+    pub csp_connect_src: Vec<String>,
+    /// Local processes started by `start_local_services`, in dependency order.
+    pub services: Vec<services::ServiceDefinition>,
+    pub service_recovery: services::RecoveryPolicy,
+    /// Services checked by `get_local_server_status`; names are unique.
+    pub status_endpoints: Vec<services::StatusEndpoint>,
+    /// Keeps every window above others; reapplied on startup.
+    pub always_on_top: bool,
+    pub maintenance: maintenance::MaintenancePolicy,
+    /// Allowlisted webview tweaks, applied when windows are created.
+    pub webview_flags: webview::WebviewFlags,
+    /// Locked-down mode for unattended displays.
+    pub kiosk_mode: bool,
+    pub kiosk: kiosk::KioskSettings,
+    pub response_cache: ResponseCacheConfig,
+    /// Index into the detected GPU list; the first usable GPU when unset.
+    pub selected_gpu: Option<usize>,
+    /// Path of the backend's OpenAPI document, relative to the API base URL.
+    pub openapi_path: String,
+    /// Environment to fail over to when the active backend is unreachable.
+    pub fallback_environment: Option<String>,
+    pub request_logging: RequestLogConfig,
+    /// BCP 47 tag overriding the OS locale, e.g. `de-AT`.
+    pub locale: Option<String>,
+    /// Fallback languages appended to the locale's `Accept-Language`, e.g. `fr,es`.
+    pub accept_language: Option<String>,
+    pub ui_density: UiDensity,
+    /// Logical size of the main window before minimal mode shrank it.
+    pub minimal_restore_size: Option<(u32, u32)>,
+    /// Set when the config came from an admin-signed import.
+    pub managed: Option<provisioning::ManagedConfig>,
+    /// Stop health polling while no app window has focus.
+    pub pause_on_blur: bool,
+    /// Keep temperature and swap checks running while paused.
+    pub critical_monitors_on_blur: bool,
+    /// Unset follows the OS accessibility settings.
+    pub accessibility: Option<AccessibilityConfig>,
+    pub rate_limit_mode: ratelimit::RateLimitMode,
+    /// Queued requests wait at most this long; longer cooldowns fail them.
+    pub rate_limit_max_wait_ms: u64,
+    /// Health checks give up after this long, well short of the request
+    /// timeout, so a backend that accepts connections but never answers
+    /// reads as slow rather than leaving the check pending.
+    pub health_check_timeout_ms: u64,
+}
+
+/// How the monitoring loop learns about backend health.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthTransport {
+    /// Request the health endpoint on every monitoring tick.
+    Poll,
+    /// Listen for pushed updates, polling only while the socket is down.
+    WebSocket,
+}
+
+/// How much the frontend shows; `Minimal` is a small pinned status widget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UiDensity {
+    #[default]
+    Comfortable,
+    Compact,
+    Minimal,
+}
+
+/// Price of a model in `currency` per million tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+/// A named backend deployment (e.g. local, staging, prod) behind its own base URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiEnvironment {
+    pub name: String,
+    pub base_url: String,
+    /// Extra headers sent with every request to this environment, e.g. gateway keys.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Overrides the locality-based default from [`ApiEnvironment::connect_timeout_ms`].
+    #[serde(default)]
+    pub connect_timeout_ms: Option<u64>,
+    /// Overrides the locality-based default from [`ApiEnvironment::request_timeout_ms`].
+    #[serde(default)]
+    pub request_timeout_ms: Option<u64>,
+}
+
+impl ApiEnvironment {
+    /// Whether the environment points at this machine (localhost or a loopback address).
+    pub fn is_local(&self) -> bool {
+        is_local_url(&self.base_url)
+    }
+
+    /// Local backends either answer quickly or aren't running, so they get a
+    /// short connect timeout; remote ones get room for slow links.
+    pub fn connect_timeout_ms(&self) -> u64 {
+        self.connect_timeout_ms
+            .unwrap_or(if self.is_local() { 2_000 } else { 10_000 })
+    }
+
+    pub fn request_timeout_ms(&self) -> u64 {
+        self.request_timeout_ms
+            .unwrap_or(if self.is_local() { 30_000 } else { 120_000 })
+    }
+}
+
+/// Settings for the shared outbound HTTP client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpClientConfig {
+    pub request_timeout_ms: u64,
+    pub connect_timeout_ms: u64,
+    /// Explicit proxy for all requests; system proxy settings apply when unset.
+    pub proxy_url: Option<String>,
+    /// PEM file holding a client certificate and private key for mutual TLS.
+    pub client_identity_pem: Option<String>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_ms: 30_000,
+            connect_timeout_ms: 10_000,
+            proxy_url: None,
+            client_identity_pem: None,
+        }
+    }
+}
+
+/// Opt-in cache for proxied GET responses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    /// Lifetime for responses without a `Cache-Control: max-age`.
+    pub default_ttl_secs: u64,
+    pub max_entries: usize,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_ttl_secs: 60,
+            max_entries: 256,
+        }
+    }
+}
+
+/// Verbose logging of proxied requests, for debugging backend integrations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestLogConfig {
+    pub enabled: bool,
+    pub log_headers: bool,
+    pub log_bodies: bool,
+    /// Header and body field names containing any of these are masked, on
+    /// top of the built-in secret patterns.
+    pub redact_patterns: Vec<String>,
+    /// Logged bodies are cut at this many bytes.
+    pub max_body_bytes: usize,
+}
+
+impl Default for RequestLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            log_headers: false,
+            log_bodies: false,
+            redact_patterns: vec!["auth".to_string(), "token".to_string(), "secret".to_string()],
+            max_body_bytes: 4096,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    pub high_contrast: bool,
+    pub reduce_motion: bool,
+    /// Multiplier on the base font size, 1.0 being the app default.
+    pub font_scale: f64,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self {
+            high_contrast: false,
+            reduce_motion: false,
+            font_scale: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackendKind {
+    StratMaster,
+    Ollama,
+    LlamaCpp,
+    OpenAiCompatible,
+}
+
+impl BackendKind {
+    /// Cheap endpoint that answers when a backend of this kind is up.
+    pub fn probe_path(&self) -> &'static str {
+        match self {
+            BackendKind::StratMaster => "/healthz",
+            BackendKind::Ollama => "/api/version",
+            BackendKind::LlamaCpp => "/health",
+            BackendKind::OpenAiCompatible => "/v1/models",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendConfig {
+    pub id: String,
+    pub name: String,
+    pub kind: BackendKind,
+    pub base_url: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +323,54 @@ pub struct CustomHardwareConfig {
     pub model_preference: String,
 }
 
+impl HardwareProfile {
+    /// Concrete settings for the profile; threads are capped at `cpu_count`.
+    ///
+    /// | Profile         | Memory   | Threads | GPU | Models |
+    /// |-----------------|----------|---------|-----|--------|
+    /// | HighPerformance | 16384 MB | 8       | yes | large  |
+    /// | Standard        | 8192 MB  | 4       | yes | medium |
+    /// | Lightweight     | 4096 MB  | 2       | no  | small  |
+    pub fn resolve(&self, cpu_count: usize) -> CustomHardwareConfig {
+        let (max_memory_mb, cpu_threads, enable_gpu, model_preference) = match self {
+            HardwareProfile::HighPerformance => (16_384, 8, true, "large"),
+            HardwareProfile::Standard => (8_192, 4, true, "medium"),
+            HardwareProfile::Lightweight => (4_096, 2, false, "small"),
+            HardwareProfile::Custom(custom) => return custom.clone(),
+        };
+        CustomHardwareConfig {
+            max_memory_mb,
+            cpu_threads: cpu_threads.min(cpu_count.max(1)),
+            enable_gpu,
+            model_preference: model_preference.to_string(),
+        }
+    }
+
+    /// Rejects custom settings this machine can't provide. Built-in profiles
+    /// always pass, as [`HardwareProfile::resolve`] fits them to the machine.
+    pub fn validate(&self, cpu_count: usize, memory_total_mb: u64) -> Result<()> {
+        let HardwareProfile::Custom(custom) = self else {
+            return Ok(());
+        };
+        if custom.cpu_threads == 0 || custom.cpu_threads > cpu_count {
+            return Err(AppError::ConfigError(format!(
+                "cpu_threads must be between 1 and {} (this machine's CPU count), got {}",
+                cpu_count, custom.cpu_threads
+            )));
+        }
+        if custom.max_memory_mb == 0 || custom.max_memory_mb > memory_total_mb {
+            return Err(AppError::ConfigError(format!(
+                "max_memory_mb must be between 1 and {} (installed memory), got {}",
+                memory_total_mb, custom.max_memory_mb
+            )));
+        }
+        if custom.model_preference.trim().is_empty() {
+            return Err(AppError::ConfigError("model_preference must not be empty".to_string()));
+        }
+        Ok(())
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -38,10 +378,187 @@ impl Default for AppConfig {
             auto_start_services: false,
             theme: "auto".to_string(),
             hardware_profile: HardwareProfile::Standard,
+            log_level: "info".to_string(),
+            backends: Vec::new(),
+            active_backend: None,
+            acknowledged_warnings: Vec::new(),
+            client_id: None,
+            http: HttpClientConfig::default(),
+            feature_flags: HashMap::new(),
+            environments: Vec::new(),
+            active_environment: None,
+            max_clipboard_bytes: 1024 * 1024,
+            metrics_listen_addr: None,
+            pricing: HashMap::new(),
+            cpu_temp_warning_celsius: 90.0,
+            credentials: Vec::new(),
+            ui_font: None,
+            event_batch_intervals_ms: HashMap::from([
+                ("service-log".to_string(), 100),
+                ("sse-chunk".to_string(), 50),
+                ("health-record".to_string(), 250),
+            ]),
+            health_transport: HealthTransport::Poll,
+            health_ws_path: "/ws/health".to_string(),
+            csp_connect_src: vec!["https://api.openai.com".to_string()],
+            services: Vec::new(),
+            service_recovery: services::RecoveryPolicy::default(),
+            status_endpoints: services::default_status_endpoints(),
+            always_on_top: false,
+            maintenance: maintenance::MaintenancePolicy::default(),
+            webview_flags: webview::WebviewFlags::default(),
+            kiosk_mode: false,
+            kiosk: kiosk::KioskSettings::default(),
+            response_cache: ResponseCacheConfig::default(),
+            selected_gpu: None,
+            openapi_path: "/openapi.json".to_string(),
+            fallback_environment: None,
+            request_logging: RequestLogConfig::default(),
+            locale: None,
+            accept_language: None,
+            ui_density: UiDensity::default(),
+            minimal_restore_size: None,
+            managed: None,
+            pause_on_blur: false,
+            critical_monitors_on_blur: true,
+            accessibility: None,
+            rate_limit_mode: ratelimit::RateLimitMode::default(),
+            rate_limit_max_wait_ms: 60_000,
+            health_check_timeout_ms: 5_000,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Reads the config from `path`, returning the defaults when the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AppError::FileSystemError(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| AppError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Checks values that would otherwise only fail later at runtime.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.api_base_url.starts_with("http://") || self.api_base_url.starts_with("https://")) {
+            return Err(AppError::ConfigError(format!(
+                "api_base_url must be an http(s) URL, got '{}'",
+                self.api_base_url
+            )));
+        }
+        if logging::parse_level(&self.log_level).is_none() {
+            return Err(AppError::ConfigError(format!("Unknown log_level '{}'", self.log_level)));
+        }
+        if self.health_check_timeout_ms == 0 {
+            return Err(AppError::ConfigError("health_check_timeout_ms must be greater than 0".to_string()));
+        }
+        if self.max_clipboard_bytes <= CLIPBOARD_TRUNCATION_MARKER.len() {
+            return Err(AppError::ConfigError(format!(
+                "max_clipboard_bytes must be larger than {}, got {}",
+                CLIPBOARD_TRUNCATION_MARKER.len(),
+                self.max_clipboard_bytes
+            )));
+        }
+        self.maintenance.validate().map_err(AppError::ConfigError)?;
+        Ok(())
+    }
+
+    /// Writes the config atomically, first rotating the current file into the
+    /// backup set unless the contents are unchanged.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::FileSystemError(format!("{}: {}", parent.display(), e)))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::ConfigError(e.to_string()))?;
+        let fs_error = |target: &Path, e: std::io::Error| AppError::FileSystemError(format!("{}: {}", target.display(), e));
+
+        if let Ok(current) = std::fs::read_to_string(path) {
+            if current != contents {
+                rotate_config_backups(path).map_err(|e| fs_error(path, e))?;
+            }
         }
+        let temp = PathBuf::from(format!("{}.partial", path.display()));
+        std::fs::write(&temp, contents).map_err(|e| fs_error(&temp, e))?;
+        std::fs::rename(&temp, path).map_err(|e| fs_error(path, e))
+    }
+
+    pub fn current_environment(&self) -> Option<&ApiEnvironment> {
+        let name = self.active_environment.as_ref()?;
+        self.environments.iter().find(|env| &env.name == name)
+    }
+
+    /// Connect timeout for the active environment, or the global HTTP setting.
+    pub fn connect_timeout_ms(&self) -> u64 {
+        self.current_environment()
+            .map(ApiEnvironment::connect_timeout_ms)
+            .unwrap_or(self.http.connect_timeout_ms)
+    }
+
+    /// Request timeout for the active environment, or the global HTTP setting.
+    pub fn request_timeout_ms(&self) -> u64 {
+        self.current_environment()
+            .map(ApiEnvironment::request_timeout_ms)
+            .unwrap_or(self.http.request_timeout_ms)
     }
 }
 
+// Config backups
+/// Previous versions kept next to the config as `config.json.1` (newest) to `config.json.5`.
+pub const CONFIG_BACKUP_COUNT: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub index: usize,
+    /// When this version was replaced, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub size_bytes: u64,
+}
+
+pub fn config_backup_path(path: &Path, index: usize) -> PathBuf {
+    PathBuf::from(format!("{}.{}", path.display(), index))
+}
+
+// Shifts `.1`..`.4` up by one (dropping `.5`), then copies the live config to
+// `.1` through a temporary. Every step is a rename, so a crash mid-rotation
+// leaves at worst a gap, never a truncated or missing set.
+fn rotate_config_backups(path: &Path) -> std::io::Result<()> {
+    for index in (1..CONFIG_BACKUP_COUNT).rev() {
+        let from = config_backup_path(path, index);
+        if from.exists() {
+            std::fs::rename(&from, config_backup_path(path, index + 1))?;
+        }
+    }
+    let newest = config_backup_path(path, 1);
+    let temp = PathBuf::from(format!("{}.partial", newest.display()));
+    std::fs::copy(path, &temp)?;
+    std::fs::rename(&temp, newest)
+}
+
+/// Existing backups of the config at `path`, newest first.
+pub fn list_config_backups(path: &Path) -> Vec<BackupEntry> {
+    (1..=CONFIG_BACKUP_COUNT)
+        .filter_map(|index| {
+            let metadata = std::fs::metadata(config_backup_path(path, index)).ok()?;
+            let timestamp_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_millis() as u64)
+                .unwrap_or(0);
+            Some(BackupEntry {
+                index,
+                timestamp_ms,
+                size_bytes: metadata.len(),
+            })
+        })
+        .collect()
+}
+
 // Error types for the application
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -58,4 +575,43 @@ pub enum AppError {
     SystemDetectionError(String),
 }
 
-pub type Result<T> = std::result::Result<T, AppError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, AppError>;
+
+/// Whether `url` points at this machine (localhost or a loopback address).
+pub fn is_local_url(url: &str) -> bool {
+    let Ok(url) = url::Url::parse(url) else {
+        return false;
+    };
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(address)) => address.is_loopback(),
+        Some(url::Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    }
+}
+
+/// Milliseconds since the Unix epoch, or 0 if the system clock is before it.
+pub fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(AppConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_a_clipboard_limit_without_room_for_the_marker() {
+        let config = AppConfig { max_clipboard_bytes: CLIPBOARD_TRUNCATION_MARKER.len(), ..AppConfig::default() };
+        assert!(config.validate().is_err());
+        let config = AppConfig { max_clipboard_bytes: 0, ..AppConfig::default() };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+// Lock files
+// The data-dir lock is an OS advisory lock on a file kept open for the life
+// of the process, so it is released the moment its holder exits or crashes
+// and there is no window in which two instances can both reclaim it. The
+// file also records who took it: a clean release empties it, so contents
+// found by the next holder mean the previous one crashed.
+
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+pub const DATA_DIR_LOCK_FILE: &str = "stratmaster.lock";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub created_ms: u64,
+    /// Executable file name, to spot a PID reused by another program.
+    pub exe: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleLock {
+    pub path: String,
+    /// `None` when the lock file could not be parsed.
+    pub owner: Option<LockOwner>,
+    pub reclaimed: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LockError {
+    /// Another live process holds the lock.
+    #[error("{path} is held by a running instance{}", .pid.map(|pid| format!(" (pid {})", pid)).unwrap_or_default())]
+    Held { path: String, pid: Option<u32> },
+    #[error("{0}")]
+    Io(String),
+}
+
+/// A held data-dir lock; dropping it without [`DataDirLock::release`] still
+/// frees the OS lock but leaves the file looking like a crash.
+#[derive(Debug)]
+pub struct DataDirLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl DataDirLock {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Empties the file and gives up the lock. The file itself stays: removing
+    /// it could delete one another instance has just locked.
+    pub fn release(self) {
+        let _ = self.file.set_len(0).and_then(|_| self.file.sync_all());
+        let _ = self.file.unlock();
+    }
+}
+
+fn current_exe_name() -> Option<String> {
+    Some(std::env::current_exe().ok()?.file_name()?.to_string_lossy().to_string())
+}
+
+fn is_contended(error: &std::io::Error) -> bool {
+    error.kind() == std::io::ErrorKind::WouldBlock || error.raw_os_error() == fs2::lock_contended_error().raw_os_error()
+}
+
+// The previous holder, if the file was left with contents
+fn read_previous(file: &mut File, path: &Path) -> Option<StaleLock> {
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).ok()?;
+    if contents.trim().is_empty() {
+        return None;
+    }
+    Some(StaleLock {
+        path: path.to_string_lossy().to_string(),
+        owner: serde_json::from_str(&contents).ok(),
+        reclaimed: false,
+    })
+}
+
+// Best effort: Windows locks keep other processes from reading the file
+fn read_holder(path: &Path) -> Option<u32> {
+    let owner: LockOwner = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    Some(owner.pid)
+}
+
+/// The lock at `path` if it exists, nobody holds it and its last holder
+/// didn't release it cleanly.
+pub fn inspect(path: &Path) -> Option<StaleLock> {
+    let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path).ok()?;
+    file.try_lock_shared().ok()?;
+    let stale = read_previous(&mut file, path);
+    let _ = file.unlock();
+    stale
+}
+
+/// Takes the lock at `path` for this process. Returns the lock left by a
+/// holder that crashed, if any; a lock held by a live process is
+/// [`LockError::Held`].
+pub fn acquire(path: &Path) -> Result<(DataDirLock, Option<StaleLock>), LockError> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| LockError::Io(format!("Failed to open {}: {}", path.display(), e)))?;
+    if let Err(e) = file.try_lock_exclusive() {
+        return Err(if is_contended(&e) {
+            LockError::Held {
+                path: path.to_string_lossy().to_string(),
+                pid: read_holder(path),
+            }
+        } else {
+            LockError::Io(format!("Failed to lock {}: {}", path.display(), e))
+        });
+    }
+    let reclaimed = read_previous(&mut file, path).map(|stale| StaleLock { reclaimed: true, ..stale });
+
+    let owner = LockOwner {
+        pid: std::process::id(),
+        created_ms: crate::unix_time_ms(),
+        exe: current_exe_name(),
+    };
+    let contents = serde_json::to_vec(&owner).map_err(|e| LockError::Io(format!("Failed to serialize lock: {}", e)))?;
+    file.set_len(0)
+        .and_then(|_| file.seek(SeekFrom::Start(0)))
+        .and_then(|_| file.write_all(&contents))
+        .and_then(|_| file.sync_all())
+        .map_err(|e| LockError::Io(format!("Failed to write {}: {}", path.display(), e)))?;
+    Ok((DataDirLock { path: path.to_path_buf(), file }, reclaimed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("stratmaster-locks-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn second_acquire_is_refused_while_held() {
+        let dir = TempDir::new("held");
+        let path = dir.0.join(DATA_DIR_LOCK_FILE);
+        let (lock, reclaimed) = acquire(&path).unwrap();
+        assert!(reclaimed.is_none());
+        match acquire(&path) {
+            Err(LockError::Held { pid, .. }) => {
+                // Unix locks are advisory, so the holder's record is readable
+                if cfg!(unix) {
+                    assert_eq!(pid, Some(std::process::id()));
+                }
+            }
+            other => panic!("expected a held lock, got {:?}", other.map(|(_, stale)| stale)),
+        }
+        assert!(inspect(&path).is_none());
+        lock.release();
+        assert!(acquire(&path).is_ok());
+    }
+
+    #[test]
+    fn clean_release_leaves_nothing_to_reclaim() {
+        let dir = TempDir::new("release");
+        let path = dir.0.join(DATA_DIR_LOCK_FILE);
+        acquire(&path).unwrap().0.release();
+        assert!(inspect(&path).is_none());
+        let (_lock, reclaimed) = acquire(&path).unwrap();
+        assert!(reclaimed.is_none());
+    }
+
+    #[test]
+    fn lock_left_by_a_crash_is_reclaimed() {
+        let dir = TempDir::new("crash");
+        let path = dir.0.join(DATA_DIR_LOCK_FILE);
+        std::fs::write(&path, r#"{"pid":4242,"created_ms":1,"exe":"stratmaster-desktop"}"#).unwrap();
+        let stale = inspect(&path).unwrap();
+        assert!(!stale.reclaimed);
+        assert_eq!(stale.owner.unwrap().pid, 4242);
+
+        let (_lock, reclaimed) = acquire(&path).unwrap();
+        let reclaimed = reclaimed.unwrap();
+        assert!(reclaimed.reclaimed);
+        assert_eq!(reclaimed.owner.unwrap().pid, 4242);
+        let owner: LockOwner = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(owner.pid, std::process::id());
+    }
+}
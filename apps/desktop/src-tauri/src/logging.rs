@@ -0,0 +1,239 @@
+// Application logging
+// Wraps env_logger with a runtime-adjustable level, an in-memory ring buffer
+// so recent log lines can be shown in the UI without reading files, and a
+// size-rotated log file in the app log directory.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const LOG_BUFFER_CAPACITY: usize = 2_000;
+pub const LOG_FILE_NAME: &str = "stratmaster.log";
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_LOG_FILES: usize = 3;
+
+static LOG_BUFFER: OnceLock<Mutex<VecDeque<LogEntry>>> = OnceLock::new();
+static LOG_FILE: Mutex<Option<LogFile>> = Mutex::new(None);
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { path, file, size })
+    }
+
+    fn write_entry(&mut self, entry: &LogEntry) {
+        let line = format_line(entry);
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.size += line.len() as u64;
+        }
+        if self.size >= MAX_LOG_FILE_BYTES {
+            self.rotate();
+        }
+    }
+
+    // stratmaster.log -> stratmaster.log.1 -> ... -> stratmaster.log.N (dropped)
+    fn rotate(&mut self) {
+        let rotated = |index: usize| PathBuf::from(format!("{}.{}", self.path.display(), index));
+        let _ = std::fs::remove_file(rotated(MAX_ROTATED_LOG_FILES));
+        for index in (1..MAX_ROTATED_LOG_FILES).rev() {
+            let _ = std::fs::rename(rotated(index), rotated(index + 1));
+        }
+        let _ = std::fs::rename(&self.path, rotated(1));
+        if let Ok(reopened) = LogFile::open(self.path.clone()) {
+            *self = reopened;
+        }
+    }
+}
+
+fn format_line(entry: &LogEntry) -> String {
+    let timestamp = chrono::DateTime::from_timestamp_millis(entry.timestamp_ms as i64)
+        .map(|t| t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+        .unwrap_or_default();
+    format!("{} {:<5} {}: {}\n", timestamp, entry.level, entry.target, entry.message)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp_ms: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+struct AppLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level() && self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        self.inner.log(record);
+
+        let entry = LogEntry {
+            timestamp_ms: crate::unix_time_ms(),
+            level: record.level().to_string(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        if let Some(log_file) = LOG_FILE.lock().unwrap().as_mut() {
+            log_file.write_entry(&entry);
+        }
+        let mut buffer = buffer().lock().unwrap();
+        if buffer.len() == LOG_BUFFER_CAPACITY {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+fn buffer() -> &'static Mutex<VecDeque<LogEntry>> {
+    LOG_BUFFER.get_or_init(|| Mutex::new(VecDeque::with_capacity(LOG_BUFFER_CAPACITY)))
+}
+
+/// Installs the global logger. Everything is passed through to env_logger
+/// (so `RUST_LOG` can still narrow individual modules) while the effective
+/// level is controlled by `log::max_level`, which can be changed at runtime.
+pub fn init(level: LevelFilter) {
+    let inner = env_logger::Builder::new()
+        .filter_level(LevelFilter::Trace)
+        .parse_default_env()
+        .build();
+
+    if log::set_boxed_logger(Box::new(AppLogger { inner })).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/// Starts mirroring log output to a rotating file in `dir`. Entries logged
+/// before the file was attached (early startup) are written first.
+pub fn attach_log_file(dir: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(LOG_FILE_NAME);
+    let mut log_file = LogFile::open(path.clone())?;
+    for entry in buffer().lock().unwrap().iter() {
+        log_file.write_entry(entry);
+    }
+    *LOG_FILE.lock().unwrap() = Some(log_file);
+    Ok(path)
+}
+
+/// The active log file followed by its rotated predecessors, newest first.
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let active = dir.join(LOG_FILE_NAME);
+    std::iter::once(active.clone())
+        .chain((1..=MAX_ROTATED_LOG_FILES).map(|index| PathBuf::from(format!("{}.{}", active.display(), index))))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Parses one of "error", "warn", "info", "debug" or "trace".
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    match level.to_ascii_lowercase().as_str() {
+        "error" => Some(LevelFilter::Error),
+        "warn" => Some(LevelFilter::Warn),
+        "info" => Some(LevelFilter::Info),
+        "debug" => Some(LevelFilter::Debug),
+        "trace" => Some(LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+pub fn current_level() -> LevelFilter {
+    log::max_level()
+}
+
+/// Returns buffered entries at or above `min_level`, newest last, keeping at most `limit`.
+pub fn recent_entries(min_level: LevelFilter, limit: usize) -> Vec<LogEntry> {
+    let buffer = buffer().lock().unwrap();
+    let matching: Vec<LogEntry> = buffer
+        .iter()
+        .filter(|entry| {
+            entry
+                .level
+                .parse::<log::Level>()
+                .map(|level| level <= min_level)
+                .unwrap_or(true)
+        })
+        .cloned()
+        .collect();
+    let skip = matching.len().saturating_sub(limit);
+    matching.into_iter().skip(skip).collect()
+}
+
+/// Path of the file log output is currently mirrored to, once attached.
+pub fn active_log_file() -> Option<PathBuf> {
+    LOG_FILE.lock().unwrap().as_ref().map(|log_file| log_file.path.clone())
+}
+
+/// Follows a log file as it grows, like `tail -F`. After a rotation the old
+/// handle still points at the renamed file, so it is drained before the new
+/// file at `path` is opened from the start.
+pub struct LogTail {
+    path: PathBuf,
+    file: File,
+    position: u64,
+    partial: String,
+}
+
+impl LogTail {
+    /// Starts at the end of the file; existing lines are not replayed.
+    pub fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = File::open(&path)?;
+        let position = file.metadata()?.len();
+        Ok(Self { path, file, position, partial: String::new() })
+    }
+
+    /// Complete lines written since the last call, oldest first.
+    pub fn read_lines(&mut self) -> std::io::Result<Vec<String>> {
+        let mut lines = self.drain()?;
+        // Shorter than what we've read means the file at `path` is a new one
+        let rotated = std::fs::metadata(&self.path)
+            .map(|metadata| metadata.len() < self.position)
+            .unwrap_or(false);
+        if rotated {
+            self.file = File::open(&self.path)?;
+            self.position = 0;
+            lines.extend(self.drain()?);
+        }
+        Ok(lines)
+    }
+
+    fn drain(&mut self) -> std::io::Result<Vec<String>> {
+        use std::io::{Read, Seek, SeekFrom};
+        self.file.seek(SeekFrom::Start(self.position))?;
+        let mut bytes = Vec::new();
+        self.position += self.file.read_to_end(&mut bytes)? as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&bytes));
+
+        let Some(end) = self.partial.rfind('\n') else {
+            return Ok(Vec::new());
+        };
+        let complete: String = self.partial.drain(..=end).collect();
+        Ok(complete.lines().map(str::to_string).collect())
+    }
+}
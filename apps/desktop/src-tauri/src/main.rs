@@ -1,14 +1,56 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use log::{info, warn, error};
+use log::{debug, info, warn, error};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager, State, Window};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use stratmaster_desktop::bridge::{self, BridgeEcho};
+use stratmaster_desktop::credentials::{self, CredentialError, CredentialErrorKind};
+use stratmaster_desktop::configdiff::{self, ConfigDiff};
+use stratmaster_desktop::containers::{self, ContainerRuntime, ContainerSpec, RunningContainer};
+use stratmaster_desktop::control::{self, ControlCommand, ControlRequest, ControlResponse};
+use stratmaster_desktop::csp::{self, MixedContentCheck};
+use stratmaster_desktop::datadir;
+use stratmaster_desktop::deeplink::{self, DeepLink};
+use stratmaster_desktop::discovery::{self, DiscoveredBackend};
+use stratmaster_desktop::kiosk::{self, KioskStatus};
+use stratmaster_desktop::locks::{self, DataDirLock, LockError, StaleLock};
+use stratmaster_desktop::maintenance::{self, Freed, MaintenancePolicy};
+use stratmaster_desktop::metrics::{MetricsSnapshot, ServiceMetrics};
+use stratmaster_desktop::models::{self, CostEstimate, ModelFit, ModelInfo};
+use stratmaster_desktop::net::{self, RequestTiming, ResolveResult, TlsCertInfo};
+use stratmaster_desktop::power::{self, PowerEvent, PowerMode};
+use stratmaster_desktop::provisioning::{self, ManagedConfig, ManagedStatus};
+use stratmaster_desktop::ratelimit::{self, RateLimitMode, RateLimitState};
+use stratmaster_desktop::sse::{SseEvent, SseParser};
+use stratmaster_desktop::services::{self, HopLatency, ReadyCheck, RecoveryPolicy, ServiceDefinition, ServiceFailure, ServiceIssue, StartResult, StatusEndpoint};
+use stratmaster_desktop::support::{self, DiagnosticsSummary};
+use stratmaster_desktop::updates;
+use stratmaster_desktop::system::{self, CpuFeatures, DiskBenchmark, FilesystemInfo, FirewallStatus, GpuDescriptor, GpuDriverInfo, HardwareInventory, LocaleInfo, OsUpdate, PermissionReport, PortOwner, SwapInfo, VramHeadroom};
+use stratmaster_desktop::webview::{WebviewFlagState, WebviewFlags};
+use stratmaster_desktop::{
+    config_backup_path, is_local_url, logging, redact, unix_time_ms, AccessibilityConfig, ApiEnvironment, AppConfig,
+    BackendConfig, BackendKind, BackupEntry, HardwareProfile, HealthTransport, HttpClientConfig, ModelPricing,
+    RequestLogConfig, ResponseCacheConfig, UiDensity, CLIPBOARD_TRUNCATION_MARKER, CONFIG_BACKUP_COUNT,
+};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use notify::{EventKind, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_notification::{NotificationExt, PermissionState};
 
 // External dependencies
-use sys_info;
 use num_cpus;
 use webbrowser;
 
@@ -17,200 +59,8335 @@ use webbrowser;
 struct AppState {
     api_base_url: std::sync::Mutex<String>,
     health_status: std::sync::Mutex<HashMap<String, bool>>,
+    retry_policy: std::sync::Mutex<RetryPolicy>,
+    config: std::sync::Mutex<AppConfig>,
+    health_history: std::sync::Mutex<VecDeque<HealthRecord>>,
+    health_counts: std::sync::Mutex<HashMap<String, HealthCounts>>,
+    model_cache: std::sync::Mutex<Option<(Instant, Vec<ModelInfo>)>>,
+    request_limiter: RequestLimiter,
+    config_watcher: std::sync::Mutex<Option<notify::RecommendedWatcher>>,
+    startup: std::sync::Mutex<StartupTracker>,
+    backend_kind: std::sync::Mutex<Option<BackendKind>>,
+    backend_warnings: std::sync::Mutex<Vec<BackendWarning>>,
+    http_client: std::sync::Mutex<Option<reqwest::Client>>,
+    remote_feature_flags: std::sync::Mutex<HashMap<String, bool>>,
+    pending_deep_link: std::sync::Mutex<Option<DeepLink>>,
+    system_fonts: std::sync::Mutex<Option<Vec<String>>>,
+    event_batcher: EventBatcher,
+    sse_streams: std::sync::Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>,
+    health_ws_task: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    health_ws_connected: std::sync::atomic::AtomicBool,
+    request_recording: std::sync::Mutex<Option<RequestRecording>>,
+    active_requests: RequestRegistry,
+    csp: AppliedCsp,
+    last_swap: std::sync::Mutex<Option<SwapInfo>>,
+    command_metrics: Arc<CommandMetrics>,
+    local_services: std::sync::Mutex<HashMap<String, tokio::process::Child>>,
+    always_on_top_items: std::sync::Mutex<Vec<CheckMenuItem<tauri::Wry>>>,
+    perf_trace: std::sync::Mutex<Option<PerfTrace>>,
+    response_cache: ResponseCache,
+    gpus: std::sync::Mutex<Option<Vec<GpuDescriptor>>>,
+    log_follow_task: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    openapi_cache: std::sync::Mutex<Option<(Instant, String, serde_json::Value)>>,
+    system_sleeping: std::sync::atomic::AtomicBool,
+    failover_until: std::sync::Mutex<Option<Instant>>,
+    webview_flags: WebviewFlags,
+    system_locale: std::sync::Mutex<Option<LocaleInfo>>,
+    power_mode: std::sync::Mutex<PowerMode>,
+    power_mode_read_at: std::sync::Mutex<Option<Instant>>,
+    frame_timings: FrameTimings,
+    hardware_survey: std::sync::Mutex<Option<HardwareInventory>>,
+    monitoring_paused: std::sync::atomic::AtomicBool,
+    /// Containers started by `start_service_container`, by service name.
+    service_containers: std::sync::Mutex<HashMap<String, RunningContainer>>,
+    container_logs: ContainerLogs,
+    control_socket: std::sync::Mutex<Option<ControlSocket>>,
+    /// The data-dir lock this process holds.
+    data_dir_lock: std::sync::Mutex<Option<DataDirLock>>,
+    /// Stale locks reclaimed at startup.
+    reclaimed_locks: std::sync::Mutex<Vec<StaleLock>>,
+    service_recovery: std::sync::Mutex<HashMap<String, RecoveryState>>,
+    /// Active cooldowns from rate-limit responses, by host.
+    rate_limits: std::sync::Mutex<HashMap<String, RateLimitState>>,
+    /// Loop started by `start_health_monitor`.
+    health_monitor: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+static PROCESS_START: std::sync::OnceLock<Instant> = std::sync::OnceLock::new();
+
+fn process_start() -> Instant {
+    *PROCESS_START.get_or_init(Instant::now)
+}
+
+// Startup milestones, recorded as offsets from process start
+#[derive(Debug, Default)]
+struct StartupTracker {
+    logger_init: Option<Duration>,
+    state_init: Option<Duration>,
+    setup_complete: Option<Duration>,
+    first_window_shown: Option<Duration>,
+    backend_ready: Option<Duration>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StartupMetrics {
+    logger_init_ms: Option<u64>,
+    state_init_ms: Option<u64>,
+    setup_ms: Option<u64>,
+    first_window_ms: Option<u64>,
+    backend_ready_ms: Option<u64>,
+    // Time between the first window appearing and the backend becoming usable
+    window_to_ready_ms: Option<u64>,
+}
+
+impl StartupTracker {
+    fn metrics(&self) -> StartupMetrics {
+        let ms = |d: Option<Duration>| d.map(|d| d.as_millis() as u64);
+        let phase = |from: Option<Duration>, to: Option<Duration>| match (from, to) {
+            (Some(from), Some(to)) => Some(to.saturating_sub(from).as_millis() as u64),
+            _ => None,
+        };
+        StartupMetrics {
+            logger_init_ms: ms(self.logger_init),
+            state_init_ms: phase(self.logger_init, self.state_init),
+            setup_ms: phase(self.state_init, self.setup_complete),
+            first_window_ms: ms(self.first_window_shown),
+            backend_ready_ms: ms(self.backend_ready),
+            window_to_ready_ms: phase(self.first_window_shown, self.backend_ready),
+        }
+    }
+}
+
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 8;
+
+// Bounds the number of simultaneous backend requests; callers beyond the
+// limit wait for a permit instead of failing.
+struct RequestLimiter {
+    semaphore: Arc<tokio::sync::Semaphore>,
+    limit: std::sync::Mutex<usize>,
+    inflight: Arc<AtomicUsize>,
+    total: AtomicU64,
+}
+
+impl Default for RequestLimiter {
+    fn default() -> Self {
+        Self {
+            semaphore: Arc::new(tokio::sync::Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            limit: std::sync::Mutex::new(DEFAULT_MAX_CONCURRENT_REQUESTS),
+            inflight: Arc::new(AtomicUsize::new(0)),
+            total: AtomicU64::new(0),
+        }
+    }
+}
+
+struct InflightPermit {
+    _permit: tokio::sync::OwnedSemaphorePermit,
+    inflight: Arc<AtomicUsize>,
+}
+
+impl Drop for InflightPermit {
+    fn drop(&mut self) {
+        self.inflight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl RequestLimiter {
+    async fn acquire(&self) -> Result<InflightPermit, String> {
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| "Request limiter was closed".to_string())?;
+        self.inflight.fetch_add(1, Ordering::SeqCst);
+        self.total.fetch_add(1, Ordering::Relaxed);
+        Ok(InflightPermit {
+            _permit: permit,
+            inflight: self.inflight.clone(),
+        })
+    }
+
+    // Resizes the one semaphore, so waiters keep their place in the queue.
+    // Lowering takes idle permits at once; permits still held by running
+    // requests are taken as those requests finish.
+    fn set_limit(&self, max_concurrent: usize) {
+        let mut limit = self.limit.lock().unwrap();
+        if max_concurrent > *limit {
+            self.semaphore.add_permits(max_concurrent - *limit);
+        } else if max_concurrent < *limit {
+            let excess = *limit - max_concurrent;
+            let outstanding = excess - self.semaphore.forget_permits(excess);
+            if outstanding > 0 {
+                let semaphore = self.semaphore.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Ok(permits) = semaphore.acquire_many_owned(outstanding as u32).await {
+                        permits.forget();
+                    }
+                });
+            }
+        }
+        *limit = max_concurrent;
+    }
+}
+
+#[cfg(test)]
+mod request_limiter_tests {
+    use super::RequestLimiter;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    // Runs `count` requests through the limiter, returning how many completed
+    // and the most that ran at once
+    async fn run_requests(limiter: Arc<RequestLimiter>, count: usize) -> (usize, usize) {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..count)
+            .map(|_| {
+                let (limiter, running, peak) = (limiter.clone(), running.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let _permit = limiter.acquire().await.unwrap();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        let mut completed = 0;
+        for task in tasks {
+            task.await.unwrap();
+            completed += 1;
+        }
+        (completed, peak.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn queues_requests_beyond_the_limit() {
+        let limiter = Arc::new(RequestLimiter::default());
+        limiter.set_limit(3);
+        let (completed, peak) = run_requests(limiter, 20).await;
+        assert_eq!(completed, 20);
+        assert!(peak <= 3, "{} requests ran at once", peak);
+    }
+
+    #[tokio::test]
+    async fn lowering_the_limit_while_busy_still_bounds_concurrency() {
+        let limiter = Arc::new(RequestLimiter::default());
+        let busy: Vec<_> = {
+            let mut permits = Vec::new();
+            for _ in 0..super::DEFAULT_MAX_CONCURRENT_REQUESTS {
+                permits.push(limiter.acquire().await.unwrap());
+            }
+            permits
+        };
+        limiter.set_limit(2);
+        drop(busy);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let (completed, peak) = run_requests(limiter.clone(), 10).await;
+        assert_eq!(completed, 10);
+        assert!(peak <= 2, "{} requests ran at once", peak);
+        assert_eq!(limiter.semaphore.available_permits(), 2);
+    }
+}
+
+const REQUEST_CANCELLED: &str = "Request cancelled";
+
+// In-flight backend requests, so switching environment or quitting can abandon
+// them. Aborting drops the wrapped future, which drops the reqwest connection.
+#[derive(Default)]
+struct RequestRegistry {
+    next_id: AtomicU64,
+    handles: std::sync::Mutex<HashMap<u64, futures_util::future::AbortHandle>>,
+}
+
+struct RegisteredRequest<'a> {
+    registry: &'a RequestRegistry,
+    id: u64,
+}
+
+impl Drop for RegisteredRequest<'_> {
+    fn drop(&mut self) {
+        self.registry.handles.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl RequestRegistry {
+    async fn run<T>(&self, request: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+        let (handle, registration) = futures_util::future::AbortHandle::new_pair();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.handles.lock().unwrap().insert(id, handle);
+        let _registered = RegisteredRequest { registry: self, id };
+        futures_util::future::Abortable::new(request, registration)
+            .await
+            .unwrap_or_else(|_| Err(REQUEST_CANCELLED.to_string()))
+    }
+
+    fn cancel_all(&self) -> u32 {
+        let handles: Vec<_> = self.handles.lock().unwrap().drain().map(|(_, handle)| handle).collect();
+        for handle in &handles {
+            handle.abort();
+        }
+        handles.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod request_registry_tests {
+    use super::{mock_http, RequestRegistry, REQUEST_CANCELLED};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn cancelled_requests_resolve_as_cancelled() {
+        let url = mock_http::serve(vec![(Duration::from_secs(30), mock_http::response("200 OK", &[], "{}"))]).await;
+        let registry = Arc::new(RequestRegistry::default());
+        let started = Instant::now();
+        let request = tokio::spawn({
+            let registry = registry.clone();
+            async move {
+                registry
+                    .run(async { reqwest::get(&url).await.map(|response| response.status()).map_err(|e| e.to_string()) })
+                    .await
+            }
+        });
+        while registry.handles.lock().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(registry.cancel_all(), 1);
+        assert_eq!(request.await.unwrap(), Err(REQUEST_CANCELLED.to_string()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(registry.handles.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn finished_requests_are_not_counted() {
+        let registry = RequestRegistry::default();
+        assert_eq!(registry.run(async { Ok::<_, String>(1) }).await, Ok(1));
+        assert_eq!(registry.cancel_all(), 0);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProxyResponse {
+    status: u16,
+    body: serde_json::Value,
+    /// Environment name, or base URL, of the backend that answered.
+    #[serde(default)]
+    served_by: String,
+    /// Answered by the fallback environment instead of the primary.
+    #[serde(default)]
+    degraded: bool,
+    // Only read by the response cache
+    #[serde(skip)]
+    cache_control: Option<String>,
+    // Why this request just failed over; unset while already on the fallback
+    #[serde(skip)]
+    failover_reason: Option<String>,
+}
+
+const MODEL_CACHE_TTL: Duration = Duration::from_secs(300);
+
+const HEALTH_HISTORY_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HealthRecord {
+    timestamp_ms: u64,
+    service: String,
+    healthy: bool,
+    latency_ms: Option<u64>,
+    detail: Option<String>,
+}
+
+// Lifetime totals; the history ring buffer forgets old checks
+#[derive(Debug, Default, Clone, Copy)]
+struct HealthCounts {
+    checks: u64,
+    failures: u64,
+}
+
+// The enclosing function's name, read from the type name of an item declared
+// inside it; async bodies add `{{closure}}` segments, which are skipped
+macro_rules! command_name {
+    () => {{
+        fn f() {}
+        let name = std::any::type_name_of_val(&f);
+        let name = name.strip_suffix("::f").unwrap_or(name);
+        let name = name.trim_end_matches("::{{closure}}");
+        name.rsplit("::").next().unwrap_or(name)
+    }};
+}
+
+// Times the rest of the enclosing command under its own name
+macro_rules! time_command {
+    ($state:expr) => {
+        $state.time_command(command_name!())
+    };
+}
+
+impl AppState {
+    fn time_command(&self, command: &'static str) -> CommandTimer {
+        CommandTimer {
+            metrics: self.command_metrics.clone(),
+            command,
+            started: Instant::now(),
+        }
+    }
+
+    fn record_health(&self, service: &str, healthy: bool, latency: Option<Duration>, detail: Option<String>) -> HealthRecord {
+        {
+            let mut counts = self.health_counts.lock().unwrap();
+            let counts = counts.entry(service.to_string()).or_default();
+            counts.checks += 1;
+            if !healthy {
+                counts.failures += 1;
+            }
+        }
+        let mut history = self.health_history.lock().unwrap();
+        if history.len() == HEALTH_HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        let record = HealthRecord {
+            timestamp_ms: unix_time_ms(),
+            service: service.to_string(),
+            healthy,
+            latency_ms: latency.map(|latency| latency.as_millis() as u64),
+            detail,
+        };
+        history.push_back(record.clone());
+        record
+    }
+}
+
+// Retry behaviour shared by every outbound HTTP helper
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RetryPolicy {
+    max_retries: u32,
+    base_delay_ms: u64,
+    max_delay_ms: u64,
+    jitter: bool,
+    /// Return timeouts at once instead of retrying them, so the request
+    /// timeout bounds the whole call. Set per call, never persisted.
+    #[serde(skip)]
+    no_timeout_retries: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 200,
+            max_delay_ms: 5_000,
+            jitter: true,
+            no_timeout_retries: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Health probes report a hung backend as timed out after one timeout,
+    // not after every retry of it
+    fn for_health_probe(&self) -> Self {
+        Self { no_timeout_retries: true, ..self.clone() }
+    }
+
+    /// Exponential backoff for the given (zero-based) retry attempt, never exceeding `max_delay_ms`.
+    fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay_ms
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(self.max_delay_ms);
+        let delay_ms = if self.jitter && exponential > 0 {
+            rand::thread_rng().gen_range(exponential / 2..=exponential)
+        } else {
+            exponential
+        };
+        Duration::from_millis(delay_ms)
+    }
+}
+
+#[cfg(test)]
+mod retry_policy_tests {
+    use super::RetryPolicy;
+    use std::time::Duration;
+
+    fn policy(jitter: bool) -> RetryPolicy {
+        RetryPolicy { max_retries: 10, base_delay_ms: 200, max_delay_ms: 1_000, jitter, no_timeout_retries: false }
+    }
+
+    #[test]
+    fn doubles_each_attempt() {
+        let policy = policy(false);
+        assert_eq!(policy.delay_for_attempt(0), Duration::from_millis(200));
+        assert_eq!(policy.delay_for_attempt(1), Duration::from_millis(400));
+        assert_eq!(policy.delay_for_attempt(2), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn never_exceeds_max_delay() {
+        for jitter in [false, true] {
+            let policy = policy(jitter);
+            for attempt in 0..64 {
+                assert!(policy.delay_for_attempt(attempt) <= Duration::from_millis(policy.max_delay_ms));
+            }
+        }
+        assert_eq!(policy(false).delay_for_attempt(u32::MAX), Duration::from_millis(1_000));
+    }
+
+    #[test]
+    fn jitter_keeps_at_least_half() {
+        let policy = policy(true);
+        for _ in 0..100 {
+            let delay = policy.delay_for_attempt(1);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400), "{:?}", delay);
+        }
+    }
+}
+
+// Outbound HTTP
+const HTTP_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+const WARM_CONNECTIONS: usize = 2;
+
+fn user_agent() -> String {
+    format!(
+        "StratMaster-Desktop/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+fn generate_client_id() -> String {
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Every outbound client identifies itself with the desktop User-Agent and the
+// install's client ID, asks for the user's language, and applies the
+// configured timeouts (the active environment's, when one is selected), proxy
+// and mTLS identity.
+fn build_http_client(config: &AppConfig, accept_language: &str) -> Result<reqwest::Client, String> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(client_id) = &config.client_id {
+        if let Ok(value) = reqwest::header::HeaderValue::from_str(client_id) {
+            headers.insert("X-Client-Id", value);
+        }
+    }
+    if let Ok(value) = reqwest::header::HeaderValue::from_str(accept_language) {
+        headers.insert(reqwest::header::ACCEPT_LANGUAGE, value);
+    }
+
+    let http = &config.http;
+    let mut builder = reqwest::Client::builder()
+        .user_agent(user_agent())
+        .default_headers(headers)
+        .timeout(Duration::from_millis(config.request_timeout_ms()))
+        .connect_timeout(Duration::from_millis(config.connect_timeout_ms()))
+        .pool_idle_timeout(HTTP_POOL_IDLE_TIMEOUT);
+
+    if let Some(proxy_url) = &http.proxy_url {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| format!("Invalid proxy URL {}: {}", proxy_url, e))?;
+        builder = builder.proxy(proxy);
+    }
+    if let Some(identity_path) = &http.client_identity_pem {
+        let pem = std::fs::read(identity_path)
+            .map_err(|e| format!("Failed to read client identity {}: {}", identity_path, e))?;
+        let identity = reqwest::Identity::from_pem(&pem)
+            .map_err(|e| format!("Invalid client identity {}: {}", identity_path, e))?;
+        builder = builder.use_rustls_tls().identity(identity);
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+#[cfg(test)]
+mod client_header_tests {
+    use super::{build_http_client, generate_client_id, user_agent, AppConfig};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn user_agent_names_version_platform_and_arch() {
+        let expected = format!(
+            "StratMaster-Desktop/{} ({}; {})",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+        assert_eq!(user_agent(), expected);
+    }
+
+    #[test]
+    fn client_id_is_128_bits_of_hex() {
+        let id = generate_client_id();
+        assert_eq!(id.len(), 32);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()), "{}", id);
+        assert_ne!(id, generate_client_id());
+    }
+
+    #[tokio::test]
+    async fn requests_carry_the_identifying_headers() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let _ = stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").await;
+            String::from_utf8(request).unwrap().to_ascii_lowercase()
+        });
+
+        let config = AppConfig { client_id: Some("0123456789abcdef0123456789abcdef".to_string()), ..AppConfig::default() };
+        let client = build_http_client(&config, "en-GB").unwrap();
+        client.get(&url).send().await.unwrap();
+        let request = server.await.unwrap();
+        assert!(request.contains(&format!("user-agent: {}", user_agent().to_ascii_lowercase())), "{}", request);
+        assert!(request.contains("x-client-id: 0123456789abcdef0123456789abcdef"), "{}", request);
+        assert!(request.contains("accept-language: en-gb"), "{}", request);
+    }
+}
+
+#[cfg(test)]
+mod environment_timeout_tests {
+    use super::{build_http_client, mock_http, ApiEnvironment, AppConfig};
+    use std::time::{Duration, Instant};
+
+    fn config_with_environment(base_url: &str, request_timeout_ms: u64, global_timeout_ms: u64) -> AppConfig {
+        let mut config = AppConfig::default();
+        config.http.request_timeout_ms = global_timeout_ms;
+        config.environments.push(ApiEnvironment {
+            name: "slow".to_string(),
+            base_url: base_url.to_string(),
+            headers: Default::default(),
+            connect_timeout_ms: None,
+            request_timeout_ms: Some(request_timeout_ms),
+        });
+        config.active_environment = Some("slow".to_string());
+        config
+    }
+
+    async fn slow_server(delay: Duration) -> String {
+        mock_http::serve(vec![(delay, mock_http::response("200 OK", &[], "{}"))]).await
+    }
+
+    #[tokio::test]
+    async fn a_short_environment_timeout_wins_over_the_global_one() {
+        let url = slow_server(Duration::from_secs(5)).await;
+        let client = build_http_client(&config_with_environment(&url, 200, 60_000), "en").unwrap();
+        let started = Instant::now();
+        let error = client.get(&url).send().await.unwrap_err();
+        assert!(error.is_timeout(), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(2), "{:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn a_long_environment_timeout_wins_over_the_global_one() {
+        let url = slow_server(Duration::from_millis(300)).await;
+        let client = build_http_client(&config_with_environment(&url, 5_000, 50), "en").unwrap();
+        assert!(client.get(&url).send().await.unwrap().status().is_success());
+    }
+}
+
+// One client (and connection pool) is shared by all commands so keep-alive
+// connections are reused instead of paying connection setup on every call.
+// It is built on first use and rebuilt after `reset_http_client`.
+fn http_client(state: &AppState) -> Result<reqwest::Client, String> {
+    let mut shared = state.http_client.lock().unwrap();
+    if let Some(client) = shared.as_ref() {
+        return Ok(client.clone());
+    }
+    let config = state.config.lock().unwrap().clone();
+    let client = build_http_client(&config, &accept_language(state))?;
+    *shared = Some(client.clone());
+    Ok(client)
+}
+
+fn reset_http_client(state: &AppState) {
+    *state.http_client.lock().unwrap() = None;
+}
+
+// reqwest resolves through the system resolver for each new connection, so
+// a stale address lives on in pooled connections; dropping the client
+// drops them and the next request resolves afresh.
+fn flush_dns(state: &AppState, reason: &str) {
+    reset_http_client(state);
+    info!("Flushed HTTP connections and cached addresses ({})", reason);
+}
+
+#[tauri::command]
+async fn flush_dns_cache(state: State<'_, AppState>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    flush_dns(&state, "requested");
+    // Rebuild now so a broken client config is reported here, not on the next request
+    http_client(&state).map(|_| ())
+}
+
+// What a new connection would use. Behind a configured proxy the proxy
+// resolves the host instead.
+#[tauri::command]
+async fn inspect_resolved_addresses(state: State<'_, AppState>, host: String) -> Result<Vec<String>, String> {
+    let _timing = time_command!(state);
+    let result = net::resolve_host(&host, Duration::from_secs(10)).await?;
+    Ok(result.addresses)
+}
+
+#[tauri::command]
+async fn get_http_client_config(state: State<'_, AppState>) -> Result<HttpClientConfig, String> {
+    let _timing = time_command!(state);
+    Ok(state.config.lock().unwrap().http.clone())
+}
+
+#[tauri::command]
+async fn set_http_client_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: HttpClientConfig,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let updated = apply_http_client_config(&state, config)?;
+    persist_config(&app, &updated)
+}
+
+// Swaps in the new settings under the config lock, putting the old ones back
+// if a client can't be built from them, so nothing else is lost to a
+// concurrent update.
+fn apply_http_client_config(state: &AppState, config: HttpClientConfig) -> Result<AppConfig, String> {
+    let accept_language = accept_language(state);
+    let updated = {
+        let mut current = state.config.lock().unwrap();
+        let previous = std::mem::replace(&mut current.http, config);
+        if let Err(e) = build_http_client(&current, &accept_language) {
+            current.http = previous;
+            return Err(e);
+        }
+        info!("Updating HTTP client settings: {:?}", current.http);
+        current.clone()
+    };
+    reset_http_client(state);
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod http_client_tests {
+    use super::{apply_http_client_config, http_client, reset_http_client, AppState, HttpClientConfig};
+
+    fn invalid_proxy() -> HttpClientConfig {
+        HttpClientConfig { proxy_url: Some("not a proxy".to_string()), ..HttpClientConfig::default() }
+    }
+
+    #[test]
+    fn repeated_calls_reuse_the_cached_client() {
+        let state = AppState::default();
+        http_client(&state).unwrap();
+        // A fresh build would fail on this proxy; the cached client is returned instead
+        state.config.lock().unwrap().http = invalid_proxy();
+        assert!(http_client(&state).is_ok());
+        reset_http_client(&state);
+        assert!(http_client(&state).is_err());
+    }
+
+    #[test]
+    fn invalid_settings_leave_the_config_unchanged() {
+        let state = AppState::default();
+        state.config.lock().unwrap().http.request_timeout_ms = 1234;
+        assert!(apply_http_client_config(&state, invalid_proxy()).is_err());
+        let config = state.config.lock().unwrap();
+        assert_eq!(config.http.request_timeout_ms, 1234);
+        assert!(config.http.proxy_url.is_none());
+    }
+}
+
+/// Prefix of `send_with_retry` errors for requests that ran out of time, as
+/// opposed to ones that could not connect.
+const REQUEST_TIMED_OUT: &str = "Request timed out";
+
+// "5s", or milliseconds for timeouts that aren't whole seconds
+fn format_timeout(timeout: Duration) -> String {
+    if timeout.subsec_millis() == 0 {
+        format!("{}s", timeout.as_secs())
+    } else {
+        format!("{}ms", timeout.as_millis())
+    }
+}
+
+// Sends a request to `url` built by `make_request`, retrying connection
+// failures, timeouts (unless `no_timeout_retries`) and 5xx responses
+// according to the policy. The last response or error is returned.
+// Requests to a rate-limited host wait out (or fail on) its cooldown first,
+// and a queued 429 is retried once the cooldown ends.
+async fn send_with_retry<F, Fut>(app: &AppHandle, policy: &RetryPolicy, url: &str, mut make_request: F) -> Result<reqwest::Response, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = reqwest::Result<reqwest::Response>>,
+{
+    let host = url::Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string));
+    let mut attempt = 0;
+    loop {
+        if let Some(host) = &host {
+            wait_for_rate_limit(app, host).await?;
+        }
+        let mut rate_limited = false;
+        let retriable = match make_request().await {
+            Ok(response) => {
+                rate_limited = host.as_deref().is_some_and(|host| note_rate_limit(app, host, &response));
+                let queued = app.state::<AppState>().config.lock().unwrap().rate_limit_mode == RateLimitMode::Queue;
+                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && queued && attempt < policy.max_retries {
+                    "rate limited".to_string()
+                } else if response.status().is_server_error() && attempt < policy.max_retries {
+                    format!("status {}", response.status())
+                } else {
+                    return Ok(response);
+                }
+            }
+            Err(e) if e.is_timeout() && policy.no_timeout_retries => return Err(format!("{}: {}", REQUEST_TIMED_OUT, e)),
+            Err(e) if (e.is_connect() || e.is_timeout()) && attempt < policy.max_retries => e.to_string(),
+            Err(e) if e.is_timeout() => return Err(format!("{}: {}", REQUEST_TIMED_OUT, e)),
+            Err(e) => return Err(e.to_string()),
+        };
+        // A cooldown is waited out at the top of the loop instead
+        let delay = if rate_limited { Duration::ZERO } else { policy.delay_for_attempt(attempt) };
+        warn!(
+            "Request failed ({}), retrying in {}ms (attempt {}/{})",
+            retriable,
+            delay.as_millis(),
+            attempt + 1,
+            policy.max_retries
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+// Rate limits
+fn active_rate_limit(state: &AppState, host: &str) -> Option<RateLimitState> {
+    let mut limits = state.rate_limits.lock().unwrap();
+    match limits.get(host) {
+        Some(limit) if limit.reset_ms > unix_time_ms() => Some(limit.clone()),
+        Some(_) => {
+            limits.remove(host);
+            None
+        }
+        None => None,
+    }
+}
+
+async fn wait_for_rate_limit(app: &AppHandle, host: &str) -> Result<(), String> {
+    let wait = rate_limit_wait(&app.state::<AppState>(), host)?;
+    if !wait.is_zero() {
+        debug!("Waiting {}ms for the rate limit on {} to reset", wait.as_millis(), host);
+        tokio::time::sleep(wait).await;
+    }
+    Ok(())
+}
+
+// How long a request to `host` has to wait, or Err when it must fail instead
+fn rate_limit_wait(state: &AppState, host: &str) -> Result<Duration, String> {
+    let Some(limit) = active_rate_limit(state, host) else {
+        return Ok(Duration::ZERO);
+    };
+    let (mode, max_wait_ms) = {
+        let config = state.config.lock().unwrap();
+        (config.rate_limit_mode, config.rate_limit_max_wait_ms)
+    };
+    let wait_ms = limit.reset_ms.saturating_sub(unix_time_ms());
+    if mode == RateLimitMode::Reject || wait_ms > max_wait_ms {
+        return Err(format!("{} is rate limited for another {}s", host, wait_ms.div_ceil(1000)));
+    }
+    Ok(Duration::from_millis(wait_ms))
+}
+
+// Records the cooldown a response asks for; true when there is one
+fn note_rate_limit(app: &AppHandle, host: &str, response: &reqwest::Response) -> bool {
+    let Some((limit, extended)) = record_rate_limit(&app.state::<AppState>(), host, response) else {
+        return false;
+    };
+    if extended {
+        warn!(
+            "{} is rate limited (status {}) for {}ms",
+            host,
+            limit.status,
+            limit.reset_ms.saturating_sub(unix_time_ms())
+        );
+        let _ = app.emit("rate-limited", &limit);
+    }
+    true
+}
+
+// The cooldown the response asks for, and whether it outlasts the one already held
+fn record_rate_limit(state: &AppState, host: &str, response: &reqwest::Response) -> Option<(RateLimitState, bool)> {
+    let limit = ratelimit::from_response(host, response.status().as_u16(), response.headers(), unix_time_ms())?;
+    let mut limits = state.rate_limits.lock().unwrap();
+    let extended = !limits.get(host).is_some_and(|current| current.reset_ms >= limit.reset_ms);
+    if extended {
+        limits.insert(host.to_string(), limit.clone());
+    }
+    Some((limit, extended))
+}
+
+#[cfg(test)]
+mod rate_limit_tests {
+    use super::{mock_http, rate_limit_wait, record_rate_limit, AppState, RateLimitMode};
+    use std::time::Duration;
+
+    async fn throttled_response() -> reqwest::Response {
+        let too_many = mock_http::response("429 Too Many Requests", &[("Retry-After", "3")], "");
+        let url = mock_http::serve(vec![(Duration::ZERO, too_many)]).await;
+        reqwest::get(url).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn retry_after_starts_a_cooldown_that_requests_wait_out() {
+        let state = AppState::default();
+        let response = throttled_response().await;
+        let (limit, extended) = record_rate_limit(&state, "127.0.0.1", &response).unwrap();
+        assert!(extended);
+        assert_eq!(limit.status, 429);
+
+        let wait = rate_limit_wait(&state, "127.0.0.1").unwrap();
+        assert!(wait > Duration::from_secs(2) && wait <= Duration::from_secs(3), "{:?}", wait);
+        // Other hosts are unaffected
+        assert_eq!(rate_limit_wait(&state, "example.com").unwrap(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn reject_mode_fails_during_the_cooldown() {
+        let state = AppState::default();
+        state.config.lock().unwrap().rate_limit_mode = RateLimitMode::Reject;
+        record_rate_limit(&state, "127.0.0.1", &throttled_response().await).unwrap();
+        let error = rate_limit_wait(&state, "127.0.0.1").unwrap_err();
+        assert!(error.contains("rate limited"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn a_shorter_cooldown_does_not_replace_a_longer_one() {
+        let state = AppState::default();
+        let response = throttled_response().await;
+        record_rate_limit(&state, "127.0.0.1", &response).unwrap();
+        state.rate_limits.lock().unwrap().get_mut("127.0.0.1").unwrap().reset_ms += 60_000;
+        let (_, extended) = record_rate_limit(&state, "127.0.0.1", &response).unwrap();
+        assert!(!extended);
+    }
+}
+
+// Accepts a host or a URL
+#[tauri::command]
+async fn get_rate_limit_state(state: State<'_, AppState>, host: String) -> Result<Option<RateLimitState>, String> {
+    let _timing = time_command!(state);
+    let host = url::Url::parse(&host)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or(host);
+    Ok(active_rate_limit(&state, &host))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct HealthResponse {
     status: String,
     services: Option<HashMap<String, String>>,
+    warnings: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackendWarning {
+    id: String,
+    message: String,
+    acknowledged: bool,
+}
+
+// Stable across runs and Rust versions, so acknowledgements can be persisted by id.
+fn warning_id(message: &str) -> String {
+    Sha256::digest(message.trim().as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AppVersion {
+    name: String,
+    version: String,
+    commit: String,
+    tauri_version: String,
+    license: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackendDescriptor {
+    id: String,
+    name: String,
+    kind: BackendKind,
+    base_url: String,
+    reachable: bool,
+    detected: bool,
+    active: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DiagnosticStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DiagnosticCheck {
+    name: String,
+    status: DiagnosticStatus,
+    message: String,
+}
+
+impl DiagnosticCheck {
+    fn new(name: &str, status: DiagnosticStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NotificationResult {
+    permission: String,
+    sent: bool,
+    guidance: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SystemInfo {
+    platform: String,
+    arch: String,
+    cpu_count: usize,
+    /// Installed memory, in bytes.
+    memory_total: u64,
+    /// Memory free for new processes right now, in bytes.
+    memory_available: u64,
+    has_gpu: bool,
+    /// The GPU services run on; see `selected_gpu`.
+    gpu: Option<GpuDescriptor>,
+    /// Every detected adapter, in discovery order.
+    gpu_devices: Vec<GpuDescriptor>,
+    cpu_features: CpuFeatures,
+    is_elevated: bool,
+    recommended_config: String,
+}
+
+// Less VRAM than this can't hold the larger models fully on the GPU
+const HIGH_PERFORMANCE_VRAM_MB: u64 = 8192;
+const GIB: u64 = 1024 * 1024 * 1024;
+// Installed memory per profile, compared in whole GiB: the OS reserves a
+// little, so a 16 GiB machine reports slightly less than 16 GiB
+const HIGH_PERFORMANCE_MEMORY_GIB: u64 = 16;
+const STANDARD_MEMORY_GIB: u64 = 8;
+// With less than this free, the recommendation drops a tier
+const LOW_AVAILABLE_MEMORY_BYTES: u64 = 4 * GIB;
+// Assumed when memory can't be read at all
+const FALLBACK_MEMORY_BYTES: u64 = 8 * GIB;
+
+// Tauri commands for frontend-backend communication
+
+#[tauri::command]
+async fn get_system_info(app: AppHandle, state: State<'_, AppState>) -> Result<SystemInfo, String> {
+    let _timing = time_command!(state);
+    info!("Getting system information");
+    
+    let platform = std::env::consts::OS.to_string();
+    let arch = std::env::consts::ARCH.to_string();
+    // Static values come from the survey, which also primes the GPU list
+    let inventory = match hardware_inventory(&app, false).await {
+        Ok((inventory, _)) => Some(inventory),
+        Err(e) => {
+            warn!("Hardware survey unavailable: {}", e);
+            None
+        }
+    };
+    let cpu_count = inventory.as_ref().map_or_else(num_cpus::get, |inventory| inventory.logical_cores);
+    let cpu_features = inventory
+        .as_ref()
+        .map_or_else(system::detect_cpu_features, |inventory| inventory.cpu_features.clone());
+    
+    // sysinfo reports bytes, and counts Apple Silicon's unified memory in full
+    let mut sys = sysinfo::System::new();
+    sys.refresh_memory();
+    let memory_total = match &inventory {
+        Some(inventory) if inventory.memory_total_bytes > 0 => inventory.memory_total_bytes,
+        _ if sys.total_memory() > 0 => sys.total_memory(),
+        _ => {
+            warn!("Failed to read installed memory; assuming {} GiB", FALLBACK_MEMORY_BYTES / GIB);
+            FALLBACK_MEMORY_BYTES
+        }
+    };
+    let memory_available = match sys.available_memory() {
+        0 => memory_total,
+        available => available,
+    };
+    
+    let gpu_devices = detected_gpus(&state, false).await.unwrap_or_else(|e| {
+        warn!("GPU detection failed: {}", e);
+        Vec::new()
+    });
+    let gpu = selected_gpu(&state, &gpu_devices);
+    let has_gpu = gpu.is_some();
+    
+    // Recommend configuration based on specs; shared-memory GPUs (Apple)
+    // have no VRAM figure and are judged by system memory alone
+    let fast_gpu = gpu.as_ref().is_some_and(|gpu| {
+        gpu.is_usable() && !gpu.vram_mb.is_some_and(|vram_mb| vram_mb < HIGH_PERFORMANCE_VRAM_MB)
+    });
+    let memory_gib = (memory_total + GIB / 2) / GIB;
+    let tiers = ["high-performance", "standard", "lightweight"];
+    let mut tier = if memory_gib >= HIGH_PERFORMANCE_MEMORY_GIB && fast_gpu {
+        0
+    } else if memory_gib >= STANDARD_MEMORY_GIB {
+        1
+    } else {
+        2
+    };
+    if memory_available < LOW_AVAILABLE_MEMORY_BYTES && tier + 1 < tiers.len() {
+        info!(
+            "Only {:.1} GiB of memory free; recommending {} over {}",
+            memory_available as f64 / GIB as f64,
+            tiers[tier + 1],
+            tiers[tier]
+        );
+        tier += 1;
+    }
+    let recommended_config = tiers[tier].to_string();
+    
+    Ok(SystemInfo {
+        platform,
+        arch,
+        cpu_count,
+        memory_total,
+        memory_available,
+        has_gpu,
+        gpu,
+        gpu_devices,
+        cpu_features,
+        is_elevated: system::is_elevated().unwrap_or(false),
+        recommended_config,
+    })
+}
+
+// Path on router-mcp that takes the resolved profile settings
+const ROUTER_HARDWARE_PROFILE_PATH: &str = "/tools/hardware-profile";
+
+// Hands the profile's concrete settings to router-mcp so model selection
+// follows it, and saves the profile only once the router has taken it; a
+// saved profile never disagrees with what the router is running.
+#[tauri::command]
+async fn apply_hardware_profile(app: AppHandle, state: State<'_, AppState>, profile: HardwareProfile) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let cpu_count = num_cpus::get();
+    let mut sys = sysinfo::System::new();
+    sys.refresh_memory();
+    // Without a memory reading there is nothing to check the limit against
+    let memory_total_mb = match sys.total_memory() / (1024 * 1024) {
+        0 => u64::MAX,
+        memory_mb => memory_mb,
+    };
+    profile.validate(cpu_count, memory_total_mb).map_err(|e| e.to_string())?;
+    let settings = profile.resolve(cpu_count);
+
+    let url = state
+        .config
+        .lock()
+        .unwrap()
+        .status_endpoints
+        .iter()
+        .find(|service| service.name == "router-mcp")
+        .and_then(|service| url::Url::parse(&service.health_url).ok())
+        .and_then(|router| router.join(ROUTER_HARDWARE_PROFILE_PATH).ok())
+        .ok_or_else(|| "Profile not applied: no router-mcp service is registered".to_string())?
+        .to_string();
+    let client = http_client(&state)?;
+    let policy = state.retry_policy.lock().unwrap().clone();
+    let response = send_with_retry(&app, &policy, &url, || client.post(&url).json(&settings).send())
+        .await
+        .map_err(|e| format!("Profile not applied: router-mcp could not be reached: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Profile not applied: router-mcp rejected it with status {}", response.status()));
+    }
+
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.hardware_profile = profile;
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    info!("Applied hardware profile: {:?}", settings);
+    Ok(())
+}
+
+// Hardware survey
+// Static hardware facts are surveyed once and kept in the data directory.
+// The cache is trusted for the rest of the process once its fingerprint has
+// been checked against the machine; a mismatch means hardware changed (or
+// the data directory moved machines) and triggers a fresh survey.
+const HARDWARE_SURVEY_FILE: &str = "hardware-survey.json";
+
+#[derive(Debug, Clone, Serialize)]
+struct HardwareSurvey {
+    #[serde(flatten)]
+    inventory: HardwareInventory,
+    memory_available_bytes: u64,
+    gpu_utilization: Option<f32>,
+    /// The static fields were read from the cache rather than probed now.
+    from_cache: bool,
+}
+
+async fn hardware_inventory(app: &AppHandle, force_rescan: bool) -> Result<(HardwareInventory, bool), String> {
+    let state = app.state::<AppState>();
+    if !force_rescan {
+        if let Some(inventory) = state.hardware_survey.lock().unwrap().clone() {
+            return Ok((inventory, true));
+        }
+    }
+    let path = data_dir(app)?.join(HARDWARE_SURVEY_FILE);
+    let (inventory, from_cache) = tokio::task::spawn_blocking(move || {
+        let cached = (!force_rescan)
+            .then(|| std::fs::read_to_string(&path).ok())
+            .flatten()
+            .and_then(|contents| serde_json::from_str::<HardwareInventory>(&contents).ok());
+        if let Some(cached) = cached {
+            let current = system::machine_fingerprint().ok();
+            if cached.fingerprint.is_some() && cached.fingerprint == current {
+                return (cached, true);
+            }
+            info!("Machine fingerprint changed; surveying hardware again");
+        }
+        let inventory = system::survey_hardware();
+        let written = serde_json::to_vec_pretty(&inventory)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                std::fs::create_dir_all(path.parent().unwrap_or(&path)).map_err(|e| e.to_string())?;
+                std::fs::write(&path, json).map_err(|e| e.to_string())
+            });
+        if let Err(e) = written {
+            warn!("Failed to cache hardware survey at {}: {}", path.display(), e);
+        }
+        (inventory, false)
+    })
+    .await
+    .map_err(|e| format!("Hardware survey failed: {}", e))?;
+
+    *state.hardware_survey.lock().unwrap() = Some(inventory.clone());
+    let mut gpus = state.gpus.lock().unwrap();
+    if gpus.is_none() || !from_cache {
+        *gpus = Some(inventory.gpus.clone());
+    }
+    Ok((inventory, from_cache))
+}
+
+#[tauri::command]
+async fn get_hardware_survey(app: AppHandle, force_rescan: bool) -> Result<HardwareSurvey, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let (inventory, from_cache) = hardware_inventory(&app, force_rescan).await?;
+    let mut sys = sysinfo::System::new();
+    sys.refresh_memory();
+    let gpu_utilization = if inventory.gpus.is_empty() {
+        None
+    } else {
+        tokio::task::spawn_blocking(system::gpu_utilization).await.unwrap_or(None)
+    };
+    Ok(HardwareSurvey {
+        inventory,
+        memory_available_bytes: sys.available_memory(),
+        gpu_utilization,
+        from_cache,
+    })
+}
+
+// GPUs
+// Detection runs external tools, so the list is kept until `list_gpus`
+// asks for a fresh one.
+async fn detected_gpus(state: &AppState, refresh: bool) -> Result<Vec<GpuDescriptor>, String> {
+    if !refresh {
+        if let Some(gpus) = state.gpus.lock().unwrap().clone() {
+            return Ok(gpus);
+        }
+    }
+    let gpus = tokio::task::spawn_blocking(system::list_gpus)
+        .await
+        .map_err(|e| format!("GPU enumeration failed: {}", e))??;
+    *state.gpus.lock().unwrap() = Some(gpus.clone());
+    Ok(gpus)
+}
+
+// The saved choice, else the first usable GPU, else whatever is there
+fn selected_gpu(state: &AppState, gpus: &[GpuDescriptor]) -> Option<GpuDescriptor> {
+    if let Some(index) = state.config.lock().unwrap().selected_gpu {
+        match gpus.get(index) {
+            Some(gpu) => return Some(gpu.clone()),
+            None => warn!("Selected GPU {} is no longer present; falling back to the default", index),
+        }
+    }
+    gpus.iter().find(|gpu| gpu.is_usable()).or_else(|| gpus.first()).cloned()
+}
+
+#[tauri::command]
+async fn list_gpus(state: State<'_, AppState>) -> Result<Vec<GpuDescriptor>, String> {
+    let _timing = time_command!(state);
+    let gpus = detected_gpus(&state, true).await?;
+    info!("Detected {} GPU(s)", gpus.len());
+    Ok(gpus)
+}
+
+// Applies to backend services started afterwards
+#[tauri::command]
+async fn select_gpu(app: AppHandle, state: State<'_, AppState>, index: usize) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let gpus = detected_gpus(&state, false).await?;
+    let Some(gpu) = gpus.get(index) else {
+        return Err(format!("GPU index {} is out of range; {} GPU(s) detected", index, gpus.len()));
+    };
+    info!("Selected GPU {} ({})", index, gpu.name);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.selected_gpu = Some(index);
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn get_cpu_features(state: State<'_, AppState>) -> Result<CpuFeatures, String> {
+    let _timing = time_command!(state);
+    let features = system::detect_cpu_features();
+    info!("CPU features: {:?} (build tier: {})", features, features.build_tier());
+    if features.build_tier() == "baseline" {
+        warn!("CPU lacks AVX2/NEON support; only the baseline backend build is usable");
+    }
+    Ok(features)
+}
+
+#[tauri::command]
+async fn check_api_health(app: AppHandle, state: State<'_, AppState>) -> Result<HealthResponse, HealthCheckError> {
+    let _timing = time_command!(state);
+    let health_url = api_health_url(&state.api_base_url.lock().unwrap());
+    
+    info!("Checking API health at: {}", health_url);
+    
+    let policy = state.retry_policy.lock().unwrap().for_health_probe();
+    let client = http_client(&state).map_err(|message| HealthCheckError {
+        kind: "client".to_string(),
+        message,
+        status: None,
+        body_snippet: None,
+    })?;
+    let headers = environment_headers(&state, &health_url);
+    let timeout = Duration::from_millis(state.config.lock().unwrap().health_check_timeout_ms);
+    let started = Instant::now();
+    let request = || client.get(&health_url).headers(headers.clone()).timeout(timeout).send();
+    let result = match send_with_retry(&app, &policy, &health_url, request).await {
+        Ok(response) => match parse_health_response(response).await {
+            Ok(health) => {
+                info!("API health check successful: {:?}", health);
+                update_backend_warnings(&app, &state, health.warnings.as_deref().unwrap_or_default());
+                Ok(health)
+            }
+            Err(error) => {
+                warn!("{}", error);
+                Err(error)
+            }
+        },
+        Err(e) => {
+            // "timeout" means the backend is slow or stuck; "connection" that it is down
+            let timed_out = e.starts_with(REQUEST_TIMED_OUT);
+            let error = HealthCheckError {
+                kind: if timed_out { "timeout" } else { "connection" }.to_string(),
+                message: if timed_out {
+                    format!("health check timed out after {}", format_timeout(timeout))
+                } else {
+                    format!("Failed to connect to API: {}", e)
+                },
+                status: None,
+                body_snippet: None,
+            };
+            error!("{}", error);
+            Err(error)
+        }
+    };
+
+    let record = state.record_health(
+        "api",
+        result.is_ok(),
+        Some(started.elapsed()),
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+    emit_batched(&app, "health-record", record);
+    if result.is_ok() {
+        mark_backend_ready(&app);
+    }
+    result
+}
+
+// Replaces the current warning set and emits `backend-warning` for warnings
+// that are new since the last check and not acknowledged.
+fn update_backend_warnings(app: &AppHandle, state: &AppState, messages: &[String]) {
+    let acknowledged = state.config.lock().unwrap().acknowledged_warnings.clone();
+    let mut current = state.backend_warnings.lock().unwrap();
+    let warnings: Vec<BackendWarning> = messages
+        .iter()
+        .map(|message| {
+            let id = warning_id(message);
+            BackendWarning {
+                acknowledged: acknowledged.contains(&id),
+                id,
+                message: message.clone(),
+            }
+        })
+        .collect();
+
+    for warning in &warnings {
+        let is_new = !current.iter().any(|existing| existing.id == warning.id);
+        if is_new && !warning.acknowledged {
+            warn!("Backend warning: {}", warning.message);
+            let _ = app.emit("backend-warning", warning);
+        }
+    }
+    *current = warnings;
+}
+
+#[tauri::command]
+async fn get_backend_warnings(state: State<'_, AppState>) -> Result<Vec<BackendWarning>, String> {
+    let _timing = time_command!(state);
+    Ok(state.backend_warnings.lock().unwrap().clone())
+}
+
+#[tauri::command]
+async fn acknowledge_warning(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    info!("Acknowledging backend warning {}", id);
+    for warning in state.backend_warnings.lock().unwrap().iter_mut() {
+        if warning.id == id {
+            warning.acknowledged = true;
+        }
+    }
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        if !config.acknowledged_warnings.contains(&id) {
+            config.acknowledged_warnings.push(id);
+        }
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+fn api_health_url(base_url: &str) -> String {
+    if base_url.is_empty() {
+        "http://localhost:8080/healthz".to_string()
+    } else {
+        format!("{}/healthz", base_url)
+    }
+}
+
+const MAX_HEALTH_BODY_BYTES: usize = 64 * 1024;
+const BODY_SNIPPET_BYTES: usize = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HealthCheckError {
+    kind: String,
+    message: String,
+    status: Option<u16>,
+    body_snippet: Option<String>,
+}
+
+impl std::fmt::Display for HealthCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(snippet) = &self.body_snippet {
+            write!(f, " (body: {})", snippet)?;
+        }
+        Ok(())
+    }
+}
+
+// Reads at most `limit` bytes of the body; the flag is set when the body was longer.
+async fn read_body_capped(mut response: reqwest::Response, limit: usize) -> (Vec<u8>, bool) {
+    let mut body = Vec::new();
+    while let Ok(Some(chunk)) = response.chunk().await {
+        let remaining = limit - body.len();
+        if chunk.len() > remaining {
+            body.extend_from_slice(&chunk[..remaining]);
+            return (body, true);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    (body, false)
+}
+
+fn body_snippet(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(&body[..body.len().min(BODY_SNIPPET_BYTES)]);
+    let snippet = text.trim().replace(char::is_whitespace, " ");
+    if body.len() > BODY_SNIPPET_BYTES {
+        format!("{}…", snippet)
+    } else {
+        snippet
+    }
+}
+
+async fn parse_health_response(response: reqwest::Response) -> Result<HealthResponse, HealthCheckError> {
+    let status = response.status();
+    let (body, truncated) = read_body_capped(response, MAX_HEALTH_BODY_BYTES).await;
+    let parsed = if truncated {
+        None
+    } else {
+        serde_json::from_slice::<HealthResponse>(&body).ok()
+    };
+    match parsed {
+        Some(health) if status.is_success() => Ok(health),
+        _ => {
+            // Often an HTML error or login page from a reverse proxy rather than the backend itself
+            let message = if status.is_success() {
+                "Health endpoint did not return valid JSON".to_string()
+            } else {
+                format!("API health check failed with status: {}", status)
+            };
+            Err(HealthCheckError {
+                kind: if status.is_success() { "invalid_response" } else { "http_status" }.to_string(),
+                message,
+                status: Some(status.as_u16()),
+                body_snippet: Some(body_snippet(&body)),
+            })
+        }
+    }
+}
+
+// Minimal HTTP/1.1 server for tests: each accepted connection is answered,
+// after the given delay, with the next canned response (the last repeats)
+#[cfg(test)]
+mod mock_http {
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    pub fn response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+        let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n", status, body.len());
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        response.push_str(body);
+        response
+    }
+
+    /// Returns the server's base URL, e.g. `http://127.0.0.1:54321`.
+    pub async fn serve(responses: Vec<(Duration, String)>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut index = 0;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let (delay, response) = responses[index.min(responses.len() - 1)].clone();
+                index += 1;
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 1024];
+                    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                        match stream.read(&mut buffer).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(read) => request.extend_from_slice(&buffer[..read]),
+                        }
+                    }
+                    tokio::time::sleep(delay).await;
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        url
+    }
+}
+
+#[cfg(test)]
+mod health_response_tests {
+    use super::{mock_http, parse_health_response};
+    use std::time::Duration;
+
+    async fn get(response: String) -> reqwest::Response {
+        let url = mock_http::serve(vec![(Duration::ZERO, response)]).await;
+        reqwest::get(format!("{}/health", url)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_html_from_a_proxy() {
+        let page = "<html><body>Please sign in</body></html>";
+        let response = get(mock_http::response("200 OK", &[("Content-Type", "text/html")], page)).await;
+        let error = parse_health_response(response).await.unwrap_err();
+        assert_eq!(error.kind, "invalid_response");
+        assert_eq!(error.status, Some(200));
+        assert_eq!(error.body_snippet.as_deref(), Some(page));
+    }
+
+    #[tokio::test]
+    async fn parses_a_json_health_body() {
+        let response = get(mock_http::response("200 OK", &[], r#"{"status":"ok"}"#)).await;
+        assert_eq!(parse_health_response(response).await.unwrap().status, "ok");
+    }
+
+    #[tokio::test]
+    async fn reports_the_status_of_an_error_page() {
+        let page = format!("<html>{}</html>", "x".repeat(1000));
+        let response = get(mock_http::response("502 Bad Gateway", &[], &page)).await;
+        let error = parse_health_response(response).await.unwrap_err();
+        assert_eq!(error.kind, "http_status");
+        assert_eq!(error.status, Some(502));
+        assert!(error.body_snippet.unwrap().ends_with('…'));
+    }
+}
+
+#[tauri::command]
+async fn set_api_base_url(app: AppHandle, state: State<'_, AppState>, url: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    ensure_unlocked(&state, "api_base_url")?;
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(format!("API base URL must be http(s): {}", url));
+    }
+    info!("Setting API base URL to: {}", url);
+    // A URL set by hand replaces any backend or environment, also on relaunch
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        *state.api_base_url.lock().unwrap() = url.clone();
+        config.api_base_url = url;
+        config.active_backend = None;
+        config.active_environment = None;
+        config.clone()
+    };
+    // Cached responses came from the previous backend
+    state.response_cache.invalidate(None);
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn set_retry_policy(state: State<'_, AppState>, policy: RetryPolicy) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if policy.base_delay_ms > policy.max_delay_ms {
+        return Err("base_delay_ms must not exceed max_delay_ms".to_string());
+    }
+    info!("Setting retry policy: {:?}", policy);
+    *state.retry_policy.lock().unwrap() = policy;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_retry_policy(state: State<'_, AppState>) -> Result<RetryPolicy, String> {
+    let _timing = time_command!(state);
+    Ok(state.retry_policy.lock().unwrap().clone())
+}
+
+// The platform data dir unless the user relocated it (see `relocate_data_dir`)
+fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+    if let Some(relocated) = datadir::read_pointer(&config_dir)? {
+        return Ok(relocated);
+    }
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+// Reads the saved config before Tauri starts, for settings that must be fixed
+// at launch (the CSP). Mirrors `data_dir`, which needs a running app.
+fn launch_config(identifier: &str) -> AppConfig {
+    let config_dir = dirs::config_dir().map(|dir| dir.join(identifier));
+    let data_dir = config_dir
+        .as_deref()
+        .and_then(|dir| {
+            datadir::read_pointer(dir).unwrap_or_else(|e| {
+                warn!("{}", e);
+                None
+            })
+        })
+        .or_else(|| dirs::data_dir().map(|dir| dir.join(identifier)));
+    let Some(path) = data_dir.map(|dir| dir.join("config.json")) else {
+        return AppConfig::default();
+    };
+    AppConfig::load(&path).unwrap_or_else(|e| {
+        warn!("Failed to read config before launch, using defaults: {}", e);
+        AppConfig::default()
+    })
+}
+
+// Content security policy
+const FALLBACK_CSP: &str = "default-src 'self'";
+
+#[derive(Debug, Default)]
+struct AppliedCsp {
+    policy: String,
+    connect_origins: Vec<String>,
+}
+
+// Narrows the bundled policy's connect-src to the configured origins
+fn apply_csp<R: tauri::Runtime>(context: &mut tauri::Context<R>, config: &AppConfig) -> AppliedCsp {
+    let base = context
+        .config()
+        .app
+        .security
+        .csp
+        .as_ref()
+        .map(|csp| csp.to_string())
+        .unwrap_or_else(|| FALLBACK_CSP.to_string());
+    let connect_origins = csp::connect_src_origins(config);
+    let policy = csp::with_connect_src(&base, &connect_origins);
+    info!("Webview connect-src limited to {:?}", connect_origins);
+    context.config_mut().app.security.csp = Some(tauri::utils::config::Csp::Policy(policy.clone()));
+    AppliedCsp { policy, connect_origins }
+}
+
+// Webview flags
+// Switches are fixed per process: WebKitGTK reads its environment once and
+// every WebView2 in a process must share the same arguments.
+fn apply_webview_flags<R: tauri::Runtime>(context: &mut tauri::Context<R>, flags: WebviewFlags) -> WebviewFlags {
+    for (name, value) in flags.environment() {
+        std::env::set_var(name, value);
+    }
+    if let Some(args) = flags.browser_args() {
+        for window in context.config_mut().app.windows.iter_mut() {
+            window.additional_browser_args = Some(args.clone());
+        }
+    }
+    if flags.ignore_certificate_errors {
+        warn!("Webview certificate errors are ignored; disable this outside local development");
+    }
+    if flags != WebviewFlags::default() {
+        info!("Webview flags applied at launch: {:?}", flags);
+    }
+    flags
+}
+
+// Windows created after launch must match the configured ones
+fn with_webview_flags<'a, R: tauri::Runtime, M: Manager<R>>(
+    builder: tauri::WebviewWindowBuilder<'a, R, M>,
+    flags: &WebviewFlags,
+) -> tauri::WebviewWindowBuilder<'a, R, M> {
+    match flags.browser_args() {
+        Some(args) => builder.additional_browser_args(&args),
+        None => builder,
+    }
+}
+
+#[tauri::command]
+async fn get_webview_flags(state: State<'_, AppState>) -> Result<Vec<WebviewFlagState>, String> {
+    let _timing = time_command!(state);
+    let saved = state.config.lock().unwrap().webview_flags;
+    Ok(saved.states(&state.webview_flags))
+}
+
+// Only allowlisted names are accepted; the value takes effect on restart
+#[tauri::command]
+async fn set_webview_flag(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    value: bool,
+) -> Result<Vec<WebviewFlagState>, String> {
+    let _timing = time_command!(state);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.webview_flags.set(&name, value)?;
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    info!("Webview flag {} set to {}", name, value);
+
+    if state.webview_flags.get(&name) != Some(value) {
+        let _ = app.emit(
+            "restart-required",
+            serde_json::json!({ "reason": "webview-flags", "flag": name }),
+        );
+    }
+    Ok(config.webview_flags.states(&state.webview_flags))
+}
+
+// Every window shares the policy applied at launch
+#[tauri::command]
+async fn get_effective_csp(window: Window, state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    debug!("CSP requested by window {}", window.label());
+    Ok(state.csp.policy.clone())
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    data_dir(app).map(|dir| dir.join("config.json"))
+}
+
+// Under a signed config, saves that touch a locked field are refused and the
+// running config is put back, since callers update it before saving
+fn persist_config(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
+    persist_enforced_config(app, config).map(|_| ())
+}
+
+// Like `persist_config`, returning the config as written: a caller that
+// replaces the running config wholesale must keep the managed record
+fn persist_enforced_config(app: &AppHandle, config: &AppConfig) -> Result<AppConfig, String> {
+    let path = config_path(app)?;
+    let managed = app
+        .try_state::<AppState>()
+        .and_then(|state| state.config.lock().unwrap().managed.clone());
+    let Some(managed) = managed else {
+        config.save(&path).map_err(|e| format!("Failed to save config: {}", e))?;
+        return Ok(config.clone());
+    };
+    let mut checked = config.clone();
+    let reset = provisioning::enforce(&mut checked, &managed);
+    if !reset.is_empty() {
+        provisioning::enforce(&mut app.state::<AppState>().config.lock().unwrap(), &managed);
+        return Err(format!("Managed by your administrator and can't be changed: {}", reset.join(", ")));
+    }
+    checked
+        .save(&path)
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+    Ok(checked)
+}
+
+fn ensure_unlocked(state: &AppState, field: &str) -> Result<(), String> {
+    let locked = state
+        .config
+        .lock()
+        .unwrap()
+        .managed
+        .as_ref()
+        .is_some_and(|managed| provisioning::status(Some(managed)).locked_fields.iter().any(|locked| locked == field));
+    if locked {
+        return Err(format!("Managed by your administrator and can't be changed: {}", field));
+    }
+    Ok(())
+}
+
+// The system-wide policy, else the data dir's copy from the last import
+fn managed_policy(app: &AppHandle) -> Option<ManagedConfig> {
+    let imported = data_dir(app).ok().map(|dir| dir.join(provisioning::POLICY_FILE_NAME));
+    provisioning::system_policy_path()
+        .into_iter()
+        .chain(imported)
+        .find_map(|path| provisioning::read_policy(&path).unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        }))
+}
+
+#[tauri::command]
+async fn import_signed_config(app: AppHandle, payload: String, signature: String) -> Result<AppConfig, String> {
+    let state = app.state::<AppState>();
+    let _timing = time_command!(state);
+    if let Some(path) = provisioning::system_policy_path().filter(|path| path.exists()) {
+        return Err(format!("This machine's config is managed by {}; it can't be replaced from the app", path.display()));
+    }
+    let signed = provisioning::verify(&payload, &signature)?;
+    let managed = ManagedConfig { payload, signature, imported_ms: unix_time_ms() };
+    let current = state.config.lock().unwrap().clone();
+    let config = provisioning::apply(&current, &signed, managed.clone())?;
+    config
+        .validate()
+        .map_err(|e| format!("Signed config is invalid: {}", e))?;
+    let policy_path = data_dir(&app)?.join(provisioning::POLICY_FILE_NAME);
+    provisioning::write_policy(&policy_path, &managed)
+        .map_err(|e| format!("Failed to write managed policy {}: {}", policy_path.display(), e))?;
+
+    apply_config(&config);
+    *state.config.lock().unwrap() = config.clone();
+    *state.api_base_url.lock().unwrap() = config.api_base_url.clone();
+    state.response_cache.invalidate(None);
+    reset_http_client(&state);
+    persist_config(&app, &config)?;
+    info!(
+        "Imported signed config ({} field(s), {} locked)",
+        signed.config.len(),
+        signed.locked_fields.len()
+    );
+    let _ = app.emit("config-reloaded", &config);
+    Ok(config)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SignedConfigExport {
+    payload: String,
+    signature: String,
+}
+
+// Admin side: the private key is read from a file for this call only, so it
+// never passes through the webview, and is never stored
+#[tauri::command]
+async fn export_signed_config(
+    state: State<'_, AppState>,
+    signing_key_path: String,
+    locked_fields: Vec<String>,
+) -> Result<SignedConfigExport, String> {
+    let _timing = time_command!(state);
+    let signing_key = zeroize::Zeroizing::new(
+        std::fs::read_to_string(&signing_key_path)
+            .map_err(|e| format!("Failed to read signing key {}: {}", signing_key_path, e))?,
+    );
+    let config = state.config.lock().unwrap().clone();
+    let payload = provisioning::payload_for(&config, locked_fields)?;
+    let signature = provisioning::sign(&payload, &signing_key)?;
+    Ok(SignedConfigExport { payload, signature })
+}
+
+#[tauri::command]
+async fn get_managed_config_status(state: State<'_, AppState>) -> Result<ManagedStatus, String> {
+    let _timing = time_command!(state);
+    let managed = state.config.lock().unwrap().managed.clone();
+    Ok(provisioning::status(managed.as_ref()))
+}
+
+// The config as persisted, which hand edits may have moved past the running
+// one; an unreadable or invalid file reads as the defaults
+#[tauri::command]
+async fn load_app_config(app: AppHandle) -> Result<AppConfig, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let path = config_path(&app)?;
+    Ok(AppConfig::load(&path)
+        .and_then(|config| config.validate().map(|_| config))
+        .unwrap_or_else(|e| {
+            warn!("Failed to load config, using defaults: {}", e);
+            AppConfig::default()
+        }))
+}
+
+// Replaces the running config wholesale and writes it to disk
+#[tauri::command]
+async fn save_app_config(app: AppHandle, config: AppConfig) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _timing = time_command!(state);
+    config.validate().map_err(|e| format!("Invalid config: {}", e))?;
+    let config = persist_enforced_config(&app, &config)?;
+
+    apply_config(&config);
+    *state.config.lock().unwrap() = config.clone();
+    *state.api_base_url.lock().unwrap() = config.api_base_url.clone();
+    state.response_cache.invalidate(None);
+    reset_http_client(&state);
+    let _ = app.emit("config-reloaded", &config);
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_config_backups(app: AppHandle) -> Result<Vec<BackupEntry>, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    Ok(stratmaster_desktop::list_config_backups(&config_path(&app)?))
+}
+
+#[tauri::command]
+async fn diff_configs(state: State<'_, AppState>, a: AppConfig, b: AppConfig) -> Result<Vec<ConfigDiff>, String> {
+    let _timing = time_command!(state);
+    configdiff::diff(&a, &b).map(masked_diffs)
+}
+
+// Environment headers are masked as in list_environments. Diffing the raw
+// values first means a changed secret still shows up, just not its value; an
+// environment only on one side is rendered whole, so its JSON is masked too.
+fn masked_diffs(diffs: Vec<ConfigDiff>) -> Vec<ConfigDiff> {
+    diffs
+        .into_iter()
+        .map(|diff| {
+            if !diff.path.starts_with("environments") {
+                return diff;
+            }
+            let secret_header = diff
+                .path
+                .split_once(".headers.")
+                .is_some_and(|(_, header)| redact::is_sensitive_key(header));
+            let mask = |value: Option<String>| {
+                let value = value?;
+                if secret_header {
+                    return Some(redact::REDACTED.to_string());
+                }
+                match serde_json::from_str::<serde_json::Value>(&value) {
+                    Ok(mut json @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => {
+                        redact::redact_json(&mut json);
+                        Some(json.to_string())
+                    }
+                    _ => Some(value),
+                }
+            };
+            ConfigDiff {
+                left: mask(diff.left),
+                right: mask(diff.right),
+                ..diff
+            }
+        })
+        .collect()
+}
+
+// Non-default settings of `config`, or of the running config when omitted;
+// defaults are on the left
+#[tauri::command]
+async fn diff_config_against_default(state: State<'_, AppState>, config: Option<AppConfig>) -> Result<Vec<ConfigDiff>, String> {
+    let _timing = time_command!(state);
+    let config = config.unwrap_or_else(|| state.config.lock().unwrap().clone());
+    configdiff::diff(&AppConfig::default(), &config).map(masked_diffs)
+}
+
+#[cfg(test)]
+mod masked_diff_tests {
+    use super::*;
+
+    fn with_headers(headers: &[(&str, &str)]) -> AppConfig {
+        AppConfig {
+            environments: vec![ApiEnvironment {
+                name: "prod".to_string(),
+                base_url: "https://api.example.com".to_string(),
+                headers: headers.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+                connect_timeout_ms: None,
+                request_timeout_ms: None,
+            }],
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn secret_headers_are_masked_on_both_sides() {
+        let old = with_headers(&[("Authorization", "Bearer old"), ("X-Team", "core")]);
+        let new = with_headers(&[("Authorization", "Bearer new"), ("X-Team", "edge")]);
+        let diffs = masked_diffs(configdiff::diff(&old, &new).unwrap());
+        let auth = diffs.iter().find(|diff| diff.path.ends_with(".headers.Authorization")).unwrap();
+        assert_eq!(auth.left.as_deref(), Some(redact::REDACTED));
+        assert_eq!(auth.right.as_deref(), Some(redact::REDACTED));
+        let team = diffs.iter().find(|diff| diff.path.ends_with(".headers.X-Team")).unwrap();
+        assert_eq!(team.right.as_deref(), Some("edge"));
+    }
+
+    #[test]
+    fn nothing_secret_leaks_against_the_default() {
+        let config = with_headers(&[("X-Api-Key", "sk-live-123")]);
+        let diffs = masked_diffs(configdiff::diff(&AppConfig::default(), &config).unwrap());
+        assert!(diffs.iter().all(|diff| !diff.left.iter().chain(&diff.right).any(|value| value.contains("sk-live-123"))));
+    }
+}
+
+// Restoring is itself a save, so the replaced config becomes backup 1 and
+// the restore can be undone the same way.
+#[tauri::command]
+async fn restore_config_backup(app: AppHandle, index: usize) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    if !(1..=CONFIG_BACKUP_COUNT).contains(&index) {
+        return Err(format!("Backup index must be between 1 and {}", CONFIG_BACKUP_COUNT));
+    }
+    let path = config_path(&app)?;
+    let backup_path = config_backup_path(&path, index);
+    if !backup_path.exists() {
+        return Err(format!("No config backup {}", index));
+    }
+    let config = AppConfig::load(&backup_path)
+        .and_then(|config| config.validate().map(|_| config))
+        .map_err(|e| format!("Config backup {} is unusable: {}", index, e))?;
+
+    persist_config(&app, &config)?;
+    info!("Restored config from backup {}", index);
+    reload_config_from_disk(&app, &path);
+    Ok(())
+}
+
+// Startup
+const STARTUP_READY_TIMEOUT: Duration = Duration::from_secs(120);
+const STARTUP_READY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Records the first successful health check and tells the UI the backend is usable.
+fn mark_backend_ready(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let mut startup = state.startup.lock().unwrap();
+    if startup.backend_ready.is_some() {
+        return;
+    }
+    let elapsed = process_start().elapsed();
+    startup.backend_ready = Some(elapsed);
+    drop(startup);
+    info!("Backend ready {}ms after launch", elapsed.as_millis());
+    let _ = app.emit("ready", elapsed.as_millis() as u64);
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = warm_connection(handle.state::<AppState>()).await {
+            warn!("{}", e);
+        }
+    });
+}
+
+async fn watch_backend_readiness(app: AppHandle) {
+    let deadline = Instant::now() + STARTUP_READY_TIMEOUT;
+    while Instant::now() < deadline {
+        let state = app.state::<AppState>();
+        if state.startup.lock().unwrap().backend_ready.is_some() {
+            return;
+        }
+        if probe_api_health(&state).await {
+            mark_backend_ready(&app);
+            return;
+        }
+        tokio::time::sleep(STARTUP_READY_POLL_INTERVAL).await;
+    }
+    warn!("Backend did not become ready within {}s of launch", STARTUP_READY_TIMEOUT.as_secs());
+}
+
+// Single health request without retries, for callers that run their own polling loop.
+async fn probe_api_health(state: &AppState) -> bool {
+    let health_url = api_health_url(&state.api_base_url.lock().unwrap());
+    let Ok(client) = http_client(state) else {
+        return false;
+    };
+    let headers = environment_headers(state, &health_url);
+    matches!(client.get(&health_url).headers(headers).send().await, Ok(r) if r.status().is_success())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WaitResult {
+    ready: bool,
+    elapsed_ms: u64,
+    attempts: u32,
+}
+
+// Polls health until it succeeds or `timeout_ms` elapses, spacing attempts
+// with the retry policy's backoff and emitting `backend-wait-progress` after each miss.
+#[tauri::command]
+async fn wait_for_backend_ready(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    timeout_ms: u64,
+) -> Result<WaitResult, String> {
+    let _timing = time_command!(state);
+    let started = Instant::now();
+    let timeout = Duration::from_millis(timeout_ms);
+    let policy = state.retry_policy.lock().unwrap().clone();
+    let mut attempts = 0;
+    info!("Waiting up to {}ms for the backend to become ready", timeout_ms);
+
+    loop {
+        attempts += 1;
+        if probe_api_health(&state).await {
+            mark_backend_ready(&app);
+            let result = WaitResult {
+                ready: true,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                attempts,
+            };
+            info!("Backend ready after {} attempts ({}ms)", attempts, result.elapsed_ms);
+            return Ok(result);
+        }
+
+        let elapsed = started.elapsed();
+        let progress = WaitResult {
+            ready: false,
+            elapsed_ms: elapsed.as_millis() as u64,
+            attempts,
+        };
+        let _ = app.emit("backend-wait-progress", &progress);
+        if elapsed >= timeout {
+            warn!("Backend not ready after {}ms ({} attempts)", progress.elapsed_ms, attempts);
+            return Ok(progress);
+        }
+        let delay = policy.delay_for_attempt(attempts - 1).min(timeout - elapsed);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[tauri::command]
+async fn get_startup_metrics(state: State<'_, AppState>) -> Result<StartupMetrics, String> {
+    let _timing = time_command!(state);
+    Ok(state.startup.lock().unwrap().metrics())
+}
+
+// Applies the runtime side effects of a (re)loaded config.
+fn apply_config(config: &AppConfig) {
+    if let Some(level) = logging::parse_level(&config.log_level) {
+        logging::set_level(level);
+    }
+}
+
+const CONFIG_RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Watches the config directory (not the file itself, since editors often
+// replace files atomically) and reloads config.json after external edits.
+fn start_config_watcher(app: &AppHandle) -> Result<(), String> {
+    let path = config_path(app)?;
+    let dir = path
+        .parent()
+        .ok_or_else(|| "Config path has no parent directory".to_string())?
+        .to_path_buf();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| format!("Failed to create config watcher: {}", e))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch config directory: {}", e))?;
+
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        // Ends when the watcher (and with it the sender) is dropped
+        while let Ok(event) = rx.recv() {
+            let touches_config = event.paths.iter().any(|p| p.file_name() == path.file_name());
+            if !touches_config || matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            while rx.recv_timeout(CONFIG_RELOAD_DEBOUNCE).is_ok() {}
+            reload_config_from_disk(&handle, &path);
+        }
+    });
+
+    *app.state::<AppState>().config_watcher.lock().unwrap() = Some(watcher);
+    info!("Watching config file for changes: {}", dir.display());
+    Ok(())
+}
+
+fn reload_config_from_disk(app: &AppHandle, path: &Path) {
+    if !path.exists() {
+        return;
+    }
+    let state = app.state::<AppState>();
+    match AppConfig::load(path).and_then(|config| config.validate().map(|_| config)) {
+        Ok(mut config) => {
+            // Hand edits can't unlock managed fields; they are reverted on disk too
+            if let Some(managed) = state.config.lock().unwrap().managed.clone() {
+                let unmanaged = config.managed.as_ref() != Some(&managed);
+                let reset = provisioning::enforce(&mut config, &managed);
+                if !reset.is_empty() || unmanaged {
+                    warn!("Reverted edits to managed config fields: {}", reset.join(", "));
+                    if let Err(e) = config.save(path) {
+                        warn!("Failed to rewrite managed config: {}", e);
+                    }
+                }
+            }
+            let current = serde_json::to_value(&*state.config.lock().unwrap()).ok();
+            if serde_json::to_value(&config).ok() == current {
+                // Our own write, or a no-op edit
+                return;
+            }
+            apply_config(&config);
+            *state.config.lock().unwrap() = config.clone();
+            *state.api_base_url.lock().unwrap() = config.api_base_url.clone();
+            state.response_cache.invalidate(None);
+            reset_http_client(&state);
+            info!("Reloaded config from {}", path.display());
+            let _ = app.emit("config-reloaded", &config);
+        }
+        Err(e) => {
+            warn!("Ignoring invalid config file, keeping previous settings: {}", e);
+            let _ = app.emit("config-reload-error", e.to_string());
+        }
+    }
+}
+
+// Logging
+#[tauri::command]
+async fn set_log_level(app: AppHandle, state: State<'_, AppState>, level: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let filter = logging::parse_level(&level).ok_or_else(|| {
+        format!("Unknown log level '{}': expected error, warn, info, debug or trace", level)
+    })?;
+    logging::set_level(filter);
+    info!("Log level set to {}", filter);
+
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.log_level = filter.to_string().to_lowercase();
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn get_logs(state: State<'_, AppState>, level: Option<String>, limit: Option<usize>) -> Result<Vec<logging::LogEntry>, String> {
+    let _timing = time_command!(state);
+    let min_level = match level {
+        Some(level) => logging::parse_level(&level)
+            .ok_or_else(|| format!("Unknown log level '{}'", level))?,
+        None => logging::current_level(),
+    };
+    Ok(logging::recent_entries(min_level, limit.unwrap_or(500)))
+}
+
+// Live log file
+const LOG_FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+// Emits each new line of the active log file as "log-line". Listeners can't
+// be counted from Rust, so following ends when the last window closes.
+#[tauri::command]
+async fn follow_log_file(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let path = logging::active_log_file().ok_or_else(|| "No log file is attached".to_string())?;
+    let mut tail = logging::LogTail::open(path.clone())
+        .map_err(|e| format!("Failed to open log file {}: {}", path.display(), e))?;
+    info!("Following log file {}", path.display());
+
+    let task_app = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(LOG_FOLLOW_INTERVAL);
+        loop {
+            interval.tick().await;
+            if task_app.webview_windows().is_empty() {
+                break;
+            }
+            // The file can be briefly missing mid-rotation; try again next tick
+            let Ok(lines) = tail.read_lines() else {
+                continue;
+            };
+            for line in lines {
+                let _ = task_app.emit("log-line", line);
+            }
+        }
+        task_app.state::<AppState>().log_follow_task.lock().unwrap().take();
+        debug!("Stopped following the log file: no windows remain");
+    });
+    if let Some(previous) = state.log_follow_task.lock().unwrap().replace(task) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_following_log(state: State<'_, AppState>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if let Some(task) = state.log_follow_task.lock().unwrap().take() {
+        task.abort();
+        info!("Stopped following the log file");
+    }
+    Ok(())
+}
+
+// Backend proxy
+#[tauri::command]
+async fn proxy_api_request(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+) -> Result<ProxyResponse, String> {
+    let _timing = time_command!(state);
+    let cache = state.config.lock().unwrap().response_cache.clone();
+    let cache_key = (cache.enabled && method.eq_ignore_ascii_case("GET"))
+        .then(|| ResponseCache::key(&method, &path, body.as_ref()));
+    let cached = cache_key.as_deref().and_then(|key| state.response_cache.get(key));
+    let response = match cached {
+        Some(response) => {
+            debug!("Serving {} {} from the response cache", method, path);
+            response
+        }
+        None => {
+            let response = send_proxy_request(&app, &method, &path, body.as_ref()).await?;
+            if let Some(key) = cache_key {
+                state.response_cache.insert(key, &path, &response, &cache);
+            }
+            response
+        }
+    };
+    if let Some(reason) = &response.failover_reason {
+        let _ = app.emit(
+            "failover",
+            serde_json::json!({ "reason": reason, "served_by": response.served_by, "path": path }),
+        );
+    }
+    if let Some(recording) = state.request_recording.lock().unwrap().as_mut() {
+        recording.record(&method, &path, body.as_ref(), &response);
+    }
+    Ok(response)
+}
+
+async fn send_proxy_request(
+    app: &AppHandle,
+    method: &str,
+    path: &str,
+    body: Option<&serde_json::Value>,
+) -> Result<ProxyResponse, String> {
+    let state = app.state::<AppState>();
+    let started = Instant::now();
+    let response = state.active_requests.run(issue_proxy_request(app, method, path, body)).await;
+    state.note_request_latency(started.elapsed());
+    response
+}
+
+async fn issue_proxy_request(
+    app: &AppHandle,
+    method: &str,
+    path: &str,
+    body: Option<&serde_json::Value>,
+) -> Result<ProxyResponse, String> {
+    let state = app.state::<AppState>().inner();
+    let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+        .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'));
+    let policy = state.retry_policy.lock().unwrap().clone();
+
+    let _permit = state.request_limiter.acquire().await?;
+    info!("Proxying {} {}", method, url);
+
+    let client = http_client(state)?;
+    let headers = environment_headers(state, &url);
+    let request_log = state.config.lock().unwrap().request_logging.clone();
+    let fallback = failover_target(state, &base_url);
+    let holding = fallback.is_some() && state.failover_until.lock().unwrap().is_some_and(|until| Instant::now() < until);
+
+    let mut failover_reason = None;
+    let (response, served_by, degraded) = match &fallback {
+        // The primary failed moments ago; don't pay its retries again yet
+        Some(fallback) if holding => {
+            let response = send_proxied(app, &client, &policy, &request_log, &method, &fallback.url(path), &fallback.headers, body).await?;
+            (response, fallback.name.clone(), true)
+        }
+        _ => {
+            let primary = send_proxied(app, &client, &policy, &request_log, &method, &url, &headers, body).await;
+            let failure = match &primary {
+                Err(e) => Some(e.clone()),
+                Ok(response) if response.status().is_server_error() => {
+                    Some(format!("{} returned {}", url, response.status()))
+                }
+                Ok(_) => None,
+            };
+            match (failure, &fallback) {
+                (Some(reason), Some(fallback)) => {
+                    warn!("{}; failing over to {}", reason, fallback.name);
+                    let response = send_proxied(app, &client, &policy, &request_log, &method, &fallback.url(path), &fallback.headers, body)
+                        .await
+                        .map_err(|e| format!("{}; fallback {} also failed: {}", reason, fallback.name, e))?;
+                    *state.failover_until.lock().unwrap() = Some(Instant::now() + FAILOVER_HOLD);
+                    failover_reason = Some(reason);
+                    (response, fallback.name.clone(), true)
+                }
+                _ => (primary?, backend_label(state, &base_url), false),
+            }
+        }
+    };
+
+    let status = response.status().as_u16();
+    let cache_control = response
+        .headers()
+        .get(reqwest::header::CACHE_CONTROL)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let response_headers = response.headers().clone();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+    if request_log.enabled {
+        log_exchange(&request_log, &format!("<-- {} {} ({})", status, path, served_by), &response_headers, Some(&text));
+    }
+    let body = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+    Ok(ProxyResponse {
+        status,
+        body,
+        served_by,
+        degraded,
+        cache_control,
+        failover_reason,
+    })
+}
+
+// Retries per the policy; only a request that never got a response is an error
+async fn send_proxied(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    policy: &RetryPolicy,
+    log: &RequestLogConfig,
+    method: &reqwest::Method,
+    url: &str,
+    headers: &reqwest::header::HeaderMap,
+    body: Option<&serde_json::Value>,
+) -> Result<reqwest::Response, String> {
+    if log.enabled {
+        log_exchange(log, &format!("--> {} {}", method, url), headers, body.map(|body| body.to_string()).as_deref());
+    }
+    send_with_retry(app, policy, url, || {
+        let mut request = client.request(method.clone(), url).headers(headers.clone());
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        request.send()
+    })
+    .await
+    .map_err(|e| {
+        let error_msg = format!("Proxy request to {} failed: {}", url, e);
+        error!("{}", error_msg);
+        error_msg
+    })
+}
+
+// Request logging
+// Off by default. Header values and JSON fields whose names look secret are
+// masked, free-text secrets are scrubbed, and bodies are cut to a size cap.
+// Lines go through the normal logger, so the ring buffer and file both get them.
+const REQUEST_LOG_TARGET: &str = "stratmaster::requests";
+
+fn redacted_headers(headers: &reqwest::header::HeaderMap, patterns: &[String]) -> Vec<String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if redact::is_sensitive_key(name.as_str()) || redact::matches_any(name.as_str(), patterns) {
+                redact::REDACTED.to_string()
+            } else {
+                redact::redact_text(&String::from_utf8_lossy(value.as_bytes())).into_owned()
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect()
+}
+
+fn redacted_body(body: &str, config: &RequestLogConfig) -> String {
+    let mut text = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            redact::redact_json_matching(&mut json, &config.redact_patterns);
+            json.to_string()
+        }
+        Err(_) => redact::redact_text(body).into_owned(),
+    };
+    if text.len() > config.max_body_bytes {
+        let total = text.len();
+        let mut end = config.max_body_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str(&format!("... ({} bytes total)", total));
+    }
+    text
+}
+
+fn log_exchange(config: &RequestLogConfig, line: &str, headers: &reqwest::header::HeaderMap, body: Option<&str>) {
+    info!(target: REQUEST_LOG_TARGET, "{}", exchange_entry(config, line, headers, body));
+}
+
+// The request line is scrubbed too: URLs can carry tokens in the query string
+fn exchange_entry(config: &RequestLogConfig, line: &str, headers: &reqwest::header::HeaderMap, body: Option<&str>) -> String {
+    let mut entry = redact::redact_text(line).into_owned();
+    if config.log_headers {
+        for header in redacted_headers(headers, &config.redact_patterns) {
+            entry.push_str(&format!("\n    {}", header));
+        }
+    }
+    if let Some(body) = body.filter(|body| config.log_bodies && !body.is_empty()) {
+        entry.push_str(&format!("\n    {}", redacted_body(body, config)));
+    }
+    entry
+}
+
+#[cfg(test)]
+mod request_log_tests {
+    use super::{exchange_entry, RequestLogConfig};
+
+    fn config() -> RequestLogConfig {
+        RequestLogConfig { enabled: true, log_headers: true, log_bodies: true, ..RequestLogConfig::default() }
+    }
+
+    #[test]
+    fn redacts_tokens_in_the_url() {
+        let line = "--> GET https://api.example.com/v1/items?token=abc123&page=2";
+        let entry = exchange_entry(&config(), line, &Default::default(), None);
+        assert!(!entry.contains("abc123"), "{}", entry);
+        assert!(entry.contains("page=2"), "{}", entry);
+    }
+
+    #[test]
+    fn redacts_authorization_headers_and_body_secrets() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::AUTHORIZATION, "Bearer s3cr3t-value".parse().unwrap());
+        let body = r#"{"api_token":"t0k3n","name":"report"}"#;
+        let entry = exchange_entry(&config(), "--> POST https://api.example.com/v1", &headers, Some(body));
+        assert!(!entry.contains("s3cr3t-value"), "{}", entry);
+        assert!(!entry.contains("t0k3n"), "{}", entry);
+        assert!(entry.contains("report"), "{}", entry);
+    }
+}
+
+#[tauri::command]
+async fn set_request_logging(app: AppHandle, state: State<'_, AppState>, config: RequestLogConfig) -> Result<(), String> {
+    let _timing = time_command!(state);
+    info!(
+        "Request logging {} (headers: {}, bodies: {})",
+        if config.enabled { "enabled" } else { "disabled" },
+        config.log_headers,
+        config.log_bodies
+    );
+    let updated = {
+        let mut current = state.config.lock().unwrap();
+        current.request_logging = config;
+        current.clone()
+    };
+    persist_config(&app, &updated)
+}
+
+// Failover
+// Opt-in by naming a fallback environment. A request fails over at most
+// once, and after a failover requests go straight to the fallback for a
+// while instead of flapping between backends.
+const FAILOVER_HOLD: Duration = Duration::from_secs(30);
+
+struct FailoverTarget {
+    name: String,
+    base_url: String,
+    headers: reqwest::header::HeaderMap,
+}
+
+impl FailoverTarget {
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+    }
+}
+
+// `None` when failover is off, or the primary already is the fallback
+fn failover_target(state: &AppState, primary_base_url: &str) -> Option<FailoverTarget> {
+    let config = state.config.lock().unwrap();
+    let name = config.fallback_environment.as_ref()?;
+    let environment = config.environments.iter().find(|env| &env.name == name)?;
+    if environment.base_url.trim_end_matches('/') == primary_base_url.trim_end_matches('/') {
+        return None;
+    }
+    Some(FailoverTarget {
+        name: environment.name.clone(),
+        base_url: environment.base_url.clone(),
+        headers: parse_environment_headers(&environment.headers).unwrap_or_default(),
+    })
+}
+
+// The active environment's name when it matches, else the URL itself
+fn backend_label(state: &AppState, base_url: &str) -> String {
+    let config = state.config.lock().unwrap();
+    config
+        .environments
+        .iter()
+        .find(|env| env.base_url.trim_end_matches('/') == base_url.trim_end_matches('/'))
+        .map(|env| env.name.clone())
+        .unwrap_or_else(|| base_url.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FailoverTest {
+    fallback: String,
+    healthy: bool,
+    status: Option<u16>,
+    latency_ms: u64,
+    detail: Option<String>,
+}
+
+// Setting `None` turns failover off
+#[tauri::command]
+async fn set_fallback_environment(app: AppHandle, state: State<'_, AppState>, name: Option<String>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        if let Some(name) = &name {
+            if !config.environments.iter().any(|env| &env.name == name) {
+                return Err(format!("Unknown environment: {}", name));
+            }
+        }
+        config.fallback_environment = name.clone();
+        config.clone()
+    };
+    *state.failover_until.lock().unwrap() = None;
+    info!("Fallback environment set to {:?}", name);
+    persist_config(&app, &config)
+}
+
+// Probes the fallback's health endpoint as failover would reach it, without
+// touching the primary
+#[tauri::command]
+async fn test_failover(app: AppHandle, state: State<'_, AppState>) -> Result<FailoverTest, String> {
+    let _timing = time_command!(state);
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let fallback = failover_target(&state, &base_url).ok_or_else(|| {
+        "No usable fallback environment: set one that differs from the active backend".to_string()
+    })?;
+    let client = http_client(&state)?;
+    let policy = state.retry_policy.lock().unwrap().clone();
+    let health_url = api_health_url(fallback.base_url.trim_end_matches('/'));
+
+    let started = Instant::now();
+    let request_log = state.config.lock().unwrap().request_logging.clone();
+    let result = send_proxied(&app, &client, &policy, &request_log, &reqwest::Method::GET, &health_url, &fallback.headers, None).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let test = match result {
+        Ok(response) => FailoverTest {
+            fallback: fallback.name,
+            healthy: response.status().is_success(),
+            status: Some(response.status().as_u16()),
+            latency_ms,
+            detail: None,
+        },
+        Err(e) => FailoverTest {
+            fallback: fallback.name,
+            healthy: false,
+            status: None,
+            latency_ms,
+            detail: Some(e),
+        },
+    };
+    info!("Failover test against {}: healthy={}", test.fallback, test.healthy);
+    Ok(test)
+}
+
+// Response cache
+// Keyed by method, path and body. Entries live for the response's
+// `Cache-Control: max-age`, or the configured default without one;
+// `no-store`/`no-cache`, non-2xx and failed-over responses are never kept.
+struct CachedResponse {
+    path: String,
+    response: ProxyResponse,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct ResponseCache {
+    entries: std::sync::Mutex<HashMap<String, CachedResponse>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheStats {
+    enabled: bool,
+    entries: usize,
+    hits: u64,
+    misses: u64,
+}
+
+// `None` when the response must not be cached
+fn cache_ttl(cache_control: Option<&str>, default: Duration) -> Option<Duration> {
+    let Some(header) = cache_control else {
+        return Some(default);
+    };
+    let mut ttl = default;
+    for directive in header.split(',').map(|directive| directive.trim().to_ascii_lowercase()) {
+        if directive == "no-store" || directive == "no-cache" {
+            return None;
+        }
+        if let Some(seconds) = directive.strip_prefix("max-age=") {
+            ttl = Duration::from_secs(seconds.trim_matches('"').parse().ok()?);
+        }
+    }
+    (!ttl.is_zero()).then_some(ttl)
+}
+
+impl ResponseCache {
+    fn key(method: &str, path: &str, body: Option<&serde_json::Value>) -> String {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        format!("{} {} {}", method.to_uppercase(), path, body)
+    }
+
+    fn get(&self, key: &str) -> Option<ProxyResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        let hit = match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.response.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if hit.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        hit
+    }
+
+    fn insert(&self, key: String, path: &str, response: &ProxyResponse, config: &ResponseCacheConfig) {
+        // Fallback answers would outlive the primary's recovery
+        if !(200..300).contains(&response.status) || response.degraded || config.max_entries == 0 {
+            return;
+        }
+        let Some(ttl) = cache_ttl(response.cache_control.as_deref(), Duration::from_secs(config.default_ttl_secs)) else {
+            return;
+        };
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| entry.expires_at > now);
+        // Still full of live entries: drop the one closest to expiring
+        while entries.len() >= config.max_entries {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            key,
+            CachedResponse {
+                path: path.to_string(),
+                response: response.clone(),
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    // Drops entries whose path starts with `prefix`, or everything
+    fn invalidate(&self, prefix: Option<&str>) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        match prefix {
+            Some(prefix) => entries.retain(|_, entry| !entry.path.starts_with(prefix)),
+            None => entries.clear(),
+        }
+        before - entries.len()
+    }
+}
+
+// Returns how many entries were removed
+#[tauri::command]
+async fn invalidate_cache(state: State<'_, AppState>, path_prefix: Option<String>) -> Result<usize, String> {
+    let _timing = time_command!(state);
+    let removed = state.response_cache.invalidate(path_prefix.as_deref());
+    info!("Invalidated {} cached response(s)", removed);
+    Ok(removed)
+}
+
+#[tauri::command]
+async fn get_cache_stats(state: State<'_, AppState>) -> Result<CacheStats, String> {
+    let _timing = time_command!(state);
+    let cache = &state.response_cache;
+    Ok(CacheStats {
+        enabled: state.config.lock().unwrap().response_cache.enabled,
+        entries: cache.entries.lock().unwrap().len(),
+        hits: cache.hits.load(Ordering::Relaxed),
+        misses: cache.misses.load(Ordering::Relaxed),
+    })
+}
+
+#[tauri::command]
+async fn set_response_cache_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: ResponseCacheConfig,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    info!("Updating response cache settings: {:?}", config);
+    if !config.enabled {
+        state.response_cache.invalidate(None);
+    }
+    let updated = {
+        let mut current = state.config.lock().unwrap();
+        current.response_cache = config;
+        current.clone()
+    };
+    persist_config(&app, &updated)
+}
+
+// Request recording
+// Captures proxied requests and their responses to a JSONL session file for
+// replaying against a backend later. Headers are never recorded and bodies
+// are redacted, so recordings can be shared alongside bug reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    timestamp_ms: u64,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+    status: u16,
+    response: serde_json::Value,
+}
+
+struct RequestRecording {
+    path: PathBuf,
+    file: std::fs::File,
+    count: usize,
+}
+
+impl RequestRecording {
+    fn record(&mut self, method: &str, path: &str, body: Option<&serde_json::Value>, response: &ProxyResponse) {
+        let entry = RecordedRequest {
+            timestamp_ms: unix_time_ms(),
+            method: method.to_uppercase(),
+            path: path.to_string(),
+            body: body.map(redacted),
+            status: response.status,
+            response: redacted(&response.body),
+        };
+        match serde_json::to_string(&entry) {
+            Ok(line) => {
+                if let Err(e) = writeln!(self.file, "{}", line) {
+                    warn!("Failed to write to request recording {}: {}", self.path.display(), e);
+                } else {
+                    self.count += 1;
+                }
+            }
+            Err(e) => warn!("Failed to serialize recorded request: {}", e),
+        }
+    }
+}
+
+fn redacted(value: &serde_json::Value) -> serde_json::Value {
+    let mut value = value.clone();
+    redact::redact_json(&mut value);
+    value
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReplayMismatch {
+    index: usize,
+    method: String,
+    path: String,
+    expected_status: u16,
+    actual_status: Option<u16>,
+    body_differs: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReplayReport {
+    total: usize,
+    matched: usize,
+    mismatches: Vec<ReplayMismatch>,
+}
+
+#[tauri::command]
+async fn start_request_recording(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    let dir = data_dir(&app)?.join("recordings");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+    let path = dir.join(format!("session-{}.jsonl", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    let file = std::fs::File::create(&path)
+        .map_err(|e| format!("Failed to create recording {}: {}", path.display(), e))?;
+
+    info!("Recording proxied requests to {}", path.display());
+    let previous = state.request_recording.lock().unwrap().replace(RequestRecording {
+        path: path.clone(),
+        file,
+        count: 0,
+    });
+    if let Some(previous) = previous {
+        info!("Closed recording {} ({} requests)", previous.path.display(), previous.count);
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Returns the finished recording's path, if one was active
+#[tauri::command]
+async fn stop_request_recording(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let _timing = time_command!(state);
+    let recording = state.request_recording.lock().unwrap().take();
+    Ok(recording.map(|recording| {
+        info!("Stopped recording {} ({} requests)", recording.path.display(), recording.count);
+        recording.path.to_string_lossy().to_string()
+    }))
+}
+
+// Re-issues each recorded request in order against the current backend and
+// compares status and (redacted) response body with what was recorded.
+#[tauri::command]
+async fn replay_request_recording(app: AppHandle, state: State<'_, AppState>, path: String) -> Result<ReplayReport, String> {
+    let _timing = time_command!(state);
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read recording {}: {}", path, e))?;
+    let requests: Vec<RecordedRequest> = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| format!("Invalid recording entry {}: {}", index + 1, e))
+        })
+        .collect::<Result<_, _>>()?;
+    info!("Replaying {} recorded requests from {}", requests.len(), path);
+
+    let mut report = ReplayReport {
+        total: requests.len(),
+        matched: 0,
+        mismatches: Vec::new(),
+    };
+    for (index, recorded) in requests.into_iter().enumerate() {
+        let mismatch = |actual_status, body_differs, error| ReplayMismatch {
+            index,
+            method: recorded.method.clone(),
+            path: recorded.path.clone(),
+            expected_status: recorded.status,
+            actual_status,
+            body_differs,
+            error,
+        };
+        match send_proxy_request(&app, &recorded.method, &recorded.path, recorded.body.as_ref()).await {
+            Ok(response) => {
+                let body_differs = redacted(&response.body) != recorded.response;
+                if response.status == recorded.status && !body_differs {
+                    report.matched += 1;
+                } else {
+                    report.mismatches.push(mismatch(Some(response.status), body_differs, None));
+                }
+            }
+            Err(e) => report.mismatches.push(mismatch(None, true, Some(e))),
+        }
+    }
+
+    if !report.mismatches.is_empty() {
+        warn!("Replay of {}: {} of {} requests differed", path, report.mismatches.len(), report.total);
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+async fn set_max_concurrent_requests(state: State<'_, AppState>, n: usize) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if n == 0 {
+        return Err("Concurrency limit must be at least 1".to_string());
+    }
+    info!("Setting max concurrent backend requests to {}", n);
+    state.request_limiter.set_limit(n);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_inflight_request_count(state: State<'_, AppState>) -> Result<usize, String> {
+    let _timing = time_command!(state);
+    Ok(state.request_limiter.inflight.load(Ordering::SeqCst))
+}
+
+// Opens a few pooled keep-alive connections to the backend so the first real
+// request doesn't pay for DNS, TCP and TLS setup.
+#[tauri::command]
+async fn warm_connection(state: State<'_, AppState>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let client = http_client(&state)?;
+    let health_url = api_health_url(&state.api_base_url.lock().unwrap());
+    info!("Warming {} backend connections to {}", WARM_CONNECTIONS, health_url);
+
+    let mut requests = tokio::task::JoinSet::new();
+    for _ in 0..WARM_CONNECTIONS {
+        let client = client.clone();
+        let url = health_url.clone();
+        let headers = environment_headers(&state, &url);
+        requests.spawn(async move { client.get(&url).headers(headers).send().await.map(|_| ()) });
+    }
+    let mut warmed = 0;
+    while let Some(result) = requests.join_next().await {
+        if matches!(result, Ok(Ok(()))) {
+            warmed += 1;
+        }
+    }
+
+    if warmed == 0 {
+        return Err(format!("Failed to warm connections to {}", health_url));
+    }
+    Ok(())
+}
+
+// Breaks one GET into DNS, connect, TLS, time-to-first-byte and download.
+// By default a fresh connection is opened so every phase is visible; with
+// `reuse_connection` the shared pool is used and the setup phases read as zero.
+#[tauri::command]
+async fn profile_request(
+    state: State<'_, AppState>,
+    path: String,
+    reuse_connection: Option<bool>,
+) -> Result<RequestTiming, String> {
+    let _timing = time_command!(state);
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'));
+    let mut headers = environment_headers(&state, &url);
+    let (client_id, request_timeout_ms) = {
+        let config = state.config.lock().unwrap();
+        (config.client_id.clone(), config.request_timeout_ms())
+    };
+
+    let _permit = state.request_limiter.acquire().await?;
+    info!("Profiling GET {}", url);
+
+    if reuse_connection.unwrap_or(false) {
+        let client = http_client(&state)?;
+        let started = Instant::now();
+        let response = client
+            .get(&url)
+            .headers(headers)
+            .send()
+            .await
+            .map_err(|e| format!("Profiled request to {} failed: {}", url, e))?;
+        let first_byte = Instant::now();
+        let status = response.status().as_u16();
+        let _ = response.bytes().await;
+        let finished = Instant::now();
+
+        let ttfb_ms = (first_byte - started).as_secs_f64() * 1000.0;
+        return Ok(RequestTiming {
+            ttfb_ms,
+            download_ms: (finished - first_byte).as_secs_f64() * 1000.0,
+            total_ms: (finished - started).as_secs_f64() * 1000.0,
+            status: Some(status),
+            reused_connection: true,
+            ..RequestTiming::default()
+        });
+    }
+
+    if let Some(value) = client_id.and_then(|id| reqwest::header::HeaderValue::from_str(&id).ok()) {
+        headers.insert("X-Client-Id", value);
+    }
+    let header_pairs: Vec<(String, String)> = headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+
+    let timing = net::profile_get(
+        &parsed,
+        &header_pairs,
+        &user_agent(),
+        Duration::from_millis(request_timeout_ms),
+    )
+    .await
+    .map_err(|e| {
+        let error_msg = format!("Profiling {} failed: {}", url, e);
+        error!("{}", error_msg);
+        error_msg
+    })?;
+    info!(
+        "Profiled {}: dns {:.1}ms, connect {:.1}ms, tls {:.1}ms, ttfb {:.1}ms, download {:.1}ms",
+        url, timing.dns_ms, timing.connect_ms, timing.tls_ms, timing.ttfb_ms, timing.download_ms
+    );
+    Ok(timing)
+}
+
+#[tauri::command]
+async fn inspect_tls_certificate(state: State<'_, AppState>, host: String, port: u16) -> Result<TlsCertInfo, String> {
+    let _timing = time_command!(state);
+    let host = host.trim().trim_start_matches('[').trim_end_matches(']').to_string();
+    let info = net::inspect_certificate(&host, port, Duration::from_secs(10))
+        .await
+        .map_err(|e| {
+            error!("{}", e);
+            e
+        })?;
+    if let Some(warning) = &info.warning {
+        warn!("TLS certificate for {}:{}: {}", host, port, warning);
+    }
+    if let Some(trust_error) = &info.trust_error {
+        warn!("TLS certificate for {}:{} is untrusted: {}", host, port, trust_error);
+    }
+    Ok(info)
+}
+
+#[tauri::command]
+async fn resolve_host(state: State<'_, AppState>, host: String) -> Result<ResolveResult, String> {
+    let _timing = time_command!(state);
+    let result = net::resolve_host(&host, Duration::from_secs(10)).await.map_err(|e| {
+        warn!("{}", e);
+        e
+    })?;
+    debug!("{} resolved to {:?} in {}ms", result.host, result.addresses, result.elapsed_ms);
+    Ok(result)
+}
+
+#[tauri::command]
+async fn get_client_id(state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    state
+        .config
+        .lock()
+        .unwrap()
+        .client_id
+        .clone()
+        .ok_or_else(|| "Client ID has not been generated yet".to_string())
+}
+
+#[tauri::command]
+async fn get_app_data_dir(app: AppHandle) -> Result<String, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    match data_dir(&app) {
+        Ok(path) => {
+            let path_str = path.to_string_lossy().to_string();
+            info!("App data directory: {}", path_str);
+            Ok(path_str)
+        }
+        Err(error_msg) => {
+            error!("{}", error_msg);
+            Err(error_msg)
+        }
+    }
+}
+
+// Anything else (file:, smb:, javascript:, registered app protocols) would
+// hand a link rendered in the webview to a local handler
+const EXTERNAL_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+fn check_external_url(url: &str) -> Result<url::Url, String> {
+    let parsed = url::Url::parse(url.trim()).map_err(|e| format!("Invalid URL {:?}: {}", url, e))?;
+    if !EXTERNAL_URL_SCHEMES.contains(&parsed.scheme()) {
+        return Err(format!(
+            "Refusing to open {}: URLs must use {}",
+            parsed.scheme(),
+            EXTERNAL_URL_SCHEMES.join(", ")
+        ));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod external_url_tests {
+    use super::check_external_url;
+
+    #[test]
+    fn allows_web_urls() {
+        assert_eq!(check_external_url("https://example.com/docs").unwrap().scheme(), "https");
+        assert_eq!(check_external_url("http://localhost:8080").unwrap().scheme(), "http");
+    }
+
+    #[test]
+    fn refuses_file_urls() {
+        let error = check_external_url("file:///etc/passwd").unwrap_err();
+        assert!(error.contains("file"), "{}", error);
+    }
+
+    #[test]
+    fn refuses_javascript_urls() {
+        let error = check_external_url("javascript:alert(1)").unwrap_err();
+        assert!(error.contains("javascript"), "{}", error);
+    }
+
+    #[test]
+    fn refuses_malformed_urls() {
+        let error = check_external_url("not a url").unwrap_err();
+        assert!(error.starts_with("Invalid URL"), "{}", error);
+    }
+}
+
+#[tauri::command]
+async fn open_external_url(state: State<'_, AppState>, url: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let parsed = check_external_url(&url).inspect_err(|e| warn!("{}", e))?;
+    {
+        let config = state.config.lock().unwrap();
+        if config.kiosk_mode && !kiosk::is_url_allowed(&config.kiosk, parsed.as_str()) {
+            warn!("Kiosk mode blocked external URL {}", parsed);
+            return Err(kiosk::KIOSK_ERROR.to_string());
+        }
+    }
+    info!("Opening external URL: {}", parsed);
+    
+    match webbrowser::open(parsed.as_str()) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let error_msg = format!("Failed to open URL: {}", e);
+            error!("{}", error_msg);
+            Err(error_msg)
+        }
+    }
+}
+
+// Kiosk mode
+#[tauri::command]
+async fn get_kiosk_status(state: State<'_, AppState>) -> Result<KioskStatus, String> {
+    let _timing = time_command!(state);
+    Ok(KioskStatus::new(state.config.lock().unwrap().kiosk_mode))
+}
+
+// Entering sets the exit passphrase; leaving must present it
+#[tauri::command]
+async fn set_kiosk_mode(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+    passphrase: String,
+) -> Result<KioskStatus, String> {
+    let _timing = time_command!(state);
+    let (current, hash) = {
+        let config = state.config.lock().unwrap();
+        (config.kiosk_mode, config.kiosk.passphrase_hash.clone())
+    };
+    if current == enabled {
+        return Ok(KioskStatus::new(enabled));
+    }
+
+    let passphrase_hash = if enabled {
+        if passphrase.trim().is_empty() {
+            return Err("A passphrase is required to enter kiosk mode".to_string());
+        }
+        let hashed = tokio::task::spawn_blocking(move || kiosk::hash_passphrase(&passphrase))
+            .await
+            .map_err(|e| format!("Passphrase hashing failed: {}", e))??;
+        Some(hashed)
+    } else {
+        let verified = match hash {
+            Some(hash) => tokio::task::spawn_blocking(move || kiosk::verify_passphrase(&hash, &passphrase))
+                .await
+                .map_err(|e| format!("Passphrase check failed: {}", e))?,
+            None => false,
+        };
+        if !verified {
+            warn!("Rejected attempt to leave kiosk mode");
+            return Err("Incorrect kiosk passphrase".to_string());
+        }
+        None
+    };
+
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.kiosk_mode = enabled;
+        config.kiosk.passphrase_hash = passphrase_hash;
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+
+    if enabled {
+        for window in app.webview_windows().values() {
+            window.close_devtools();
+        }
+    }
+    info!("Kiosk mode {}", if enabled { "enabled" } else { "disabled" });
+    let status = KioskStatus::new(enabled);
+    let _ = app.emit("kiosk-mode-changed", &status);
+    Ok(status)
+}
+
+#[tauri::command]
+async fn test_notification(app: AppHandle) -> Result<NotificationResult, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let notification = app.notification();
+
+    let mut permission = notification
+        .permission_state()
+        .map_err(|e| format!("Failed to query notification permission: {}", e))?;
+    if matches!(permission, PermissionState::Prompt | PermissionState::PromptWithRationale) {
+        permission = notification
+            .request_permission()
+            .map_err(|e| format!("Failed to request notification permission: {}", e))?;
+    }
+
+    let permission_label = match permission {
+        PermissionState::Granted => "granted",
+        PermissionState::Denied => "denied",
+        PermissionState::Prompt | PermissionState::PromptWithRationale => "prompt",
+    }
+    .to_string();
+
+    if !matches!(permission, PermissionState::Granted) {
+        warn!("Notification permission is {}", permission_label);
+        return Ok(NotificationResult {
+            permission: permission_label,
+            sent: false,
+            guidance: Some(
+                "Notifications are disabled for StratMaster. Enable them in your system notification settings and try again."
+                    .to_string(),
+            ),
+        });
+    }
+
+    match notification
+        .builder()
+        .title("StratMaster")
+        .body("Test notification: notifications are working.")
+        .show()
+    {
+        Ok(_) => {
+            info!("Test notification sent");
+            Ok(NotificationResult {
+                permission: permission_label,
+                sent: true,
+                guidance: None,
+            })
+        }
+        Err(e) => {
+            warn!("Failed to send test notification: {}", e);
+            Ok(NotificationResult {
+                permission: permission_label,
+                sent: false,
+                guidance: Some(format!("The notification could not be delivered: {}", e)),
+            })
+        }
+    }
+}
+
+#[tauri::command] 
+async fn show_file_in_folder(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    info!("Showing file in folder: {}", path);
+    
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.exists() {
+        return Err(format!("File does not exist: {}", path));
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("explorer")
+            .args(["/select,", &path])
+            .spawn()
+            .map_err(|e| format!("Failed to open explorer: {}", e))?;
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .args(["-R", &path])
+            .spawn()
+            .map_err(|e| format!("Failed to open finder: {}", e))?;
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(path_buf.parent().unwrap_or(&path_buf))
+            .spawn()
+            .map_err(|e| format!("Failed to open file manager: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_local_server_status(app: AppHandle, state: State<'_, AppState>) -> Result<HashMap<String, bool>, String> {
+    let _timing = time_command!(state);
+    let policy = state.retry_policy.lock().unwrap().for_health_probe();
+    let client = http_client(&state)?;
+    let (timeout, services) = {
+        let config = state.config.lock().unwrap();
+        (Duration::from_millis(config.health_check_timeout_ms), config.status_endpoints.clone())
+    };
+    
+    // Probed concurrently, so the slowest service bounds the wait. Every
+    // service starts out down, which is what a probe that panics leaves it as.
+    let mut status: HashMap<String, bool> = services.iter().map(|service| (service.name.clone(), false)).collect();
+    let mut probes = tokio::task::JoinSet::new();
+    for StatusEndpoint { name: service, health_url: url } in services {
+        let app = app.clone();
+        let policy = policy.clone();
+        let client = client.clone();
+        probes.spawn(async move {
+            let started = Instant::now();
+            // A service that times out counts as down here
+            let (is_healthy, error) = match send_with_retry(&app, &policy, &url, || client.get(&url).timeout(timeout).send()).await {
+                Ok(response) => (response.status().is_success(), None),
+                Err(e) if e.starts_with(REQUEST_TIMED_OUT) => (false, Some(format!("timed out after {}", format_timeout(timeout)))),
+                Err(_) => (false, None),
+            };
+            let record = app.state::<AppState>().record_health(&service, is_healthy, Some(started.elapsed()), error);
+            emit_batched(&app, "health-record", record);
+            (service, is_healthy)
+        });
+    }
+    while let Some(result) = probes.join_next().await {
+        match result {
+            Ok((service, is_healthy)) => {
+                status.insert(service, is_healthy);
+            }
+            Err(e) => warn!("Service health probe failed: {}", e),
+        }
+    }
+    
+    Ok(status)
+}
+
+// Status monitor
+// Runs the `get_local_server_status` probes on a timer and emits the result
+// as "health-update", so the dashboard needn't poll. Each probe also emits
+// its usual "health-record". Starting again replaces the running loop.
+#[tauri::command]
+async fn start_health_monitor(app: AppHandle, state: State<'_, AppState>, interval_secs: u64) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if interval_secs == 0 {
+        return Err("Health monitor interval must be at least 1 second".to_string());
+    }
+    let task_app = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            let state = task_app.state::<AppState>();
+            if state.system_sleeping.load(Ordering::SeqCst) || state.monitoring_paused.load(Ordering::SeqCst) {
+                continue;
+            }
+            match get_local_server_status(task_app.clone(), task_app.state()).await {
+                Ok(status) => {
+                    let _ = task_app.emit("health-update", status);
+                }
+                Err(e) => debug!("Health monitor check failed: {}", e),
+            }
+        }
+    });
+    if let Some(previous) = state.health_monitor.lock().unwrap().replace(task) {
+        previous.abort();
+    }
+    info!("Started health monitor every {}s", interval_secs);
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_health_monitor(state: State<'_, AppState>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    stop_health_monitor_task(&state);
+    Ok(())
+}
+
+fn stop_health_monitor_task(state: &AppState) {
+    if let Some(task) = state.health_monitor.lock().unwrap().take() {
+        task.abort();
+        info!("Stopped health monitor");
+    }
+}
+
+#[tauri::command]
+async fn list_services(state: State<'_, AppState>) -> Result<Vec<StatusEndpoint>, String> {
+    let _timing = time_command!(state);
+    Ok(state.config.lock().unwrap().status_endpoints.clone())
+}
+
+// A service with the same name is replaced
+#[tauri::command]
+async fn register_service(app: AppHandle, state: State<'_, AppState>, service: StatusEndpoint) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if service.name.trim().is_empty() {
+        return Err("Service name must not be empty".to_string());
+    }
+    if !(service.health_url.starts_with("http://") || service.health_url.starts_with("https://")) {
+        return Err(format!("Service health URL must be http(s): {}", service.health_url));
+    }
+    info!("Registering service {} at {}", service.name, service.health_url);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.status_endpoints.retain(|existing| existing.name != service.name);
+        config.status_endpoints.push(service);
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn unregister_service(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        let before = config.status_endpoints.len();
+        config.status_endpoints.retain(|existing| existing.name != name);
+        if config.status_endpoints.len() == before {
+            return Err(format!("Unknown service: {}", name));
+        }
+        config.clone()
+    };
+    info!("Unregistered service {}", name);
+    persist_config(&app, &config)
+}
+
+// Event batching
+// Streams such as logs and SSE produce bursts of events that would each cost
+// a webview round trip. Events with a batch interval are queued and delivered
+// as one array payload at most once per interval; events without one are
+// emitted as they come, with their payload unwrapped.
+#[derive(Default)]
+struct EventBatcher {
+    pending: std::sync::Mutex<HashMap<String, Vec<serde_json::Value>>>,
+}
+
+fn emit_batched<T: Serialize>(app: &AppHandle, event: &str, payload: T) {
+    let interval_ms = app
+        .state::<AppState>()
+        .config
+        .lock()
+        .unwrap()
+        .event_batch_intervals_ms
+        .get(event)
+        .copied()
+        .unwrap_or(0);
+    if interval_ms == 0 {
+        let _ = app.emit(event, payload);
+        return;
+    }
+    let Ok(payload) = serde_json::to_value(payload) else {
+        return;
+    };
+
+    let state = app.state::<AppState>();
+    let mut pending = state.event_batcher.pending.lock().unwrap();
+    let queue = pending.entry(event.to_string()).or_default();
+    queue.push(payload);
+    // The first queued payload schedules the flush; later ones ride along
+    if queue.len() == 1 {
+        let app = app.clone();
+        let event = event.to_string();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+            let batch = app
+                .state::<AppState>()
+                .event_batcher
+                .pending
+                .lock()
+                .unwrap()
+                .remove(&event)
+                .unwrap_or_default();
+            if !batch.is_empty() {
+                let _ = app.emit(&event, batch);
+            }
+        });
+    }
+}
+
+#[tauri::command]
+async fn set_event_batch_interval(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    event: String,
+    ms: u64,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if ms > 10_000 {
+        return Err("Batch interval must be at most 10000ms".to_string());
+    }
+    info!("Batch interval for {} set to {}ms", event, ms);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.event_batch_intervals_ms.insert(event, ms);
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+// SSE streams
+// A backend `text/event-stream` endpoint read by the app, with each event
+// forwarded as an `sse-chunk` (batched like other streams, so a fast token
+// stream arrives as arrays of chunks). `sse-closed` follows when the stream
+// ends, with the error if it failed.
+// Streams outlive any request timeout; this only bounds a stream left open
+const SSE_STREAM_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+struct SseChunk {
+    stream: String,
+    #[serde(flatten)]
+    event: SseEvent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SseClosed {
+    stream: String,
+    error: Option<String>,
+}
+
+// Returns the stream ID carried by its events
+#[tauri::command]
+async fn open_sse_stream(app: AppHandle, state: State<'_, AppState>, path: String) -> Result<String, String> {
+    let _timing = time_command!(state);
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'));
+    let response = http_client(&state)?
+        .get(&url)
+        .headers(environment_headers(&state, &url))
+        .header(reqwest::header::ACCEPT, "text/event-stream")
+        .timeout(SSE_STREAM_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to open event stream {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Event stream {} returned {}", url, response.status()));
+    }
+
+    let stream = format!("sse-{}", generate_client_id());
+    info!("Opened event stream {} ({})", url, stream);
+    let id = stream.clone();
+    let forwarder = app.clone();
+    // Held until the task is recorded, so a stream that ends at once still
+    // finds its entry to remove
+    let mut streams = state.sse_streams.lock().unwrap();
+    let task = tauri::async_runtime::spawn(async move {
+        let error = forward_sse(&forwarder, &id, response).await.err();
+        if let Some(e) = &error {
+            warn!("Event stream {} failed: {}", id, e);
+        }
+        forwarder.state::<AppState>().sse_streams.lock().unwrap().remove(&id);
+        let _ = forwarder.emit("sse-closed", SseClosed { stream: id, error });
+    });
+    streams.insert(stream.clone(), task);
+    Ok(stream)
+}
+
+async fn forward_sse(app: &AppHandle, stream: &str, mut response: reqwest::Response) -> Result<(), String> {
+    let mut parser = SseParser::default();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        for event in parser.push(&chunk) {
+            emit_batched(
+                app,
+                "sse-chunk",
+                SseChunk {
+                    stream: stream.to_string(),
+                    event,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn close_sse_stream(app: AppHandle, state: State<'_, AppState>, stream: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if let Some(task) = state.sse_streams.lock().unwrap().remove(&stream) {
+        task.abort();
+        info!("Closed event stream {}", stream);
+        let _ = app.emit("sse-closed", SseClosed { stream, error: None });
+    }
+    Ok(())
+}
+
+fn stop_sse_streams(state: &AppState) {
+    for (_, task) in state.sse_streams.lock().unwrap().drain() {
+        task.abort();
+    }
+}
+
+// Metrics
+fn metrics_snapshot(state: &AppState) -> MetricsSnapshot {
+    let counts = state.health_counts.lock().unwrap().clone();
+    let history = state.health_history.lock().unwrap();
+    let mut services: Vec<ServiceMetrics> = counts
+        .into_iter()
+        .map(|(service, counts)| {
+            let latest = history.iter().rev().find(|record| record.service == service);
+            ServiceMetrics {
+                up: latest.map(|record| record.healthy).unwrap_or(false),
+                latency_ms: latest.and_then(|record| record.latency_ms),
+                checks_total: counts.checks,
+                check_failures_total: counts.failures,
+                service,
+            }
+        })
+        .collect();
+    services.sort_by(|a, b| a.service.cmp(&b.service));
+
+    let mut sys = sysinfo::System::new();
+    sys.refresh_memory();
+    let memory_total_bytes = Some(sys.total_memory()).filter(|total| *total > 0);
+    // Sampled by the monitoring loop; reading swap can spawn a process on Windows
+    let swap = state.last_swap.lock().unwrap().clone();
+    MetricsSnapshot {
+        services,
+        memory_total_bytes,
+        memory_available_bytes: memory_total_bytes.map(|_| sys.available_memory()),
+        swap_total_bytes: swap.as_ref().map(|swap| swap.total_bytes),
+        swap_used_bytes: swap.as_ref().map(|swap| swap.used_bytes),
+        requests_total: state.request_limiter.total.load(Ordering::Relaxed),
+        requests_inflight: state.request_limiter.inflight.load(Ordering::SeqCst) as u64,
+    }
+}
+
+#[tauri::command]
+async fn get_metrics_prometheus(state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    Ok(metrics_snapshot(&state).render_prometheus())
+}
+
+// Minimal HTTP/1.1 responder for scrapers: `GET /metrics` and nothing else
+async fn serve_metrics(app: AppHandle, addr: String) {
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to bind metrics endpoint on {}: {}", addr, e);
+            return;
+        }
+    };
+    if listener.local_addr().map(|local| !local.ip().is_loopback()).unwrap_or(false) {
+        warn!("Metrics endpoint on {} is reachable from other machines", addr);
+    }
+    info!("Serving Prometheus metrics at http://{}/metrics", addr);
+
+    loop {
+        let Ok((mut socket, _)) = listener.accept().await else {
+            continue;
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            let mut request = [0u8; 1024];
+            let Ok(read) = socket.read(&mut request).await else {
+                return;
+            };
+            let is_metrics = std::str::from_utf8(&request[..read])
+                .ok()
+                .and_then(|text| text.lines().next())
+                .map(|line| line.starts_with("GET /metrics ") || line.starts_with("GET /metrics?"))
+                .unwrap_or(false);
+
+            let response = if is_metrics {
+                let body = metrics_snapshot(&app.state::<AppState>()).render_prometheus();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        });
+    }
+}
+
+// Command latency
+// Every command starts a timer that records into a per-command histogram when
+// it finishes (or is dropped). One short lock per call, so the overhead is
+// negligible next to the IPC round trip.
+const COMMAND_SAMPLE_WINDOW: usize = 256;
+
+/// `count`, `avg_ms` and `max_ms` cover the whole session; `p95_ms` the last
+/// `COMMAND_SAMPLE_WINDOW` calls, so it tracks current behaviour.
+#[derive(Debug, Clone, Serialize)]
+struct CommandStat {
+    count: u64,
+    avg_ms: f64,
+    p95_ms: f64,
+    max_ms: f64,
+}
+
+#[derive(Debug, Default)]
+struct CommandTiming {
+    count: u64,
+    total_ms: f64,
+    max_ms: f64,
+    recent_ms: VecDeque<f64>,
+}
+
+#[derive(Debug, Default)]
+struct CommandMetrics {
+    commands: std::sync::Mutex<HashMap<&'static str, CommandTiming>>,
+}
+
+impl CommandMetrics {
+    fn record(&self, command: &'static str, elapsed: Duration) {
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        let mut commands = self.commands.lock().unwrap();
+        let timing = commands.entry(command).or_default();
+        timing.count += 1;
+        timing.total_ms += elapsed_ms;
+        timing.max_ms = timing.max_ms.max(elapsed_ms);
+        if timing.recent_ms.len() == COMMAND_SAMPLE_WINDOW {
+            timing.recent_ms.pop_front();
+        }
+        timing.recent_ms.push_back(elapsed_ms);
+    }
+
+    fn stats(&self) -> HashMap<String, CommandStat> {
+        let commands = self.commands.lock().unwrap();
+        commands
+            .iter()
+            .map(|(command, timing)| {
+                let mut recent: Vec<f64> = timing.recent_ms.iter().copied().collect();
+                recent.sort_by(|a, b| a.total_cmp(b));
+                let p95_index = (recent.len() * 95).div_ceil(100).saturating_sub(1);
+                let stat = CommandStat {
+                    count: timing.count,
+                    avg_ms: timing.total_ms / timing.count.max(1) as f64,
+                    p95_ms: recent.get(p95_index).copied().unwrap_or(0.0),
+                    max_ms: timing.max_ms,
+                };
+                (command.to_string(), stat)
+            })
+            .collect()
+    }
+}
+
+struct CommandTimer {
+    metrics: Arc<CommandMetrics>,
+    command: &'static str,
+    started: Instant,
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        self.metrics.record(self.command, self.started.elapsed());
+    }
+}
+
+#[tauri::command]
+async fn get_command_metrics(state: State<'_, AppState>) -> Result<HashMap<String, CommandStat>, String> {
+    let _timing = time_command!(state);
+    Ok(state.command_metrics.stats())
+}
+
+#[cfg(test)]
+mod command_timing_tests {
+    use super::*;
+
+    async fn sample_command(state: &AppState) -> &'static str {
+        let _timing = time_command!(state);
+        command_name!()
+    }
+
+    #[tokio::test]
+    async fn commands_are_timed_under_their_own_name() {
+        let state = AppState::default();
+        assert_eq!(sample_command(&state).await, "sample_command");
+        sample_command(&state).await;
+        let stats = state.command_metrics.stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats["sample_command"].count, 2);
+    }
+
+    #[test]
+    fn plain_functions_are_named_too() {
+        fn helper() -> &'static str {
+            command_name!()
+        }
+        assert_eq!(helper(), "helper");
+    }
+}
+
+// Render timing
+// The frontend reports requestAnimationFrame deltas in batches. A frame
+// counts as dropped when it took over 1.5x the median, which adapts to the
+// display's refresh rate.
+const RENDER_SAMPLE_WINDOW: usize = 3600;
+const MAX_FRAME_BATCH: usize = 10_000;
+// Longer deltas are a hidden tab or a paused debugger, not a slow frame
+const MAX_FRAME_TIME_MS: f64 = 5000.0;
+const DROPPED_FRAME_FACTOR: f64 = 1.5;
+const LOW_FPS_WARNING: f64 = 30.0;
+
+#[derive(Debug, Default)]
+struct FrameTimings {
+    frames_ms: std::sync::Mutex<VecDeque<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RenderStats {
+    fps_avg: f64,
+    /// FPS of the slowest 5% of frames.
+    fps_p5: f64,
+    dropped_frames: usize,
+    frames: usize,
+}
+
+impl FrameTimings {
+    fn record(&self, frame_times_ms: &[f64]) {
+        let mut frames = self.frames_ms.lock().unwrap();
+        for &frame in frame_times_ms.iter().filter(|frame| frame.is_finite() && **frame > 0.0 && **frame <= MAX_FRAME_TIME_MS) {
+            if frames.len() == RENDER_SAMPLE_WINDOW {
+                frames.pop_front();
+            }
+            frames.push_back(frame);
+        }
+    }
+
+    fn stats(&self) -> Option<RenderStats> {
+        let mut sorted: Vec<f64> = self.frames_ms.lock().unwrap().iter().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+        let median = sorted[sorted.len() / 2];
+        let p95_index = (sorted.len() * 95).div_ceil(100).saturating_sub(1);
+        Some(RenderStats {
+            fps_avg: 1000.0 / mean,
+            fps_p5: 1000.0 / sorted[p95_index],
+            dropped_frames: sorted.iter().filter(|frame| **frame > median * DROPPED_FRAME_FACTOR).count(),
+            frames: sorted.len(),
+        })
+    }
+}
+
+#[tauri::command]
+async fn report_frame_timing(state: State<'_, AppState>, frame_times_ms: Vec<f64>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if frame_times_ms.len() > MAX_FRAME_BATCH {
+        return Err(format!("At most {} frame times can be reported at once", MAX_FRAME_BATCH));
+    }
+    state.frame_timings.record(&frame_times_ms);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_render_stats(state: State<'_, AppState>) -> Result<Option<RenderStats>, String> {
+    let _timing = time_command!(state);
+    Ok(state.frame_timings.stats())
+}
+
+// Low FPS is reported alongside what else was slow or scarce at the time
+fn render_check(state: &AppState) -> DiagnosticCheck {
+    const NAME: &str = "render_performance";
+    let Some(stats) = state.frame_timings.stats() else {
+        return DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, "No frame timings reported yet");
+    };
+    let summary = format!(
+        "{:.0} FPS average, {:.0} FPS in the slowest 5% of frames, {} of {} frames dropped",
+        stats.fps_avg, stats.fps_p5, stats.dropped_frames, stats.frames
+    );
+    if stats.fps_p5 >= LOW_FPS_WARNING {
+        return DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, summary);
+    }
+
+    let mut context = Vec::new();
+    let slowest = state
+        .command_metrics
+        .stats()
+        .into_iter()
+        .max_by(|(_, a), (_, b)| a.p95_ms.total_cmp(&b.p95_ms));
+    if let Some((command, stat)) = slowest {
+        context.push(format!("slowest command {} at {:.0}ms p95", command, stat.p95_ms));
+    }
+    let mut sys = sysinfo::System::new();
+    sys.refresh_memory();
+    if sys.total_memory() > 0 {
+        let used = 1.0 - sys.available_memory() as f64 / sys.total_memory() as f64;
+        context.push(format!("memory {:.0}% used", used * 100.0));
+    }
+    if sys.used_swap() > 0 {
+        context.push(format!("{} MB swapped", sys.used_swap() / (1024 * 1024)));
+    }
+    let message = if context.is_empty() { summary } else { format!("{} ({})", summary, context.join(", ")) };
+    DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, message)
+}
+
+// Performance trace
+// A rolling window of coarse samples, dumped on demand right after a user
+// reproduces slowness. One sysinfo refresh per second keeps the cost low;
+// GPU utilisation may spawn `nvidia-smi`, so it is read less often.
+const PERF_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const PERF_GPU_SAMPLE_EVERY: u64 = 5;
+const MAX_PERF_TRACE_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Serialize)]
+struct PerfSample {
+    timestamp_ms: u64,
+    cpu_percent: f32,
+    memory_used_bytes: u64,
+    gpu_percent: Option<f32>,
+    /// Proxied requests that completed during this sample's interval.
+    requests: usize,
+    request_latency_avg_ms: Option<f64>,
+    request_latency_max_ms: Option<f64>,
+}
+
+struct PerfTrace {
+    buffer: Duration,
+    samples: VecDeque<PerfSample>,
+    pending_latencies_ms: Vec<f64>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+#[derive(Serialize)]
+struct PerfTraceDump<'a> {
+    buffer_secs: u64,
+    interval_ms: u64,
+    samples: &'a VecDeque<PerfSample>,
+}
+
+impl AppState {
+    // Only collected while a trace runs
+    fn note_request_latency(&self, elapsed: Duration) {
+        if let Some(trace) = self.perf_trace.lock().unwrap().as_mut() {
+            trace.pending_latencies_ms.push(elapsed.as_secs_f64() * 1000.0);
+        }
+    }
+}
+
+async fn run_perf_sampler(app: AppHandle) {
+    let mut sys = sysinfo::System::new();
+    let mut interval = tokio::time::interval(PERF_SAMPLE_INTERVAL);
+    let mut gpu_percent = None;
+    for tick in 0u64.. {
+        interval.tick().await;
+        // CPU usage is a delta between refreshes, so the first reading is always 0
+        sys.refresh_cpu_usage();
+        sys.refresh_memory();
+        if tick % PERF_GPU_SAMPLE_EVERY == 0 {
+            gpu_percent = tokio::task::spawn_blocking(system::gpu_utilization).await.unwrap_or(None);
+        }
+
+        let state = app.state::<AppState>();
+        let mut trace = state.perf_trace.lock().unwrap();
+        let Some(trace) = trace.as_mut() else {
+            return;
+        };
+        let latencies = std::mem::take(&mut trace.pending_latencies_ms);
+        let sample = PerfSample {
+            timestamp_ms: unix_time_ms(),
+            cpu_percent: sys.global_cpu_usage(),
+            memory_used_bytes: sys.used_memory(),
+            gpu_percent,
+            requests: latencies.len(),
+            request_latency_avg_ms: (!latencies.is_empty())
+                .then(|| latencies.iter().sum::<f64>() / latencies.len() as f64),
+            request_latency_max_ms: latencies.iter().copied().reduce(f64::max),
+        };
+        let cutoff = sample.timestamp_ms.saturating_sub(trace.buffer.as_millis() as u64);
+        while trace.samples.front().is_some_and(|oldest| oldest.timestamp_ms < cutoff) {
+            trace.samples.pop_front();
+        }
+        trace.samples.push_back(sample);
+    }
+}
+
+// Restarting discards the previous buffer
+#[tauri::command]
+async fn start_perf_trace(app: AppHandle, state: State<'_, AppState>, buffer_secs: u64) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if !(1..=MAX_PERF_TRACE_SECS).contains(&buffer_secs) {
+        return Err(format!("Trace buffer must be between 1 and {} seconds", MAX_PERF_TRACE_SECS));
+    }
+    let trace = PerfTrace {
+        buffer: Duration::from_secs(buffer_secs),
+        samples: VecDeque::new(),
+        pending_latencies_ms: Vec::new(),
+        task: tauri::async_runtime::spawn(run_perf_sampler(app.clone())),
+    };
+    if let Some(previous) = state.perf_trace.lock().unwrap().replace(trace) {
+        previous.task.abort();
+    }
+    info!("Started performance trace with a {}s buffer", buffer_secs);
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_perf_trace(state: State<'_, AppState>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if let Some(trace) = state.perf_trace.lock().unwrap().take() {
+        trace.task.abort();
+        info!("Stopped performance trace");
+    }
+    Ok(())
+}
+
+// Writes the buffered window to a JSON file and keeps tracing
+#[tauri::command]
+async fn dump_perf_trace(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    let contents = {
+        let trace = state.perf_trace.lock().unwrap();
+        let trace = trace.as_ref().ok_or_else(|| "No performance trace is running".to_string())?;
+        serde_json::to_string_pretty(&PerfTraceDump {
+            buffer_secs: trace.buffer.as_secs(),
+            interval_ms: PERF_SAMPLE_INTERVAL.as_millis() as u64,
+            samples: &trace.samples,
+        })
+        .map_err(|e| format!("Failed to serialize performance trace: {}", e))?
+    };
+
+    let dir = data_dir(&app)?.join("traces");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create traces directory: {}", e))?;
+    let path = dir.join(format!("perf-{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    info!("Wrote performance trace to {}", path.display());
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Local services
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize)]
+struct ServiceLogLine {
+    service: String,
+    stream: &'static str,
+    line: String,
+}
+
+// Shared by a service's stdout and stderr readers; the first matching line
+// fires `ready`. When both readers end without a match the sender is
+// dropped, which the waiting side sees as the service exiting.
+struct LogMatcher {
+    regex: regex::Regex,
+    ready: std::sync::Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
+}
+
+fn service_running(state: &AppState, name: &str) -> bool {
+    state
+        .local_services
+        .lock()
+        .unwrap()
+        .get_mut(name)
+        .is_some_and(|child| matches!(child.try_wait(), Ok(None)))
+}
+
+// Err with the exit status once the service's process has gone away
+fn ensure_service_alive(state: &AppState, name: &str) -> Result<(), String> {
+    let mut services = state.local_services.lock().unwrap();
+    match services.get_mut(name).map(|child| child.try_wait()) {
+        Some(Ok(None)) => Ok(()),
+        Some(Ok(Some(status))) => Err(format!("process exited with {}", status)),
+        Some(Err(e)) => Err(format!("failed to query process: {}", e)),
+        None => Err("process is not running".to_string()),
+    }
+}
+
+async fn forward_service_output(
+    app: AppHandle,
+    service: String,
+    stream: &'static str,
+    output: impl tokio::io::AsyncRead + Unpin,
+    matcher: Option<Arc<LogMatcher>>,
+) {
+    use tokio::io::AsyncBufReadExt;
+
+    let mut lines = tokio::io::BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Some(matcher) = &matcher {
+            if matcher.regex.is_match(&line) {
+                if let Some(ready) = matcher.ready.lock().unwrap().take() {
+                    let _ = ready.send(());
+                }
+            }
+        }
+        emit_batched(&app, "service-log", ServiceLogLine { service: service.clone(), stream, line });
+    }
+}
+
+// Returns the log-match signal when the service's ready check needs one
+fn spawn_service(
+    app: &AppHandle,
+    service: &ServiceDefinition,
+    gpu_env: &[(&'static str, String)],
+) -> Result<Option<tokio::sync::oneshot::Receiver<()>>, String> {
+    let (matcher, ready) = match &service.ready_check {
+        ReadyCheck::LogMatch { pattern } => {
+            let regex = regex::Regex::new(pattern)
+                .map_err(|e| format!("Invalid ready pattern for service {}: {}", service.name, e))?;
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            let matcher = LogMatcher {
+                regex,
+                ready: std::sync::Mutex::new(Some(sender)),
+            };
+            (Some(Arc::new(matcher)), Some(receiver))
+        }
+        _ => (None, None),
+    };
+
+    let mut command = tokio::process::Command::new(&service.command);
+    command
+        .args(&service.args)
+        // A service's own env wins over the GPU selection
+        .envs(gpu_env.iter().map(|(name, value)| (*name, value)))
+        .envs(&service.env)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = &service.working_dir {
+        command.current_dir(dir);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start service {}: {}", service.name, e))?;
+    info!("Started service {} (pid {:?})", service.name, child.id());
+
+    if let Some(stdout) = child.stdout.take() {
+        tauri::async_runtime::spawn(forward_service_output(app.clone(), service.name.clone(), "stdout", stdout, matcher.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tauri::async_runtime::spawn(forward_service_output(app.clone(), service.name.clone(), "stderr", stderr, matcher));
+    }
+    app.state::<AppState>()
+        .local_services
+        .lock()
+        .unwrap()
+        .insert(service.name.clone(), child);
+    Ok(ready)
+}
+
+async fn wait_until_ready(
+    state: &AppState,
+    service: &ServiceDefinition,
+    log_ready: Option<tokio::sync::oneshot::Receiver<()>>,
+) -> Result<(), String> {
+    match &service.ready_check {
+        check @ (ReadyCheck::HttpOk { .. } | ReadyCheck::TcpOpen { .. }) => {
+            let client = http_client(state)?;
+            while probe_ready_check(&client, check).await != Some(true) {
+                ensure_service_alive(state, &service.name)?;
+                tokio::time::sleep(READY_POLL_INTERVAL).await;
+            }
+            Ok(())
+        }
+        ReadyCheck::LogMatch { pattern } => match log_ready {
+            Some(ready) => ready
+                .await
+                .map_err(|_| format!("output ended without a line matching {}", pattern)),
+            None => Err("log matcher was not set up".to_string()),
+        },
+        ReadyCheck::DelayMs { ms } => {
+            tokio::time::sleep(Duration::from_millis(*ms)).await;
+            ensure_service_alive(state, &service.name)
+        }
+    }
+}
+
+#[tauri::command]
+async fn identify_port_owner(state: State<'_, AppState>, port: u16) -> Result<Option<PortOwner>, String> {
+    let _timing = time_command!(state);
+    tokio::task::spawn_blocking(move || system::port_owner(port))
+        .await
+        .map_err(|e| format!("Port owner lookup failed: {}", e))?
+}
+
+// The port a service is expected to listen on, from its ready check
+fn service_port(service: &ServiceDefinition) -> Option<u16> {
+    match &service.ready_check {
+        ReadyCheck::TcpOpen { port } => Some(*port),
+        ReadyCheck::HttpOk { url } => url::Url::parse(url).ok()?.port_or_known_default(),
+        _ => None,
+    }
+}
+
+// Names whoever else holds the service's port, the usual cause of a start failure
+async fn port_conflict_hint(state: &AppState, service: &ServiceDefinition) -> Option<String> {
+    let port = service_port(service)?;
+    let own_pid = state
+        .local_services
+        .lock()
+        .unwrap()
+        .get(&service.name)
+        .and_then(|child| child.id());
+    let owner = tokio::task::spawn_blocking(move || system::port_owner(port)).await.ok()?;
+    match owner {
+        Ok(Some(owner)) if Some(owner.pid) != own_pid => Some(format!(
+            "port {} is held by {} (pid {}{}); stop it and try again",
+            port,
+            owner.process_name,
+            owner.pid,
+            owner.executable_path.map(|path| format!(", {}", path)).unwrap_or_default()
+        )),
+        Ok(_) => None,
+        Err(e) => Some(e),
+    }
+}
+
+// Starts configured services in dependency order, waiting for each one's
+// ready check before starting anything that depends on it. Returns the
+// services started; already-running or already-healthy ones are left alone.
+#[tauri::command]
+async fn start_local_services(app: AppHandle, state: State<'_, AppState>) -> Result<StartResult, String> {
+    let _timing = time_command!(state);
+    start_services(&app, &state).await
+}
+
+// What launch runs when auto_start_services is on: the configured backend
+// (API and MCP servers) as child processes, tracked in `local_services` so
+// they are stopped on exit. Returns the services started; healthy ones are
+// not started twice. Any service that fails makes this an error naming it,
+// after the rest have been started.
+#[tauri::command]
+async fn start_backend_services(app: AppHandle, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let _timing = time_command!(state);
+    let result = start_services(&app, &state).await?;
+    if result.failed.is_empty() {
+        return Ok(result.started);
+    }
+    let failures: Vec<String> = result
+        .failed
+        .iter()
+        .map(|failure| format!("{}: {}", failure.name, failure.reason))
+        .collect();
+    Err(format!("Backend services failed to start: {}", failures.join("; ")))
+}
+
+async fn start_services(app: &AppHandle, state: &AppState) -> Result<StartResult, String> {
+    let definitions = state.config.lock().unwrap().services.clone();
+    let order = services::start_order(&definitions)?;
+    // Refuse up front rather than failing halfway through the order
+    let issues: Vec<ServiceIssue> = order.iter().flat_map(|service| services::validate(service)).collect();
+    if !issues.is_empty() {
+        let details: Vec<String> = issues
+            .iter()
+            .map(|issue| format!("{} {}: {}", issue.service, issue.field, issue.message))
+            .collect();
+        let error_msg = format!("Services not started, fix their definitions first: {}", details.join("; "));
+        error!("{}", error_msg);
+        return Err(error_msg);
+    }
+    let gpu_env = service_gpu_env(state).await;
+    let gpu_env = &gpu_env;
+
+    let result = start_in_order(
+        order.into_iter().cloned().collect(),
+        |service| async move {
+            if service_running(state, &service.name) {
+                debug!("Service {} is already running", service.name);
+                return true;
+            }
+            // Started outside the app, e.g. from a terminal; a second copy
+            // would only fail on the port
+            let answering = ready_check_passes(state, &service.ready_check).await == Some(true);
+            if answering {
+                debug!("Service {} is already answering; not starting another", service.name);
+            }
+            answering
+        },
+        |service| async move {
+            let failure = match spawn_service(app, &service, gpu_env) {
+                Ok(log_ready) => {
+                    let timeout = Duration::from_millis(service.ready_timeout_ms);
+                    let failure = match tokio::time::timeout(timeout, wait_until_ready(state, &service, log_ready)).await {
+                        Ok(Ok(())) => None,
+                        Ok(Err(e)) => Some(format!("ready check failed: {}", e)),
+                        Err(_) => Some(format!("not ready after {}ms", service.ready_timeout_ms)),
+                    };
+                    // A half-started process would hold its port and be
+                    // skipped as running on the next attempt
+                    if failure.is_some() {
+                        stop_service(state, &service.name).await;
+                    }
+                    failure
+                }
+                Err(e) => Some(e),
+            };
+            match failure {
+                Some(mut reason) => {
+                    if let Some(hint) = port_conflict_hint(state, &service).await {
+                        reason = format!("{} ({})", reason, hint);
+                    }
+                    Err(reason)
+                }
+                None => Ok(()),
+            }
+        },
+    )
+    .await;
+    info!(
+        "Services: {} started, {} failed, {} already running",
+        result.started.len(),
+        result.failed.len(),
+        result.skipped.len()
+    );
+    Ok(result)
+}
+
+// Start order puts dependencies first, so a failure is recorded before any
+// dependent is reached; services that don't depend on it still start.
+// `is_up` says whether a service is already running, `start` starts one and
+// waits until it is ready.
+async fn start_in_order<U, UF, S, SF>(order: Vec<ServiceDefinition>, mut is_up: U, mut start: S) -> StartResult
+where
+    U: FnMut(ServiceDefinition) -> UF,
+    UF: Future<Output = bool>,
+    S: FnMut(ServiceDefinition) -> SF,
+    SF: Future<Output = Result<(), String>>,
+{
+    let mut result = StartResult::default();
+    for service in order {
+        if is_up(service.clone()).await {
+            result.skipped.push(service.name.clone());
+            continue;
+        }
+        if let Some(dependency) = result.failed_dependency(&service) {
+            let reason = format!("dependency {} did not start", dependency);
+            warn!("Service {} not started: {}", service.name, reason);
+            result.failed.push(ServiceFailure { name: service.name.clone(), reason });
+            continue;
+        }
+        let name = service.name.clone();
+        match start(service).await {
+            Ok(()) => {
+                info!("Service {} is ready", name);
+                result.started.push(name);
+            }
+            Err(reason) => {
+                error!("Service {} failed to start: {}", name, reason);
+                result.failed.push(ServiceFailure { name, reason });
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod start_order_tests {
+    use super::{start_in_order, ServiceDefinition};
+
+    fn service(name: &str, depends_on: &[&str]) -> ServiceDefinition {
+        serde_json::from_value(serde_json::json!({ "name": name, "command": name, "depends_on": depends_on })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_started_failed_and_skipped_services() {
+        let order = vec![
+            service("db", &[]),
+            service("cache", &[]),
+            service("api", &["db"]),
+            service("worker", &["cache"]),
+            service("web", &["api"]),
+        ];
+        let mut started = Vec::new();
+        let result = start_in_order(
+            order,
+            |service| async move { service.name == "db" },
+            |service| {
+                started.push(service.name.clone());
+                async move {
+                    match service.name.as_str() {
+                        "cache" => Err("port 6379 in use".to_string()),
+                        _ => Ok(()),
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(result.skipped, ["db"]);
+        assert_eq!(result.started, ["api", "web"]);
+        let failed: Vec<(&str, &str)> = result.failed.iter().map(|f| (f.name.as_str(), f.reason.as_str())).collect();
+        assert_eq!(failed, [("cache", "port 6379 in use"), ("worker", "dependency cache did not start")]);
+        // A service behind a failed dependency is never attempted
+        assert_eq!(started, ["cache", "api", "web"]);
+    }
+}
+
+// Removes the service from the running set, killing its process if it is
+// still up. Returns whether there was one to stop.
+async fn stop_service(state: &AppState, name: &str) -> bool {
+    let child = state.local_services.lock().unwrap().remove(name);
+    match child {
+        Some(mut child) => {
+            if matches!(child.try_wait(), Ok(None)) {
+                let _ = child.kill().await;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+async fn service_gpu_env(state: &AppState) -> Vec<(&'static str, String)> {
+    match detected_gpus(state, false).await {
+        Ok(gpus) => selected_gpu(state, &gpus).map(|gpu| gpu.device_env()).unwrap_or_default(),
+        Err(e) => {
+            warn!("GPU detection failed, services see every GPU: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Stops the service if it is running, starts it again and waits for its
+// ready check. Dependents are left running.
+#[tauri::command]
+async fn restart_service(app: AppHandle, name: String) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _timing = time_command!(state);
+    let service = state
+        .config
+        .lock()
+        .unwrap()
+        .services
+        .iter()
+        .find(|service| service.name == name)
+        .cloned()
+        .ok_or_else(|| format!("Unknown service: {}", name))?;
+    if stop_service(&state, &name).await {
+        info!("Stopped service {} for restart", name);
+    }
+
+    let gpu_env = service_gpu_env(&state).await;
+    let log_ready = spawn_service(&app, &service, &gpu_env)?;
+    let timeout = Duration::from_millis(service.ready_timeout_ms);
+    let failure = match tokio::time::timeout(timeout, wait_until_ready(&state, &service, log_ready)).await {
+        Ok(Ok(())) => {
+            info!("Service {} restarted", name);
+            return Ok(());
+        }
+        Ok(Err(e)) => format!("Service {} failed its ready check after restart: {}", name, e),
+        Err(_) => format!("Service {} not ready {}ms after restart", name, service.ready_timeout_ms),
+    };
+    // Left running, it would count as up and block the next restart
+    stop_service(&state, &name).await;
+    Err(failure)
+}
+
+#[tauri::command]
+async fn validate_service_definitions(state: State<'_, AppState>) -> Result<Vec<ServiceIssue>, String> {
+    let _timing = time_command!(state);
+    let definitions = state.config.lock().unwrap().services.clone();
+    let issues: Vec<ServiceIssue> = tokio::task::spawn_blocking(move || definitions.iter().flat_map(services::validate).collect())
+        .await
+        .map_err(|e| format!("Service validation failed: {}", e))?;
+    Ok(issues)
+}
+
+// Probes every configured service at once and lists them in start order,
+// dependencies first, so a slow dependency shows up ahead of the services
+// it slows down.
+#[tauri::command]
+async fn measure_service_chain_latency(state: State<'_, AppState>) -> Result<Vec<HopLatency>, String> {
+    let _timing = time_command!(state);
+    let definitions = state.config.lock().unwrap().services.clone();
+    let order: Vec<ServiceDefinition> = services::start_order(&definitions)?.into_iter().cloned().collect();
+    let client = http_client(&state)?;
+
+    let mut probes = tokio::task::JoinSet::new();
+    for (index, service) in order.into_iter().enumerate() {
+        let client = client.clone();
+        // Services without a network check count as healthy while their process runs
+        let running = service_running(&state, &service.name);
+        probes.spawn(async move {
+            let started = Instant::now();
+            let healthy = probe_ready_check(&client, &service.ready_check).await.unwrap_or(running);
+            let probed = ready_check_probes(&service.ready_check);
+            let hop = HopLatency {
+                service: service.name,
+                latency_ms: (probed && healthy).then(|| started.elapsed().as_secs_f64() * 1000.0),
+                healthy,
+                depends_on: service.depends_on,
+            };
+            (index, hop)
+        });
+    }
+
+    let mut hops = Vec::new();
+    while let Some(result) = probes.join_next().await {
+        if let Ok(hop) = result {
+            hops.push(hop);
+        }
+    }
+    hops.sort_by_key(|(index, _)| *index);
+    let hops: Vec<HopLatency> = hops.into_iter().map(|(_, hop)| hop).collect();
+    if let Some(slowest) = hops
+        .iter()
+        .filter_map(|hop| hop.latency_ms.map(|latency| (hop, latency)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+    {
+        debug!("Slowest service hop: {} ({:.1}ms)", slowest.0.service, slowest.1);
+    }
+    Ok(hops)
+}
+
+#[tauri::command]
+async fn detect_container_runtimes(state: State<'_, AppState>) -> Result<Vec<ContainerRuntime>, String> {
+    let _timing = time_command!(state);
+    let runtimes = tokio::task::spawn_blocking(containers::detect)
+        .await
+        .map_err(|e| format!("Container runtime detection failed: {}", e))?;
+    for runtime in &runtimes {
+        info!(
+            "Found {} {} ({})",
+            runtime.name,
+            runtime.version,
+            if runtime.running { "running" } else { "not running" }
+        );
+    }
+    Ok(runtimes)
+}
+
+// Runs a service as a container on the first runtime whose daemon answers
+// and waits for its ready check, removing the container if it never gets
+// there. Returns the container ID.
+#[tauri::command]
+async fn start_service_container(app: AppHandle, spec: ContainerSpec) -> Result<String, String> {
+    let state = app.state::<AppState>();
+    let _timing = time_command!(state);
+    spec.run_args()?;
+    if state.service_containers.lock().unwrap().contains_key(&spec.name) || service_running(&state, &spec.name) {
+        return Err(format!("Service {} is already running", spec.name));
+    }
+
+    let launch = spec.clone();
+    let container = tokio::task::spawn_blocking(move || {
+        let runtime = containers::available_runtime()
+            .ok_or_else(|| "No container runtime is available: install Docker or Podman and make sure it is running".to_string())?;
+        let id = containers::run_detached(&runtime.name, &launch)?;
+        Ok::<_, String>(RunningContainer { runtime: runtime.name, id })
+    })
+    .await
+    .map_err(|e| format!("Container start failed: {}", e))??;
+    info!("Started container {} for service {} on {}", container.id, spec.name, container.runtime);
+    state
+        .service_containers
+        .lock()
+        .unwrap()
+        .insert(spec.name.clone(), container.clone());
+
+    let timeout = Duration::from_millis(spec.ready_timeout_ms);
+    let failure = match tokio::time::timeout(timeout, wait_until_container_ready(&state, &container, &spec.ready_check)).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(format!("ready check failed: {}", e)),
+        Err(_) => Some(format!("not ready after {}ms", spec.ready_timeout_ms)),
+    };
+    if let Some(reason) = failure {
+        state.service_containers.lock().unwrap().remove(&spec.name);
+        let removed = container.clone();
+        let _ = tokio::task::spawn_blocking(move || containers::remove(&removed)).await;
+        error!("Service container {} failed to start: {}", spec.name, reason);
+        return Err(format!("Service {} failed to start: {}", spec.name, reason));
+    }
+    info!("Service container {} is ready", spec.name);
+    Ok(container.id)
+}
+
+// Container logs
+// `logs --follow` per container, forwarded like native service output. The
+// newest lines are kept so a frontend that attaches late (or reloads) can
+// backfill; followers end on their own when the container stops.
+const CONTAINER_LOG_BACKFILL_LINES: usize = 500;
+
+#[derive(Debug, Clone, Serialize)]
+struct ContainerLogLine {
+    container_id: String,
+    stream: &'static str,
+    line: String,
+}
+
+#[derive(Default)]
+struct ContainerLogs {
+    followers: std::sync::Mutex<HashMap<String, tokio::process::Child>>,
+    backfill: std::sync::Mutex<HashMap<String, VecDeque<ContainerLogLine>>>,
+}
+
+fn valid_container_ref(container_id: &str) -> bool {
+    !container_id.is_empty()
+        && !container_id.starts_with('-')
+        && container_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+// The runtime that started the container, else whichever one is up
+async fn runtime_for_container(state: &AppState, container_id: &str) -> Result<String, String> {
+    let known = state
+        .service_containers
+        .lock()
+        .unwrap()
+        .values()
+        .find(|container| container.id == container_id || container.id.starts_with(container_id))
+        .map(|container| container.runtime.clone());
+    if let Some(runtime) = known {
+        return Ok(runtime);
+    }
+    tokio::task::spawn_blocking(containers::available_runtime)
+        .await
+        .map_err(|e| format!("Container runtime detection failed: {}", e))?
+        .map(|runtime| runtime.name)
+        .ok_or_else(|| "No container runtime is available: install Docker or Podman and make sure it is running".to_string())
+}
+
+async fn forward_container_output(app: AppHandle, container_id: String, stream: &'static str, output: impl tokio::io::AsyncRead + Unpin) {
+    use tokio::io::AsyncBufReadExt;
+
+    let mut lines = tokio::io::BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let entry = ContainerLogLine { container_id: container_id.clone(), stream, line };
+        {
+            let state = app.state::<AppState>();
+            let mut backfill = state.container_logs.backfill.lock().unwrap();
+            let buffered = backfill.entry(container_id.clone()).or_default();
+            if buffered.len() >= CONTAINER_LOG_BACKFILL_LINES {
+                buffered.pop_front();
+            }
+            buffered.push_back(entry.clone());
+        }
+        emit_batched(&app, "container-log", entry);
+    }
+}
+
+// Forwards the container's output as `container-log` events, starting from
+// its last CONTAINER_LOG_BACKFILL_LINES lines. Following twice is a no-op.
+#[tauri::command]
+async fn follow_container_logs(app: AppHandle, container_id: String) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _timing = time_command!(state);
+    if !valid_container_ref(&container_id) {
+        return Err(format!("Invalid container ID {:?}", container_id));
+    }
+    if state.container_logs.followers.lock().unwrap().contains_key(&container_id) {
+        return Ok(());
+    }
+    let runtime = runtime_for_container(&state, &container_id).await?;
+
+    let tail = CONTAINER_LOG_BACKFILL_LINES.to_string();
+    let mut child = tokio::process::Command::new(&runtime)
+        .args(["logs", "--follow", "--tail", &tail, &container_id])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to follow logs of container {}: {}", container_id, e))?;
+    let pid = child.id();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    {
+        let mut followers = state.container_logs.followers.lock().unwrap();
+        if followers.contains_key(&container_id) {
+            return Ok(());
+        }
+        followers.insert(container_id.clone(), child);
+    }
+    // The runtime replays its own tail, so the old buffer would duplicate it
+    state.container_logs.backfill.lock().unwrap().remove(&container_id);
+    info!("Following logs of container {} on {}", container_id, runtime);
+
+    let stdout = stdout.map(|stdout| tauri::async_runtime::spawn(forward_container_output(app.clone(), container_id.clone(), "stdout", stdout)));
+    let stderr = stderr.map(|stderr| tauri::async_runtime::spawn(forward_container_output(app.clone(), container_id.clone(), "stderr", stderr)));
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for reader in [stdout, stderr].into_iter().flatten() {
+            let _ = reader.await;
+        }
+        // Output ends when the container stops or the follower is stopped;
+        // leave a newer follower for the same container alone
+        let state = app.state::<AppState>();
+        let mut followers = state.container_logs.followers.lock().unwrap();
+        if followers.get(&container_id).is_some_and(|child| child.id() == pid) {
+            if let Some(mut child) = followers.remove(&container_id) {
+                let _ = child.start_kill();
+            }
+            debug!("Stopped following logs of container {}", container_id);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_following_container_logs(app: AppHandle, container_id: String) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _timing = time_command!(state);
+    if let Some(mut child) = state.container_logs.followers.lock().unwrap().remove(&container_id) {
+        child
+            .start_kill()
+            .map_err(|e| format!("Failed to stop following container {}: {}", container_id, e))?;
+        info!("Stopped following logs of container {}", container_id);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_container_log_backfill(state: State<'_, AppState>, container_id: String) -> Result<Vec<ContainerLogLine>, String> {
+    let _timing = time_command!(state);
+    Ok(state
+        .container_logs
+        .backfill
+        .lock()
+        .unwrap()
+        .get(&container_id)
+        .map(|lines| lines.iter().cloned().collect())
+        .unwrap_or_default())
+}
+
+fn stop_container_log_followers(state: &AppState) {
+    for (_, mut child) in state.container_logs.followers.lock().unwrap().drain() {
+        let _ = child.start_kill();
+    }
+}
+
+async fn container_alive(container: &RunningContainer) -> Result<(), String> {
+    let probed = container.clone();
+    let running = tokio::task::spawn_blocking(move || containers::is_running(&probed.runtime, &probed.id))
+        .await
+        .map_err(|e| format!("failed to query container: {}", e))?;
+    if running {
+        Ok(())
+    } else {
+        Err("container exited".to_string())
+    }
+}
+
+// The same checks as `wait_until_ready`, with the container's state standing
+// in for the process and its logs polled rather than streamed
+async fn wait_until_container_ready(state: &AppState, container: &RunningContainer, check: &ReadyCheck) -> Result<(), String> {
+    match check {
+        ReadyCheck::HttpOk { .. } | ReadyCheck::TcpOpen { .. } => {
+            let client = http_client(state)?;
+            while probe_ready_check(&client, check).await != Some(true) {
+                container_alive(container).await?;
+                tokio::time::sleep(READY_POLL_INTERVAL).await;
+            }
+            Ok(())
+        }
+        ReadyCheck::LogMatch { pattern } => {
+            let regex = regex::Regex::new(pattern).map_err(|e| format!("invalid ready pattern: {}", e))?;
+            loop {
+                let probed = container.clone();
+                let logs = tokio::task::spawn_blocking(move || containers::logs(&probed.runtime, &probed.id))
+                    .await
+                    .map_err(|e| format!("failed to read container logs: {}", e))?;
+                if logs.is_some_and(|logs| logs.lines().any(|line| regex.is_match(line))) {
+                    return Ok(());
+                }
+                container_alive(container).await?;
+                tokio::time::sleep(READY_POLL_INTERVAL).await;
+            }
+        }
+        ReadyCheck::DelayMs { ms } => {
+            tokio::time::sleep(Duration::from_millis(*ms)).await;
+            container_alive(container).await
+        }
+    }
+}
+
+#[tauri::command]
+async fn stop_local_services(app: AppHandle) -> Result<u32, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    // Each container removal waits on the runtime CLI
+    let handle = app.clone();
+    tokio::task::spawn_blocking(move || stop_services(&handle.state::<AppState>()))
+        .await
+        .map_err(|e| format!("Stopping services failed: {}", e))
+}
+
+// Blocks on `docker rm` for each container; off the async runtime, or at exit
+fn stop_services(state: &AppState) -> u32 {
+    // Stopped on purpose, so nothing is left to recover
+    state.service_recovery.lock().unwrap().clear();
+    let mut services = state.local_services.lock().unwrap();
+    let mut stopped = 0;
+    for (name, mut child) in services.drain() {
+        if matches!(child.try_wait(), Ok(None)) {
+            match child.start_kill() {
+                Ok(()) => {
+                    info!("Stopped service {}", name);
+                    stopped += 1;
+                }
+                Err(e) => warn!("Failed to stop service {}: {}", name, e),
+            }
+        }
+    }
+    drop(services);
+    let service_containers: Vec<(String, RunningContainer)> = state.service_containers.lock().unwrap().drain().collect();
+    for (name, container) in service_containers {
+        match containers::remove(&container) {
+            Ok(()) => {
+                info!("Stopped service container {} ({})", name, container.id);
+                stopped += 1;
+            }
+            Err(e) => warn!("Failed to stop service container {}: {}", name, e),
+        }
+    }
+    stopped
+}
+
+// Monitoring
+const MONITOR_INTERVAL: Duration = Duration::from_secs(15);
+// Re-arm the temperature warning only after cooling this far below the threshold
+const CPU_TEMP_HYSTERESIS: f64 = 5.0;
+
+#[derive(Debug, Clone, Serialize)]
+struct CpuTemperatureWarning {
+    celsius: f64,
+    threshold_celsius: f64,
+}
+
+#[tauri::command]
+async fn get_cpu_temperature(state: State<'_, AppState>) -> Result<Option<f64>, String> {
+    let _timing = time_command!(state);
+    tokio::task::spawn_blocking(system::cpu_temperature)
+        .await
+        .map_err(|e| format!("Temperature read failed: {}", e))?
+}
+
+// Swapping out faster than this during inference means the model doesn't fit in RAM
+const SWAP_OUT_PRESSURE_BYTES_PER_SEC: f64 = 8.0 * 1024.0 * 1024.0;
+// Used where the OS exposes no paging rate: growth of used swap between two checks
+const SWAP_GROWTH_PRESSURE_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+struct SwapPressure {
+    used_bytes: u64,
+    total_bytes: u64,
+    swap_out_rate: Option<f64>,
+    growth_bytes: u64,
+}
+
+#[tauri::command]
+async fn get_swap_info(state: State<'_, AppState>) -> Result<SwapInfo, String> {
+    let _timing = time_command!(state);
+    let swap = tokio::task::spawn_blocking(system::swap_info)
+        .await
+        .map_err(|e| format!("Swap read failed: {}", e))??;
+    *state.last_swap.lock().unwrap() = Some(swap.clone());
+    Ok(swap)
+}
+
+// Energy mode
+// In low-power mode the monitoring loop does its work on one tick in this
+// many. Reading the mode spawns pmset/powercfg/powerprofilesctl, so the loop
+// reuses the last reading for POWER_MODE_MAX_AGE; waking from sleep, when the
+// power source has most likely changed, forces a fresh one.
+const LOW_POWER_TICK_DIVISOR: u64 = 4;
+const POWER_MODE_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+struct PowerModeChange {
+    mode: PowerMode,
+    previous: PowerMode,
+    /// Offered when the current hardware profile is heavier than the mode suits.
+    suggested_profile: Option<HardwareProfile>,
+}
+
+async fn refresh_power_mode(app: &AppHandle) -> Result<PowerMode, String> {
+    let mode = tokio::task::spawn_blocking(power::power_mode)
+        .await
+        .map_err(|e| format!("Power mode query failed: {}", e))??;
+    let state = app.state::<AppState>();
+    *state.power_mode_read_at.lock().unwrap() = Some(Instant::now());
+    let previous = std::mem::replace(&mut *state.power_mode.lock().unwrap(), mode);
+    if mode != previous {
+        info!("Power mode changed from {:?} to {:?}", previous, mode);
+        let profile = state.config.lock().unwrap().hardware_profile.clone();
+        let suggested_profile = (mode == PowerMode::LowPower && !matches!(profile, HardwareProfile::Lightweight))
+            .then_some(HardwareProfile::Lightweight);
+        let _ = app.emit("power-mode-changed", PowerModeChange { mode, previous, suggested_profile });
+    }
+    Ok(mode)
+}
+
+// The last reading while it is fresh enough, else a new one
+async fn current_power_mode(app: &AppHandle) -> PowerMode {
+    let state = app.state::<AppState>();
+    let fresh = state
+        .power_mode_read_at
+        .lock()
+        .unwrap()
+        .is_some_and(|read_at| read_at.elapsed() < POWER_MODE_MAX_AGE);
+    if fresh {
+        return *state.power_mode.lock().unwrap();
+    }
+    match refresh_power_mode(app).await {
+        Ok(mode) => mode,
+        Err(e) => {
+            debug!("{}", e);
+            // Failing commands aren't retried every tick either
+            *state.power_mode_read_at.lock().unwrap() = Some(Instant::now());
+            *state.power_mode.lock().unwrap()
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_power_mode(app: AppHandle) -> Result<PowerMode, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    match refresh_power_mode(&app).await {
+        Ok(mode) => Ok(mode),
+        Err(e) => {
+            debug!("{}; assuming normal power mode", e);
+            Ok(PowerMode::Normal)
+        }
+    }
+}
+
+// Background checks that run for the lifetime of the app
+async fn run_monitoring_loop(app: AppHandle) {
+    let mut interval = tokio::time::interval(MONITOR_INTERVAL);
+    let mut temperature_warned = false;
+    let mut swap_warned = false;
+    let mut previous_swap_used: Option<u64> = None;
+    let mut tick: u64 = 0;
+    loop {
+        interval.tick().await;
+        if app.state::<AppState>().system_sleeping.load(Ordering::SeqCst) {
+            continue;
+        }
+        let power_mode = current_power_mode(&app).await;
+        tick += 1;
+        if power_mode == PowerMode::LowPower && tick % LOW_POWER_TICK_DIVISOR != 0 {
+            continue;
+        }
+        let paused = app.state::<AppState>().monitoring_paused.load(Ordering::SeqCst);
+        if paused && !app.state::<AppState>().config.lock().unwrap().critical_monitors_on_blur {
+            continue;
+        }
+
+        if !paused {
+            let (transport, ws_path) = {
+                let config = app.state::<AppState>().config.lock().unwrap();
+                (config.health_transport, config.health_ws_path.clone())
+            };
+            if transport == HealthTransport::WebSocket && !health_ws_running(&app) {
+                if let Err(e) = start_health_ws(&app, &ws_path).await {
+                    warn!("{}; falling back to polling", e);
+                }
+            }
+            // Pushed updates replace polling only while the socket is actually up
+            let ws_live = app.state::<AppState>().health_ws_connected.load(Ordering::SeqCst);
+            if !(transport == HealthTransport::WebSocket && ws_live) {
+                let _ = check_api_health(app.clone(), app.state()).await;
+            }
+            check_service_recovery(&app).await;
+        }
+
+        let threshold_celsius = app.state::<AppState>().config.lock().unwrap().cpu_temp_warning_celsius;
+        match get_cpu_temperature(app.state()).await {
+            Ok(Some(celsius)) if celsius >= threshold_celsius => {
+                if !temperature_warned {
+                    warn!("CPU temperature {:.1}°C is above {:.1}°C", celsius, threshold_celsius);
+                    let _ = app.emit("cpu-temp-warning", CpuTemperatureWarning { celsius, threshold_celsius });
+                    temperature_warned = true;
+                }
+            }
+            Ok(Some(celsius)) if celsius < threshold_celsius - CPU_TEMP_HYSTERESIS => temperature_warned = false,
+            Ok(_) => {}
+            Err(e) => debug!("{}", e),
+        }
+
+        match get_swap_info(app.state()).await {
+            Ok(swap) => {
+                let growth_bytes = previous_swap_used.map_or(0, |used| swap.used_bytes.saturating_sub(used));
+                let climbing = swap.swap_out_rate.is_some_and(|rate| rate >= SWAP_OUT_PRESSURE_BYTES_PER_SEC)
+                    || growth_bytes >= SWAP_GROWTH_PRESSURE_BYTES;
+                if climbing && !swap_warned {
+                    warn!(
+                        "System is swapping heavily ({} of {} bytes used, +{} since last check); the model may be too large",
+                        swap.used_bytes, swap.total_bytes, growth_bytes
+                    );
+                    let _ = app.emit(
+                        "swap-pressure",
+                        SwapPressure {
+                            used_bytes: swap.used_bytes,
+                            total_bytes: swap.total_bytes,
+                            swap_out_rate: swap.swap_out_rate,
+                            growth_bytes,
+                        },
+                    );
+                }
+                swap_warned = climbing;
+                previous_swap_used = Some(swap.used_bytes);
+            }
+            Err(e) => debug!("{}", e),
+        }
+    }
+}
+
+// Service recovery
+// Services the app started that have `auto_recover` are probed on every
+// monitoring tick. After `unhealthy_threshold` failed checks in a row they
+// are restarted with growing backoff, at most `max_attempts` times; the count
+// resets once a service has stayed healthy for `stable_after_ms`.
+#[derive(Debug, Default)]
+struct RecoveryState {
+    consecutive_failures: u32,
+    attempts: u32,
+    last_attempt: Option<Instant>,
+    restarting: bool,
+    gave_up: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ServiceRecoveryEvent {
+    service: String,
+    attempt: u32,
+    max_attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// The process must be running and, where the ready check names one, its
+// endpoint must answer
+async fn probe_service_health(state: &AppState, service: &ServiceDefinition) -> bool {
+    service_running(state, &service.name) && ready_check_passes(state, &service.ready_check).await.unwrap_or(true)
+}
+
+// Whether the service answers its network ready check right now; `None` for
+// checks that only make sense while watching a process the app started
+async fn ready_check_passes(state: &AppState, check: &ReadyCheck) -> Option<bool> {
+    match http_client(state) {
+        Ok(client) => probe_ready_check(&client, check).await,
+        Err(_) => ready_check_probes(check).then_some(false),
+    }
+}
+
+fn ready_check_probes(check: &ReadyCheck) -> bool {
+    matches!(check, ReadyCheck::HttpOk { .. } | ReadyCheck::TcpOpen { .. })
+}
+
+// One attempt at a network ready check, shared by the start, restart,
+// container, recovery and latency paths; `None` when the check has nothing
+// to probe
+async fn probe_ready_check(client: &reqwest::Client, check: &ReadyCheck) -> Option<bool> {
+    match check {
+        ReadyCheck::HttpOk { url } => Some(matches!(
+            client.get(url).timeout(BACKEND_PROBE_TIMEOUT).send().await,
+            Ok(response) if response.status().is_success()
+        )),
+        ReadyCheck::TcpOpen { port } => Some(matches!(
+            tokio::time::timeout(BACKEND_PROBE_TIMEOUT, tokio::net::TcpStream::connect(("127.0.0.1", *port))).await,
+            Ok(Ok(_))
+        )),
+        ReadyCheck::LogMatch { .. } | ReadyCheck::DelayMs { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod ready_probe_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn http_check_passes_only_on_success() {
+        let client = reqwest::Client::new();
+        let ok = mock_http::serve(vec![(Duration::ZERO, mock_http::response("200 OK", &[], ""))]).await;
+        let failing = mock_http::serve(vec![(Duration::ZERO, mock_http::response("503 Service Unavailable", &[], ""))]).await;
+        assert_eq!(probe_ready_check(&client, &ReadyCheck::HttpOk { url: ok }).await, Some(true));
+        assert_eq!(probe_ready_check(&client, &ReadyCheck::HttpOk { url: failing }).await, Some(false));
+    }
+
+    #[tokio::test]
+    async fn tcp_check_follows_the_listener() {
+        let client = reqwest::Client::new();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(probe_ready_check(&client, &ReadyCheck::TcpOpen { port }).await, Some(true));
+        drop(listener);
+        assert_eq!(probe_ready_check(&client, &ReadyCheck::TcpOpen { port }).await, Some(false));
+    }
+
+    #[tokio::test]
+    async fn process_checks_have_nothing_to_probe() {
+        let client = reqwest::Client::new();
+        assert_eq!(probe_ready_check(&client, &ReadyCheck::DelayMs { ms: 10 }).await, None);
+        assert_eq!(probe_ready_check(&client, &ReadyCheck::LogMatch { pattern: "ready".into() }).await, None);
+    }
+}
+
+async fn check_service_recovery(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let (services, policy) = {
+        let config = state.config.lock().unwrap();
+        let services: Vec<ServiceDefinition> = config.services.iter().filter(|service| service.auto_recover).cloned().collect();
+        (services, config.service_recovery.clone())
+    };
+    for service in services {
+        // Services that were never started, or were stopped, stay down. One
+        // whose restart failed to spawn is still in recovery: that attempt
+        // counts, and the next check retries or gives up.
+        let tracked = state.local_services.lock().unwrap().contains_key(&service.name);
+        let recovering = state
+            .service_recovery
+            .lock()
+            .unwrap()
+            .get(&service.name)
+            .is_some_and(|recovery| recovery.attempts > 0);
+        if !tracked && !recovering {
+            state.service_recovery.lock().unwrap().remove(&service.name);
+            continue;
+        }
+        if state.service_recovery.lock().unwrap().get(&service.name).is_some_and(|recovery| recovery.restarting) {
+            continue;
+        }
+        let healthy = probe_service_health(&state, &service).await;
+
+        let attempt = {
+            let mut recovery = state.service_recovery.lock().unwrap();
+            let entry = recovery.entry(service.name.clone()).or_default();
+            if healthy {
+                entry.consecutive_failures = 0;
+                let settled = !entry
+                    .last_attempt
+                    .is_some_and(|at| at.elapsed() < Duration::from_millis(policy.stable_after_ms));
+                if settled {
+                    *entry = RecoveryState::default();
+                }
+                continue;
+            }
+            entry.consecutive_failures += 1;
+            if entry.gave_up || entry.consecutive_failures < policy.unhealthy_threshold {
+                continue;
+            }
+            if entry
+                .last_attempt
+                .is_some_and(|at| at.elapsed() < Duration::from_millis(policy.backoff_ms(entry.attempts)))
+            {
+                continue;
+            }
+            if entry.attempts >= policy.max_attempts {
+                entry.gave_up = true;
+                None
+            } else {
+                entry.attempts += 1;
+                entry.last_attempt = Some(Instant::now());
+                entry.restarting = true;
+                Some(entry.attempts)
+            }
+        };
+
+        let Some(attempt) = attempt else {
+            let reason = format!("gave up after {} restart attempts", policy.max_attempts);
+            error!("Auto-recovery of service {} {}", service.name, reason);
+            state.record_health(&service.name, false, None, Some(format!("auto-recovery {}", reason)));
+            let _ = app.emit(
+                "service-recovery-gave-up",
+                ServiceRecoveryEvent {
+                    service: service.name.clone(),
+                    attempt: policy.max_attempts,
+                    max_attempts: policy.max_attempts,
+                    error: Some(reason),
+                },
+            );
+            continue;
+        };
+        warn!("Service {} is unhealthy; restart attempt {}/{}", service.name, attempt, policy.max_attempts);
+        state.record_health(
+            &service.name,
+            false,
+            None,
+            Some(format!("auto-recovery attempt {}/{}", attempt, policy.max_attempts)),
+        );
+        let _ = app.emit(
+            "service-recovery-attempt",
+            ServiceRecoveryEvent {
+                service: service.name.clone(),
+                attempt,
+                max_attempts: policy.max_attempts,
+                error: None,
+            },
+        );
+
+        // Restarts wait on the ready check, which mustn't hold up the monitoring loop
+        let app = app.clone();
+        let max_attempts = policy.max_attempts;
+        tauri::async_runtime::spawn(async move {
+            let started = Instant::now();
+            let result = restart_service(app.clone(), service.name.clone()).await;
+            let state = app.state::<AppState>();
+            if let Some(recovery) = state.service_recovery.lock().unwrap().get_mut(&service.name) {
+                recovery.restarting = false;
+            }
+            let event = ServiceRecoveryEvent {
+                service: service.name.clone(),
+                attempt,
+                max_attempts,
+                error: result.as_ref().err().cloned(),
+            };
+            match result {
+                Ok(()) => {
+                    info!("Service {} recovered on attempt {}", service.name, attempt);
+                    state.record_health(
+                        &service.name,
+                        true,
+                        Some(started.elapsed()),
+                        Some(format!("recovered on attempt {}", attempt)),
+                    );
+                    let _ = app.emit("service-recovered", event);
+                }
+                Err(e) => {
+                    warn!("Restart attempt {} of service {} failed: {}", attempt, service.name, e);
+                    state.record_health(&service.name, false, None, Some(e));
+                }
+            }
+        });
+    }
+}
+
+// `policy` replaces the recovery policy shared by every service. Changing a
+// service's setting clears its attempt count, including after giving up.
+#[tauri::command]
+async fn set_service_recovery(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    service: String,
+    auto_recover: bool,
+    policy: Option<RecoveryPolicy>,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if let Some(policy) = &policy {
+        if policy.unhealthy_threshold == 0 {
+            return Err("unhealthy_threshold must be at least 1".to_string());
+        }
+    }
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        let definition = config
+            .services
+            .iter_mut()
+            .find(|definition| definition.name == service)
+            .ok_or_else(|| format!("Unknown service: {}", service))?;
+        definition.auto_recover = auto_recover;
+        if let Some(policy) = policy {
+            config.service_recovery = policy;
+        }
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    state.service_recovery.lock().unwrap().remove(&service);
+    info!("Auto-recovery {} for service {}", if auto_recover { "enabled" } else { "disabled" }, service);
+    Ok(())
+}
+
+// Pause on blur
+// With `pause_on_blur` set, health monitoring stops while no window has
+// focus. The paused event says whether the window was minimised or hidden,
+// or is merely in the background (and so possibly still on screen).
+#[derive(Debug, Clone, Serialize)]
+struct MonitoringPaused {
+    reason: &'static str,
+}
+
+fn handle_focus_change(window: &Window, focused: bool) {
+    let app = window.app_handle();
+    let state = app.state::<AppState>();
+    if focused {
+        resume_monitoring(app);
+        return;
+    }
+    if !state.config.lock().unwrap().pause_on_blur {
+        return;
+    }
+    // Focus moving between our own windows isn't a blur of the app
+    if app.webview_windows().values().any(|other| other.is_focused().unwrap_or(false)) {
+        return;
+    }
+    let reason = if window.is_minimized().unwrap_or(false) {
+        "minimized"
+    } else if !window.is_visible().unwrap_or(true) {
+        "hidden"
+    } else {
+        "background"
+    };
+    if !state.monitoring_paused.swap(true, Ordering::SeqCst) {
+        info!("Pausing health monitoring ({})", reason);
+        let _ = app.emit("monitoring-paused", MonitoringPaused { reason });
+    }
+}
+
+fn resume_monitoring(app: &AppHandle) {
+    if app.state::<AppState>().monitoring_paused.swap(false, Ordering::SeqCst) {
+        info!("Resuming health monitoring");
+        let _ = app.emit("monitoring-resumed", ());
+    }
+}
+
+#[tauri::command]
+async fn set_pause_on_blur(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+    keep_critical_monitors: bool,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    info!(
+        "Pause on blur {} (critical monitors {})",
+        if enabled { "enabled" } else { "disabled" },
+        if keep_critical_monitors { "kept" } else { "paused too" }
+    );
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.pause_on_blur = enabled;
+        config.critical_monitors_on_blur = keep_critical_monitors;
+        config.clone()
+    };
+    if !enabled {
+        resume_monitoring(&app);
+    }
+    persist_config(&app, &config)
+}
+
+// Sleep and wake
+// Connections held across a suspend are dead but look alive until they time
+// out, so waking drops everything cached about the backend and re-probes.
+async fn handle_power_event(app: AppHandle, event: PowerEvent) {
+    let state = app.state::<AppState>();
+    match event {
+        PowerEvent::Sleep => {
+            info!("System is going to sleep; pausing monitoring");
+            state.system_sleeping.store(true, Ordering::SeqCst);
+        }
+        PowerEvent::Wake => {
+            info!("System woke; resetting backend connections");
+            state.health_status.lock().unwrap().clear();
+            flush_dns(&state, "system woke");
+            // The monitoring loop reopens it if the transport is still selected
+            stop_health_ws(&state);
+            *state.power_mode_read_at.lock().unwrap() = None;
+            state.system_sleeping.store(false, Ordering::SeqCst);
+            let healthy = check_api_health(app.clone(), app.state()).await.is_ok();
+            let _ = app.emit("system-woke", serde_json::json!({ "healthy": healthy }));
+        }
+    }
+}
+
+// Health websocket
+type HealthSocket = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+fn health_ws_running(app: &AppHandle) -> bool {
+    app.state::<AppState>().health_ws_task.lock().unwrap().is_some()
+}
+
+async fn open_health_socket(app: &AppHandle, path: &str) -> Result<HealthSocket, String> {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let state = app.state::<AppState>();
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let http_url = format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'));
+    let ws_url = if let Some(rest) = http_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = http_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        return Err(format!("Unsupported API base URL for websocket: {}", base_url));
+    };
+
+    let mut request = ws_url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| format!("Invalid health websocket URL {}: {}", ws_url, e))?;
+    request.headers_mut().extend(environment_headers(&state, &http_url));
+    if let Ok(value) = reqwest::header::HeaderValue::from_str(&user_agent()) {
+        request.headers_mut().insert(reqwest::header::USER_AGENT, value);
+    }
+
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| format!("Failed to connect health websocket {}: {}", ws_url, e))?;
+    info!("Connected health websocket {}", ws_url);
+    Ok(socket)
+}
+
+// Establishes the first connection before returning so callers learn
+// whether the transport works; reconnects are handled by the task.
+async fn start_health_ws(app: &AppHandle, path: &str) -> Result<(), String> {
+    let socket = open_health_socket(app, path).await?;
+    let state = app.state::<AppState>();
+    state.health_ws_connected.store(true, Ordering::SeqCst);
+    let task = tauri::async_runtime::spawn(run_health_ws(app.clone(), path.to_string(), socket));
+    if let Some(previous) = state.health_ws_task.lock().unwrap().replace(task) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+fn stop_health_ws(state: &AppState) {
+    if let Some(task) = state.health_ws_task.lock().unwrap().take() {
+        task.abort();
+        info!("Stopped health websocket");
+    }
+    state.health_ws_connected.store(false, Ordering::SeqCst);
+}
+
+async fn run_health_ws(app: AppHandle, path: String, socket: HealthSocket) {
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let mut socket = Some(socket);
+    let mut attempt = 0;
+    loop {
+        if let Some(mut connected) = socket.take() {
+            attempt = 0;
+            app.state::<AppState>().health_ws_connected.store(true, Ordering::SeqCst);
+            while let Some(message) = connected.next().await {
+                match message {
+                    Ok(Message::Text(text)) => handle_health_ws_message(&app, &text),
+                    Ok(Message::Close(_)) | Err(_) => break,
+                    Ok(_) => {}
+                }
+            }
+            app.state::<AppState>().health_ws_connected.store(false, Ordering::SeqCst);
+            warn!("Health websocket disconnected; reconnecting");
+        }
+
+        let delay = app.state::<AppState>().retry_policy.lock().unwrap().delay_for_attempt(attempt);
+        attempt = attempt.saturating_add(1);
+        tokio::time::sleep(delay).await;
+        match open_health_socket(&app, &path).await {
+            Ok(reconnected) => socket = Some(reconnected),
+            Err(e) => debug!("{}", e),
+        }
+    }
+}
+
+// Accepts `{"status": "ok" | "healthy" | "up"}` or `{"healthy": true}`
+fn handle_health_ws_message(app: &AppHandle, text: &str) {
+    let Ok(message) = serde_json::from_str::<serde_json::Value>(text) else {
+        debug!("Ignoring non-JSON health message");
+        return;
+    };
+    let healthy = message
+        .get("healthy")
+        .and_then(serde_json::Value::as_bool)
+        .or_else(|| {
+            message
+                .get("status")
+                .and_then(serde_json::Value::as_str)
+                .map(|status| matches!(status.to_ascii_lowercase().as_str(), "ok" | "healthy" | "up"))
+        });
+    let Some(healthy) = healthy else {
+        return;
+    };
+    let record = app.state::<AppState>().record_health("api", healthy, None, None);
+    emit_batched(app, "health-record", record);
+}
+
+#[tauri::command]
+async fn connect_health_ws(app: AppHandle, state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.health_transport = HealthTransport::WebSocket;
+        config.health_ws_path = path.clone();
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    // On failure the transport stays selected: monitoring keeps polling and retries the socket
+    start_health_ws(&app, &path).await
+}
+
+#[tauri::command]
+async fn set_health_transport(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    transport: HealthTransport,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    info!("Health transport set to {:?}", transport);
+    if transport == HealthTransport::Poll {
+        stop_health_ws(&state);
+    }
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.health_transport = transport;
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+// Backends
+const BACKEND_PROBE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+// Well-known local backends probed on their default ports. llama.cpp's server
+// also defaults to 8080, which collides with the StratMaster API, so it has to
+// be added to `backends` in the config explicitly.
+const WELL_KNOWN_BACKENDS: &[(&str, &str, BackendKind, &str)] = &[
+    ("stratmaster", "StratMaster API", BackendKind::StratMaster, "http://localhost:8080"),
+    ("ollama", "Ollama", BackendKind::Ollama, "http://localhost:11434"),
+    ("lm-studio", "LM Studio", BackendKind::OpenAiCompatible, "http://localhost:1234"),
+    ("vllm", "vLLM", BackendKind::OpenAiCompatible, "http://localhost:8000"),
+];
+
+// Configured backends first; well-known ones are added unless an entry with the same id exists.
+fn known_backends(config: &AppConfig) -> Vec<(BackendConfig, bool)> {
+    let mut backends: Vec<(BackendConfig, bool)> =
+        config.backends.iter().cloned().map(|backend| (backend, false)).collect();
+    for (id, name, kind, base_url) in WELL_KNOWN_BACKENDS {
+        if !backends.iter().any(|(backend, _)| backend.id == *id) {
+            backends.push((
+                BackendConfig {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    kind: *kind,
+                    base_url: base_url.to_string(),
+                },
+                true,
+            ));
+        }
+    }
+    backends
+}
+
+#[tauri::command]
+async fn list_backends(state: State<'_, AppState>) -> Result<Vec<BackendDescriptor>, String> {
+    let _timing = time_command!(state);
+    let config = state.config.lock().unwrap().clone();
+    let client = http_client(&state)?;
+
+    let mut probes = tokio::task::JoinSet::new();
+    for (index, (backend, detected)) in known_backends(&config).into_iter().enumerate() {
+        let client = client.clone();
+        probes.spawn(async move {
+            let url = format!("{}{}", backend.base_url.trim_end_matches('/'), backend.kind.probe_path());
+            let reachable = matches!(
+                client.get(&url).timeout(BACKEND_PROBE_TIMEOUT).send().await,
+                Ok(r) if r.status().is_success()
+            );
+            (index, backend, detected, reachable)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = probes.join_next().await {
+        if let Ok(result) = result {
+            results.push(result);
+        }
+    }
+    results.sort_by_key(|(index, ..)| *index);
+
+    // Undetected well-known backends are only listed while they are the active selection
+    Ok(results
+        .into_iter()
+        .map(|(_, backend, detected, reachable)| BackendDescriptor {
+            active: config.active_backend.as_deref() == Some(backend.id.as_str()),
+            id: backend.id,
+            name: backend.name,
+            kind: backend.kind,
+            base_url: backend.base_url,
+            reachable,
+            detected,
+        })
+        .filter(|backend| !backend.detected || backend.reachable || backend.active)
+        .collect())
+}
+
+#[tauri::command]
+async fn select_backend(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        let (backend, _) = known_backends(&config)
+            .into_iter()
+            .find(|(backend, _)| backend.id == id)
+            .ok_or_else(|| format!("Unknown backend: {}", id))?;
+
+        info!("Selecting backend {} ({:?}) at {}", backend.id, backend.kind, backend.base_url);
+        *state.api_base_url.lock().unwrap() = backend.base_url.clone();
+        *state.backend_kind.lock().unwrap() = Some(backend.kind);
+        config.api_base_url = backend.base_url;
+        config.active_backend = Some(backend.id);
+        config.active_environment = None;
+        config.clone()
+    };
+    *state.model_cache.lock().unwrap() = None;
+    state.response_cache.invalidate(None);
+    flush_dns(&state, "backend changed");
+
+    persist_config(&app, &config)?;
+    let _ = app.emit("backend-changed", &id);
+    Ok(())
+}
+
+// Browses mDNS for backends on other machines. A timeout with no answers
+// (including networks that block multicast) returns an empty list.
+#[tauri::command]
+async fn discover_backends_mdns(state: State<'_, AppState>, timeout_ms: u64) -> Result<Vec<DiscoveredBackend>, String> {
+    let _timing = time_command!(state);
+    let timeout = Duration::from_millis(timeout_ms.clamp(100, 30_000));
+    info!("Browsing for {} for {}ms", discovery::SERVICE_TYPE, timeout.as_millis());
+
+    let discovered = tokio::task::spawn_blocking(move || discovery::browse(timeout))
+        .await
+        .map_err(|e| format!("mDNS discovery task failed: {}", e))?
+        .unwrap_or_else(|e| {
+            warn!("mDNS discovery unavailable: {}", e);
+            Vec::new()
+        });
+    info!("Discovered {} backend(s) via mDNS", discovered.len());
+    Ok(discovered)
+}
+
+// Environments
+fn parse_environment_headers(headers: &HashMap<String, String>) -> Result<reqwest::header::HeaderMap, String> {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("Invalid header name: {:?}", name))?;
+        let header_value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| format!("Invalid value for header {}", name))?;
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+fn masked_headers(headers: &HashMap<String, String>) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let shown = if redact::is_sensitive_key(name) {
+                redact::REDACTED.to_string()
+            } else {
+                value.clone()
+            };
+            (name.clone(), shown)
+        })
+        .collect()
+}
+
+// The UI edits what list_environments returned, so a masked value coming
+// back means "unchanged" and is swapped for the stored one rather than saved
+fn unmask_headers(
+    headers: HashMap<String, String>,
+    stored: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            if value != redact::REDACTED {
+                return Ok((name, value));
+            }
+            stored
+                .iter()
+                .find(|(stored_name, _)| stored_name.eq_ignore_ascii_case(&name))
+                .map(|(_, stored_value)| (name.clone(), stored_value.clone()))
+                .ok_or_else(|| format!("Header {} has no saved value to keep; enter one", name))
+        })
+        .collect()
+}
+
+fn stored_headers(state: &AppState, environment: &str) -> HashMap<String, String> {
+    state
+        .config
+        .lock()
+        .unwrap()
+        .environments
+        .iter()
+        .find(|env| env.name == environment)
+        .map(|env| env.headers.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod unmask_headers_tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn masked_values_keep_the_stored_secret() {
+        let stored = headers(&[("Authorization", "Bearer real"), ("X-Team", "core")]);
+        let listed = masked_headers(&stored);
+        assert_eq!(listed["Authorization"], redact::REDACTED);
+        assert_eq!(unmask_headers(listed, &stored).unwrap(), stored);
+    }
+
+    #[test]
+    fn edited_values_replace_the_stored_ones() {
+        let stored = headers(&[("Authorization", "Bearer old")]);
+        let sent = headers(&[("authorization", redact::REDACTED), ("X-Api-Key", "new")]);
+        let merged = unmask_headers(sent, &stored).unwrap();
+        assert_eq!(merged, headers(&[("authorization", "Bearer old"), ("X-Api-Key", "new")]));
+    }
+
+    #[test]
+    fn masked_value_without_a_stored_one_is_refused() {
+        let sent = headers(&[("Authorization", redact::REDACTED)]);
+        assert!(unmask_headers(sent, &HashMap::new()).is_err());
+    }
+}
+
+// Headers for the active environment, applied only to requests under its base URL
+fn environment_headers(state: &AppState, url: &str) -> reqwest::header::HeaderMap {
+    let config = state.config.lock().unwrap();
+    config
+        .active_environment
+        .as_ref()
+        .and_then(|name| config.environments.iter().find(|env| &env.name == name))
+        .filter(|env| url.starts_with(env.base_url.trim_end_matches('/')))
+        .and_then(|env| parse_environment_headers(&env.headers).ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+async fn list_environments(state: State<'_, AppState>) -> Result<Vec<ApiEnvironment>, String> {
+    let _timing = time_command!(state);
+    let environments = state.config.lock().unwrap().environments.clone();
+    Ok(environments
+        .into_iter()
+        .map(|env| ApiEnvironment {
+            headers: masked_headers(&env.headers),
+            ..env
+        })
+        .collect())
+}
+
+// Where the frontend is loaded from: the main window's page, else the dev
+// server in debug builds and the bundled-asset origin in release builds
+fn frontend_url(app: &AppHandle) -> String {
+    if let Some(url) = app.get_webview_window("main").and_then(|window| window.url().ok()) {
+        return url.to_string();
+    }
+    if cfg!(debug_assertions) {
+        if let Some(dev_url) = &app.config().build.dev_url {
+            return dev_url.to_string();
+        }
+    }
+    if cfg!(target_os = "windows") { "http://tauri.localhost" } else { "tauri://localhost" }.to_string()
+}
+
+fn mixed_content_warning(app: &AppHandle, base_url: &str) -> Option<MixedContentCheck> {
+    let check = csp::check_mixed_content(&frontend_url(app), base_url).ok()?;
+    if check.mixed_content {
+        warn!("{} is plain http but the frontend is served from {}; requests will be blocked", base_url, check.frontend_origin);
+    }
+    check.mixed_content.then_some(check)
+}
+
+#[tauri::command]
+async fn check_mixed_content(app: AppHandle, base_url: String) -> Result<MixedContentCheck, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    csp::check_mixed_content(&frontend_url(&app), &base_url)
+}
+
+// Returns a mixed-content warning when the webview won't be able to use it
+#[tauri::command]
+async fn add_environment(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    environment: ApiEnvironment,
+) -> Result<Option<MixedContentCheck>, String> {
+    let _timing = time_command!(state);
+    let mut environment = environment;
+    // Re-adding a listed environment sends its masked headers back
+    environment.headers = unmask_headers(environment.headers, &stored_headers(&state, &environment.name))?;
+    if environment.name.trim().is_empty() {
+        return Err("Environment name must not be empty".to_string());
+    }
+    if !(environment.base_url.starts_with("http://") || environment.base_url.starts_with("https://")) {
+        return Err(format!("Environment base URL must be http(s): {}", environment.base_url));
+    }
+    parse_environment_headers(&environment.headers)?;
+
+    info!(
+        "Adding environment {} at {} with headers {:?}",
+        environment.name,
+        environment.base_url,
+        masked_headers(&environment.headers)
+    );
+    let origin = csp::origin(&environment.base_url);
+    let mixed_content = mixed_content_warning(&app, &environment.base_url);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.environments.retain(|env| env.name != environment.name);
+        config.environments.push(environment);
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+
+    // The webview can't reach the new origin until the CSP is rebuilt at launch
+    if let Some(origin) = origin.filter(|origin| !state.csp.connect_origins.contains(origin)) {
+        warn!("CSP does not yet allow {}; restart required", origin);
+        let _ = app.emit(
+            "restart-required",
+            serde_json::json!({ "reason": "csp", "origin": origin }),
+        );
+    }
+    Ok(mixed_content)
+}
+
+// Returns how many requests were aborted
+#[tauri::command]
+async fn cancel_all_requests(state: State<'_, AppState>) -> Result<u32, String> {
+    let _timing = time_command!(state);
+    let cancelled = state.active_requests.cancel_all();
+    info!("Cancelled {} in-flight requests", cancelled);
+    Ok(cancelled)
+}
+
+#[tauri::command]
+async fn exit_app(app: AppHandle) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    shutdown(&app);
+    Ok(())
+}
+
+// Abandons outstanding backend work before the process exits
+fn shutdown(app: &AppHandle) {
+    release_resources(app);
+    app.exit(0);
+}
+
+// Everything the process holds outside itself; safe to call more than once,
+// as `shutdown` leads to an exit request that calls it again
+fn release_resources(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let cancelled = state.active_requests.cancel_all();
+    if cancelled > 0 {
+        info!("Cancelled {} in-flight requests on exit", cancelled);
+    }
+    stop_health_ws(&state);
+    stop_health_monitor_task(&state);
+    stop_container_log_followers(&state);
+    stop_sse_streams(&state);
+    stop_control_socket(&state);
+    stop_services(&state);
+    if let Some(lock) = state.data_dir_lock.lock().unwrap().take() {
+        lock.release();
+    }
+}
+
+// Lock files
+// Taken at startup, before anything writes to the data dir. A lock whose
+// owner crashed is reclaimed; one held by a live instance (another user
+// session, or a shared data dir on a network drive) stops this launch, as
+// single-instance only hands over launches within one session.
+fn acquire_data_dir_lock(app: &AppHandle) -> Result<(), LockError> {
+    let state = app.state::<AppState>();
+    let dir = data_dir(app).map_err(LockError::Io)?;
+    let lock = take_lock_in(&state, &dir)?;
+    *state.data_dir_lock.lock().unwrap() = Some(lock);
+    Ok(())
+}
+
+fn take_lock_in(state: &AppState, dir: &Path) -> Result<DataDirLock, LockError> {
+    std::fs::create_dir_all(dir).map_err(|e| LockError::Io(format!("Failed to create {}: {}", dir.display(), e)))?;
+    let (lock, stale) = locks::acquire(&dir.join(locks::DATA_DIR_LOCK_FILE))?;
+    if let Some(stale) = stale {
+        warn!(
+            "Reclaimed stale lock {} left by pid {}",
+            stale.path,
+            stale.owner.as_ref().map(|owner| owner.pid.to_string()).unwrap_or_else(|| "unknown".to_string())
+        );
+        state.reclaimed_locks.lock().unwrap().push(stale);
+    }
+    Ok(lock)
+}
+
+// Locks reclaimed at startup, plus any lock that is stale now
+#[tauri::command]
+async fn check_stale_locks(app: AppHandle) -> Result<Vec<StaleLock>, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let state = app.state::<AppState>();
+    let mut found = state.reclaimed_locks.lock().unwrap().clone();
+    let path = data_dir(&app)?.join(locks::DATA_DIR_LOCK_FILE);
+    if let Some(stale) = tokio::task::spawn_blocking(move || locks::inspect(&path))
+        .await
+        .map_err(|e| format!("Lock check failed: {}", e))?
+    {
+        found.push(stale);
+    }
+    Ok(found)
+}
+
+// Control socket
+// Requests are served one line at a time per connection. A connection is
+// closed on the first request without the session's token, and kiosk mode
+// restrictions apply just as they do to the frontend.
+struct ControlSocket {
+    path: String,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+// Returns the socket path (a pipe name on Windows) for scripts to connect to.
+// Enabling again replaces the token.
+#[tauri::command]
+async fn enable_control_socket(app: AppHandle, token: String) -> Result<String, String> {
+    let state = app.state::<AppState>();
+    let _timing = time_command!(state);
+    control::validate_token(&token)?;
+    stop_control_socket(&state);
+    let path = control::socket_path(&data_dir(&app)?);
+    let task = listen_control_socket(app.clone(), &path, Arc::new(token))?;
+    info!("Control socket listening on {}", path);
+    *state.control_socket.lock().unwrap() = Some(ControlSocket { path: path.clone(), task });
+    Ok(path)
+}
+
+#[tauri::command]
+async fn disable_control_socket(state: State<'_, AppState>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    stop_control_socket(&state);
+    Ok(())
+}
+
+fn stop_control_socket(state: &AppState) {
+    let Some(socket) = state.control_socket.lock().unwrap().take() else {
+        return;
+    };
+    socket.task.abort();
+    #[cfg(unix)]
+    let _ = std::fs::remove_file(&socket.path);
+    info!("Closed control socket {}", socket.path);
+}
+
+#[cfg(unix)]
+fn listen_control_socket(app: AppHandle, path: &str, token: Arc<String>) -> Result<tauri::async_runtime::JoinHandle<()>, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A socket file left behind by a crash would make the bind fail
+    let _ = std::fs::remove_file(path);
+    let listener = tokio::net::UnixListener::bind(path).map_err(|e| format!("Failed to open control socket {}: {}", path, e))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict control socket {}: {}", path, e))?;
+    Ok(tauri::async_runtime::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve_control_connection(app.clone(), stream, token.clone()));
+                }
+                Err(e) => {
+                    warn!("Control socket accept failed: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(windows)]
+fn listen_control_socket(app: AppHandle, path: &str, token: Arc<String>) -> Result<tauri::async_runtime::JoinHandle<()>, String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let create = |first: bool| ServerOptions::new().first_pipe_instance(first).reject_remote_clients(true).create(path);
+    let mut server = create(true).map_err(|e| format!("Failed to open control pipe {}: {}", path, e))?;
+    let path = path.to_string();
+    Ok(tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                warn!("Control pipe connection failed: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+            // The next instance must exist before this one is handed off
+            let next = match ServerOptions::new().reject_remote_clients(true).create(&path) {
+                Ok(next) => next,
+                Err(e) => {
+                    error!("Failed to reopen control pipe {}: {}", path, e);
+                    return;
+                }
+            };
+            let connected = std::mem::replace(&mut server, next);
+            tauri::async_runtime::spawn(serve_control_connection(app.clone(), connected, token.clone()));
+        }
+    }))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn listen_control_socket(_app: AppHandle, _path: &str, _token: Arc<String>) -> Result<tauri::async_runtime::JoinHandle<()>, String> {
+    Err("The control socket is not supported on this platform".to_string())
+}
+
+async fn serve_control_connection<S>(app: AppHandle, stream: S, token: Arc<String>)
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = tokio::io::BufReader::new(reader);
+    loop {
+        let mut line = String::new();
+        match (&mut reader).take(control::MAX_REQUEST_BYTES as u64 + 1).read_line(&mut line).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        let (response, close) = if line.len() > control::MAX_REQUEST_BYTES {
+            (ControlResponse::failure(format!("Request exceeds {} bytes", control::MAX_REQUEST_BYTES)), true)
+        } else {
+            match serde_json::from_str::<ControlRequest>(line.trim()) {
+                Ok(request) if control::token_matches(&token, &request.token) => (run_control_command(&app, request.command).await, false),
+                Ok(_) => {
+                    warn!("Control socket rejected a request with an invalid token");
+                    (ControlResponse::failure("Invalid token"), true)
+                }
+                // Without a token there is nothing to authenticate the connection
+                Err(e) => (ControlResponse::failure(format!("Invalid request: {}", e)), true),
+            }
+        };
+        let mut payload = serde_json::to_vec(&response).unwrap_or_default();
+        payload.push(b'\n');
+        if writer.write_all(&payload).await.is_err() || close {
+            return;
+        }
+    }
+}
+
+fn control_result<T: Serialize>(result: Result<T, String>) -> ControlResponse {
+    match result.and_then(|value| serde_json::to_value(value).map_err(|e| e.to_string())) {
+        Ok(value) => ControlResponse::success(value),
+        Err(e) => ControlResponse::failure(e),
+    }
+}
+
+async fn run_control_command(app: &AppHandle, command: ControlCommand) -> ControlResponse {
+    let name = command.command_name();
+    if kiosk::is_restricted(name) && app.state::<AppState>().config.lock().unwrap().kiosk_mode {
+        warn!("Kiosk mode rejected control command {}", name);
+        return ControlResponse::failure(kiosk::KIOSK_ERROR);
+    }
+    info!("Control socket: {}", name);
+    match command {
+        ControlCommand::HealthCheck => control_result(get_local_server_status(app.clone(), app.state()).await),
+        ControlCommand::SwitchEnvironment { name } => control_result(switch_environment(app.clone(), name).await),
+        ControlCommand::StartServices => control_result(start_local_services(app.clone(), app.state()).await),
+        ControlCommand::StopServices => control_result(stop_local_services(app.state()).await),
+    }
+}
+
+#[tauri::command]
+async fn switch_environment(app: AppHandle, name: String) -> Result<Option<MixedContentCheck>, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    activate_environment(&app, &name)?;
+    let base_url = app.state::<AppState>().api_base_url.lock().unwrap().clone();
+    Ok(mixed_content_warning(&app, &base_url))
+}
+
+fn activate_environment(app: &AppHandle, name: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        let environment = config
+            .environments
+            .iter()
+            .find(|env| env.name == name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown environment: {}", name))?;
+        info!("Switching to environment {} at {}", environment.name, environment.base_url);
+        *state.api_base_url.lock().unwrap() = environment.base_url.clone();
+        config.api_base_url = environment.base_url;
+        config.active_environment = Some(environment.name);
+        config.active_backend = None;
+        config.clone()
+    };
+    *state.model_cache.lock().unwrap() = None;
+    state.response_cache.invalidate(None);
+    let cancelled = state.active_requests.cancel_all();
+    if cancelled > 0 {
+        info!("Cancelled {} in-flight requests to the previous environment", cancelled);
+    }
+    // Rebuild the client so the new environment's timeouts apply
+    flush_dns(&state, "environment changed");
+    // The monitoring loop reopens the health socket against the new base URL
+    stop_health_ws(&state);
+
+    persist_config(app, &config)?;
+    let _ = app.emit("backend-changed", name);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_environment_headers(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    headers: HashMap<String, String>,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let headers = unmask_headers(headers, &stored_headers(&state, &name))?;
+    // Reject names/values reqwest would refuse before they reach a request
+    parse_environment_headers(&headers)?;
+    info!("Setting headers for environment {}: {:?}", name, masked_headers(&headers));
+
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        let environment = config
+            .environments
+            .iter_mut()
+            .find(|env| env.name == name)
+            .ok_or_else(|| format!("Unknown environment: {}", name))?;
+        environment.headers = headers;
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+// `None` restores the default for the environment's locality
+#[tauri::command]
+async fn set_environment_timeouts(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    connect_timeout_ms: Option<u64>,
+    request_timeout_ms: Option<u64>,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if connect_timeout_ms == Some(0) || request_timeout_ms == Some(0) {
+        return Err("Timeouts must be greater than zero".to_string());
+    }
+
+    let (config, is_active) = {
+        let mut config = state.config.lock().unwrap();
+        let environment = config
+            .environments
+            .iter_mut()
+            .find(|env| env.name == name)
+            .ok_or_else(|| format!("Unknown environment: {}", name))?;
+        environment.connect_timeout_ms = connect_timeout_ms;
+        environment.request_timeout_ms = request_timeout_ms;
+        info!(
+            "Environment {} timeouts: connect {}ms, request {}ms",
+            name,
+            environment.connect_timeout_ms(),
+            environment.request_timeout_ms()
+        );
+        let is_active = config.active_environment.as_deref() == Some(name.as_str());
+        (config.clone(), is_active)
+    };
+    if is_active {
+        reset_http_client(&state);
+    }
+    persist_config(&app, &config)
+}
+
+// Feature flags
+fn effective_feature_flags(state: &AppState) -> HashMap<String, bool> {
+    let mut flags = state.remote_feature_flags.lock().unwrap().clone();
+    flags.extend(state.config.lock().unwrap().feature_flags.clone());
+    flags
+}
+
+#[tauri::command]
+async fn get_feature_flag(state: State<'_, AppState>, name: String) -> Result<bool, String> {
+    let _timing = time_command!(state);
+    Ok(effective_feature_flags(&state).get(&name).copied().unwrap_or(false))
+}
+
+#[tauri::command]
+async fn list_feature_flags(state: State<'_, AppState>) -> Result<HashMap<String, bool>, String> {
+    let _timing = time_command!(state);
+    Ok(effective_feature_flags(&state))
+}
+
+#[tauri::command]
+async fn set_feature_flag(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if name.trim().is_empty() {
+        return Err("Feature flag name must not be empty".to_string());
+    }
+    info!("Setting feature flag {} = {}", name, enabled);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.feature_flags.insert(name, enabled);
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    let _ = app.emit("feature-flags-changed", effective_feature_flags(&state));
+    Ok(())
+}
+
+// Pulls default flags from the backend's capability manifest (`{"feature_flags": {...}}`).
+#[tauri::command]
+async fn refresh_feature_flags(app: AppHandle, state: State<'_, AppState>) -> Result<HashMap<String, bool>, String> {
+    let _timing = time_command!(state);
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let url = format!("{}/capabilities", base_url.trim_end_matches('/'));
+    let client = http_client(&state)?;
+    let manifest: serde_json::Value = client
+        .get(&url)
+        .headers(environment_headers(&state, &url))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch capability manifest: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse capability manifest: {}", e))?;
+
+    let remote: HashMap<String, bool> = manifest
+        .get("feature_flags")
+        .and_then(|flags| flags.as_object())
+        .map(|flags| {
+            flags
+                .iter()
+                .filter_map(|(name, value)| value.as_bool().map(|enabled| (name.clone(), enabled)))
+                .collect()
+        })
+        .unwrap_or_default();
+    info!("Backend manifest provided {} feature flags", remote.len());
+    *state.remote_feature_flags.lock().unwrap() = remote;
+
+    let flags = effective_feature_flags(&state);
+    let _ = app.emit("feature-flags-changed", &flags);
+    Ok(flags)
+}
+
+// Backend OpenAPI schema
+const OPENAPI_CACHE_TTL: Duration = Duration::from_secs(600);
+const HTTP_METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EndpointSummary {
+    method: String,
+    path: String,
+    summary: Option<String>,
+    operation_id: Option<String>,
+}
+
+// Cached per base URL, so switching backends never serves a stale schema
+#[tauri::command]
+async fn get_backend_openapi(app: AppHandle, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let _timing = time_command!(state);
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    if let Some((fetched_at, url, schema)) = state.openapi_cache.lock().unwrap().as_ref() {
+        if *url == base_url && fetched_at.elapsed() < OPENAPI_CACHE_TTL {
+            return Ok(schema.clone());
+        }
+    }
+
+    let path = state.config.lock().unwrap().openapi_path.clone();
+    let not_available = || format!("The backend at {} does not publish an OpenAPI document at {}", base_url, path);
+    let response = send_proxy_request(&app, "GET", &path, None).await?;
+    if !(200..300).contains(&response.status) {
+        warn!("OpenAPI request returned status {}", response.status);
+        return Err(not_available());
+    }
+    // Anything without a version marker is likely an HTML fallback page
+    let schema = response.body;
+    if schema.get("openapi").is_none() && schema.get("swagger").is_none() {
+        return Err(not_available());
+    }
+
+    info!("Fetched OpenAPI schema from {}{}", base_url, path);
+    *state.openapi_cache.lock().unwrap() = Some((Instant::now(), base_url, schema.clone()));
+    Ok(schema)
+}
+
+#[tauri::command]
+async fn list_backend_endpoints(app: AppHandle, state: State<'_, AppState>) -> Result<Vec<EndpointSummary>, String> {
+    let _timing = time_command!(state);
+    let schema = get_backend_openapi(app, state).await?;
+    let Some(paths) = schema.get("paths").and_then(|paths| paths.as_object()) else {
+        return Ok(Vec::new());
+    };
+    let text = |operation: &serde_json::Value, key: &str| operation.get(key).and_then(|value| value.as_str()).map(str::to_string);
+    let endpoints = paths
+        .iter()
+        .filter_map(|(path, item)| item.as_object().map(|operations| (path, operations)))
+        .flat_map(|(path, operations)| {
+            operations
+                .iter()
+                .filter(|(method, _)| HTTP_METHODS.contains(&method.as_str()))
+                .map(move |(method, operation)| EndpointSummary {
+                    method: method.to_uppercase(),
+                    path: path.clone(),
+                    summary: text(operation, "summary"),
+                    operation_id: text(operation, "operationId"),
+                })
+        })
+        .collect();
+    Ok(endpoints)
+}
+
+// Response headers
+// For debugging proxies and caches in front of the backend. The status is
+// returned under the `:status` pseudo-header, repeated headers are joined
+// with ", ", and cookies and auth headers are masked.
+#[tauri::command]
+async fn fetch_headers(state: State<'_, AppState>, path: String) -> Result<HashMap<String, String>, String> {
+    let _timing = time_command!(state);
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'));
+    let client = http_client(&state)?;
+    let headers = environment_headers(&state, &url);
+    let _permit = state.request_limiter.acquire().await?;
+
+    let send = |method: reqwest::Method| client.request(method, &url).headers(headers.clone()).send();
+    let mut response = send(reqwest::Method::HEAD)
+        .await
+        .map_err(|e| format!("HEAD {} failed: {}", url, e))?;
+    // Some servers and frameworks don't route HEAD; the body of the GET is never read
+    if matches!(response.status().as_u16(), 404 | 405 | 501) {
+        debug!("HEAD {} returned {}, retrying with GET", url, response.status());
+        response = send(reqwest::Method::GET)
+            .await
+            .map_err(|e| format!("GET {} failed: {}", url, e))?;
+    }
+
+    let mut result = HashMap::new();
+    result.insert(":status".to_string(), response.status().as_u16().to_string());
+    for name in response.headers().keys() {
+        let value = if redact::is_sensitive_key(name.as_str()) {
+            redact::REDACTED.to_string()
+        } else {
+            response
+                .headers()
+                .get_all(name)
+                .iter()
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        result.insert(name.as_str().to_string(), value);
+    }
+    info!("Fetched {} header(s) from {} ({})", result.len() - 1, url, response.status());
+    Ok(result)
+}
+
+// Models
+#[tauri::command]
+async fn estimate_model_fit(app: AppHandle, state: State<'_, AppState>, size_bytes: u64) -> Result<ModelFit, String> {
+    let _timing = time_command!(state);
+    let system_info = get_system_info(app.clone(), state.clone()).await?;
+    // Free rather than total VRAM: the desktop and other apps hold some of it
+    let free_vram_mb = match get_vram_headroom(state).await {
+        Ok(headroom) => headroom.map(|headroom| headroom.free_mb),
+        Err(e) => {
+            warn!("VRAM headroom unavailable, assuming no GPU: {}", e);
+            None
+        }
+    };
+    Ok(models::estimate_model_fit(size_bytes, system_info.memory_total, free_vram_mb))
+}
+
+// Requests to a backend on this machine are free; anything else is priced
+// from the user-editable table in the config.
+#[tauri::command]
+async fn estimate_request_cost(
+    state: State<'_, AppState>,
+    tokens_in: u32,
+    tokens_out: u32,
+    model: String,
+) -> Result<CostEstimate, String> {
+    let _timing = time_command!(state);
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    if is_local_url(&base_url) {
+        return Ok(CostEstimate::free());
+    }
+    let pricing = state.config.lock().unwrap().pricing.clone();
+    models::estimate_cost(&pricing, &model, tokens_in, tokens_out)
+}
+
+#[tauri::command]
+async fn get_pricing_table(state: State<'_, AppState>) -> Result<HashMap<String, ModelPricing>, String> {
+    let _timing = time_command!(state);
+    Ok(state.config.lock().unwrap().pricing.clone())
+}
+
+// `None` removes the model's entry; use "*" as the model to set the default price
+#[tauri::command]
+async fn set_model_pricing(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    model: String,
+    pricing: Option<ModelPricing>,
+) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if let Some(pricing) = &pricing {
+        let valid = |price: f64| price.is_finite() && price >= 0.0;
+        if !valid(pricing.input_per_million) || !valid(pricing.output_per_million) {
+            return Err("Prices must be non-negative numbers".to_string());
+        }
+    }
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        match pricing {
+            Some(pricing) => {
+                info!("Pricing for {}: {:?}", model, pricing);
+                config.pricing.insert(model, pricing);
+            }
+            None => {
+                info!("Removing pricing for {}", model);
+                config.pricing.remove(&model);
+            }
+        }
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn list_models(app: AppHandle, state: State<'_, AppState>, force_refresh: Option<bool>) -> Result<Vec<ModelInfo>, String> {
+    let _timing = time_command!(state);
+    if !force_refresh.unwrap_or(false) {
+        if let Some((fetched_at, cached)) = state.model_cache.lock().unwrap().as_ref() {
+            if fetched_at.elapsed() < MODEL_CACHE_TTL {
+                return Ok(cached.clone());
+            }
+        }
+    }
+
+    let base_url = state.api_base_url.lock().unwrap().clone();
+    let models_url = format!("{}/models", base_url.trim_end_matches('/'));
+    info!("Fetching model list from: {}", models_url);
+
+    let policy = state.retry_policy.lock().unwrap().clone();
+    let client = http_client(&state)?;
+    let headers = environment_headers(&state, &models_url);
+    let body: serde_json::Value = state
+        .active_requests
+        .run(async {
+            let response = send_with_retry(&app, &policy, &models_url, || client.get(&models_url).headers(headers.clone()).send())
+                .await
+                .map_err(|e| format!("Failed to fetch models: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("Model list request failed with status: {}", response.status()));
+            }
+            response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse model list: {}", e))
+        })
+        .await?;
+
+    let system_info = get_system_info(app.clone(), state.clone()).await?;
+    let mut models = models::parse_model_list(&body);
+    for model in &mut models {
+        model.fit = model
+            .size_bytes
+            .map(|size| models::estimate_model_fit(size, system_info.memory_total, None));
+    }
+    info!("Backend reported {} models", models.len());
+
+    *state.model_cache.lock().unwrap() = Some((Instant::now(), models.clone()));
+    Ok(models)
+}
+
+// Data directory relocation
+const RELOCATION_SPACE_MARGIN: u64 = 100 * 1024 * 1024;
+const RELOCATION_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize)]
+struct RelocationProgress {
+    copied_bytes: u64,
+    total_bytes: u64,
+}
+
+// Copies the data dir to `new_path` and switches the pointer file. Until the
+// pointer is switched the old location stays authoritative, so an interrupted
+// move loses nothing and can simply be retried. The lock moves with the
+// pointer; the old copy still holds the control socket and other open files,
+// so it is removed on the next launch, and a restart is requested.
+#[tauri::command]
+async fn relocate_data_dir(app: AppHandle, new_path: String) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let current = data_dir(&app)?;
+    let destination = PathBuf::from(new_path.trim());
+    if !destination.is_absolute() {
+        return Err(format!("Destination must be an absolute path: {}", destination.display()));
+    }
+    if destination.starts_with(&current) {
+        return Err("Destination must be outside the current data directory".to_string());
+    }
+    info!("Relocating data directory from {} to {}", current.display(), destination.display());
+
+    let handle = app.clone();
+    let moved_to = destination.clone();
+    tokio::task::spawn_blocking(move || relocate_data_dir_blocking(&handle, &current, &destination))
+        .await
+        .map_err(|e| format!("Data directory relocation failed: {}", e))?
+        .map_err(|e| {
+            error!("{}", e);
+            e
+        })?;
+
+    if let Err(e) = start_config_watcher(&app) {
+        warn!("{}", e);
+    }
+    let _ = app.emit("data-dir-relocated", moved_to.to_string_lossy().to_string());
+    let _ = app.emit("restart-required", serde_json::json!({ "reason": "data_dir" }));
+    Ok(())
+}
+
+fn relocate_data_dir_blocking(app: &AppHandle, current: &Path, destination: &Path) -> Result<(), String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+    let default_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    // On macOS and Windows the config dir is the default data dir, so the
+    // pointer file may sit inside the directory being moved
+    let pointer_file = config_dir.join(datadir::POINTER_FILE_NAME);
+    let marker = destination.join(datadir::RELOCATION_MARKER);
+
+    if destination.exists() {
+        if !destination.is_dir() {
+            return Err(format!("{} is not a directory", destination.display()));
+        }
+        let is_empty = std::fs::read_dir(destination)
+            .map(|entries| entries.flatten().all(|entry| entry.path() == pointer_file))
+            .unwrap_or(false);
+        if !is_empty && !marker.exists() {
+            return Err(format!("{} is not empty", destination.display()));
+        }
+    }
+    std::fs::create_dir_all(destination)
+        .and_then(|_| std::fs::write(&marker, current.to_string_lossy().as_bytes()))
+        .map_err(|e| format!("{} is not writable: {}", destination.display(), e))?;
+
+    let total_bytes = system::directory_size(current);
+    let available = system::available_space(destination)?;
+    if available < total_bytes + RELOCATION_SPACE_MARGIN {
+        return Err(format!(
+            "Not enough space at {}: need {} MB, {} MB available",
+            destination.display(),
+            (total_bytes + RELOCATION_SPACE_MARGIN) / (1024 * 1024),
+            available / (1024 * 1024)
+        ));
+    }
+
+    let mut last_emit = Instant::now();
+    let mut progress = |copied_bytes: u64| {
+        if last_emit.elapsed() >= RELOCATION_PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            let _ = app.emit("data-dir-relocation-progress", RelocationProgress { copied_bytes, total_bytes });
+        }
+    };
+    // Per-process state stays behind; the next launch creates its own
+    let skip = [pointer_file.clone(), current.join(locks::DATA_DIR_LOCK_FILE)];
+    let copied_bytes = datadir::copy_tree_resumable(current, destination, &skip, &mut progress)
+        .map_err(|e| format!("Copying data to {} failed: {}", destination.display(), e))?;
+    let _ = app.emit("data-dir-relocation-progress", RelocationProgress { copied_bytes, total_bytes });
+
+    let _ = std::fs::remove_file(&marker);
+    // Locked before the pointer moves, so no other instance can start on the
+    // new location in between
+    let state = app.state::<AppState>();
+    let moved_lock = take_lock_in(&state, destination).map_err(|e| format!("Failed to lock {}: {}", destination.display(), e))?;
+    let switched = if destination == default_dir {
+        datadir::remove_pointer(&config_dir)
+    } else {
+        datadir::write_pointer(&config_dir, destination)
+    };
+    if let Err(e) = switched {
+        moved_lock.release();
+        return Err(format!("Failed to record new data directory: {}", e));
+    }
+    if let Some(old_lock) = state.data_dir_lock.lock().unwrap().replace(moved_lock) {
+        old_lock.release();
+    }
+    info!("Data directory now at {} ({} bytes copied)", destination.display(), copied_bytes);
+
+    if let Err(e) = datadir::schedule_removal(&config_dir, current) {
+        warn!("Old data directory {} will not be removed: {}", current.display(), e);
+    }
+    Ok(())
+}
+
+// Removes the directory a relocation left behind, now that no open file of
+// this process points into it
+fn remove_previous_data_dir(app: &AppHandle) {
+    let (Ok(config_dir), Ok(current)) = (app.path().app_config_dir(), data_dir(app)) else {
+        return;
+    };
+    let Some(previous) = datadir::take_scheduled_removal(&config_dir) else {
+        return;
+    };
+    // Moved back, or nested either way: removing would take the live data too
+    if current.starts_with(&previous) || previous.starts_with(&current) {
+        return;
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let keep = [config_dir.join(datadir::POINTER_FILE_NAME)];
+        match datadir::remove_tree_except(&previous, &keep) {
+            0 => info!("Removed old data directory {}", previous.display()),
+            left_behind => warn!(
+                "{} entries could not be removed from old data directory {}",
+                left_behind,
+                previous.display()
+            ),
+        }
+    });
+}
+
+// Diagnostics
+#[tauri::command]
+async fn get_data_dir_filesystem(app: AppHandle) -> Result<FilesystemInfo, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let data_dir = data_dir(&app)?;
+    let info = tokio::task::spawn_blocking(move || system::filesystem_info(&data_dir))
+        .await
+        .map_err(|e| format!("Filesystem detection failed: {}", e))??;
+    info!("Data directory filesystem: {:?}", info);
+    Ok(info)
+}
+
+fn filesystem_check(info: &Result<FilesystemInfo, String>) -> DiagnosticCheck {
+    const NAME: &str = "data_dir_filesystem";
+    match info {
+        Ok(info) if info.is_network => DiagnosticCheck::new(
+            NAME,
+            DiagnosticStatus::Warning,
+            format!(
+                "The data directory is on a network filesystem ({}); model caching and loading will be slow",
+                info.fs_type
+            ),
+        ),
+        Ok(info) if info.is_removable => DiagnosticCheck::new(
+            NAME,
+            DiagnosticStatus::Warning,
+            "The data directory is on removable media; the app will break if it is disconnected",
+        ),
+        Ok(info) => DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, format!("Local {} filesystem", info.fs_type)),
+        Err(e) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, format!("Could not detect filesystem: {}", e)),
+    }
+}
+
+const ELEVATED_WARNING: &str =
+    "StratMaster is running with administrator/root privileges. Relaunch it as a regular user to avoid permission problems in the data directory.";
+
+#[tauri::command]
+async fn is_elevated(state: State<'_, AppState>) -> Result<bool, String> {
+    let _timing = time_command!(state);
+    system::is_elevated()
+}
+
+// Hashed hardware identifiers for licensing/telemetry; see `system::machine_fingerprint`
+#[tauri::command]
+async fn get_machine_fingerprint(state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    tauri::async_runtime::spawn_blocking(system::machine_fingerprint)
+        .await
+        .map_err(|e| format!("Fingerprint task failed: {}", e))?
+}
+
+fn elevation_check() -> DiagnosticCheck {
+    const NAME: &str = "elevated_privileges";
+    match system::is_elevated() {
+        Ok(true) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, ELEVATED_WARNING),
+        Ok(false) => DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, "Running without elevated privileges"),
+        Err(e) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, format!("Could not determine privileges: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn check_port_firewall(state: State<'_, AppState>, port: u16) -> Result<FirewallStatus, String> {
+    let _timing = time_command!(state);
+    let status = tokio::task::spawn_blocking(move || system::firewall_status(port))
+        .await
+        .map_err(|e| format!("Firewall check failed: {}", e))?;
+    info!("Firewall status for port {}: {:?}", port, status);
+    Ok(status)
+}
+
+async fn firewall_check(app: &AppHandle) -> DiagnosticCheck {
+    const NAME: &str = "backend_port_firewall";
+    let base_url = app.state::<AppState>().api_base_url.lock().unwrap().clone();
+    let Some(port) = url::Url::parse(&base_url).ok().and_then(|url| url.port_or_known_default()) else {
+        return DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, format!("Could not determine the backend port from {}", base_url));
+    };
+    match check_port_firewall(app.state(), port).await {
+        Ok(FirewallStatus { blocked: Some(true), detail }) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, detail),
+        Ok(FirewallStatus { blocked: Some(false), detail }) => DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, detail),
+        Ok(FirewallStatus { blocked: None, detail }) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, detail),
+        Err(e) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, e),
+    }
+}
+
+#[tauri::command]
+async fn get_gpu_driver_info(state: State<'_, AppState>) -> Result<Option<GpuDriverInfo>, String> {
+    let _timing = time_command!(state);
+    tokio::task::spawn_blocking(system::gpu_driver_info)
+        .await
+        .map_err(|e| format!("GPU driver query failed: {}", e))?
+}
+
+#[tauri::command]
+async fn get_vram_headroom(state: State<'_, AppState>) -> Result<Option<VramHeadroom>, String> {
+    let _timing = time_command!(state);
+    tokio::task::spawn_blocking(system::vram_headroom)
+        .await
+        .map_err(|e| format!("VRAM query failed: {}", e))?
+}
+
+async fn gpu_driver_check(app: &AppHandle) -> DiagnosticCheck {
+    const NAME: &str = "gpu_driver";
+    match get_gpu_driver_info(app.state()).await {
+        Ok(Some(info)) => match info.below_minimum() {
+            Some(problem) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, problem),
+            None => DiagnosticCheck::new(
+                NAME,
+                DiagnosticStatus::Ok,
+                format!("{} driver {}", info.vendor, info.driver_version),
+            ),
+        },
+        Ok(None) => DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, "No GPU detected; inference runs on the CPU"),
+        Err(e) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, e),
+    }
+}
+
+// Must pass before an update is offered for install; an invalid or missing
+// signature is an error, never `Ok(false)`
+#[tauri::command]
+async fn verify_update_signature(state: State<'_, AppState>, file_path: String, signature: String) -> Result<bool, String> {
+    let _timing = time_command!(state);
+    let path = PathBuf::from(&file_path);
+    tokio::task::spawn_blocking(move || updates::verify_file(&path, &signature))
+        .await
+        .map_err(|e| format!("Update verification failed: {}", e))?
+        .inspect_err(|e| error!("Refusing update {}: {}", file_path, e))?;
+    Ok(true)
+}
+
+// The only way an update is launched. The artifact is copied into the cache
+// first and the copy is verified and opened, so the file can't be swapped
+// between the check and the launch.
+#[tauri::command]
+async fn install_update(app: AppHandle, file_path: String, signature: String) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let source = PathBuf::from(&file_path);
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| format!("Not an update file: {}", file_path))?
+        .to_os_string();
+    let staging = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to get cache directory: {}", e))?
+        .join("updates");
+    let staged = tokio::task::spawn_blocking(move || {
+        std::fs::create_dir_all(&staging).map_err(|e| format!("Failed to create {}: {}", staging.display(), e))?;
+        let staged = staging.join(file_name);
+        std::fs::copy(&source, &staged).map_err(|e| format!("Failed to stage {}: {}", source.display(), e))?;
+        updates::verify_file(&staged, &signature).inspect_err(|_| {
+            let _ = std::fs::remove_file(&staged);
+        })?;
+        Ok::<_, String>(staged)
+    })
+    .await
+    .map_err(|e| format!("Update verification failed: {}", e))?
+    .inspect_err(|e| error!("Refusing update {}: {}", file_path, e))?;
+
+    info!("Update {} verified; launching installer", staged.display());
+    #[cfg(target_os = "windows")]
+    let launched = std::process::Command::new("cmd").arg("/C").arg("start").arg("").arg(&staged).spawn();
+    #[cfg(target_os = "macos")]
+    let launched = std::process::Command::new("open").arg(&staged).spawn();
+    #[cfg(target_os = "linux")]
+    let launched = std::process::Command::new("xdg-open").arg(&staged).spawn();
+    launched.map_err(|e| format!("Failed to launch installer {}: {}", staged.display(), e))?;
+    Ok(())
+}
+
+// softwareupdate and Windows Update both call home and can hang for minutes
+const OS_UPDATE_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[tauri::command]
+async fn get_pending_os_updates(state: State<'_, AppState>) -> Result<Vec<OsUpdate>, String> {
+    let _timing = time_command!(state);
+    // A timed-out query keeps running on its blocking thread; its result is dropped
+    let updates = tokio::time::timeout(OS_UPDATE_QUERY_TIMEOUT, tokio::task::spawn_blocking(system::pending_os_updates))
+        .await
+        .map_err(|_| format!("OS update query timed out after {}s", OS_UPDATE_QUERY_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("OS update query failed: {}", e))??;
+    info!("{} pending OS update(s)", updates.len());
+    Ok(updates)
+}
+
+async fn os_updates_check(app: &AppHandle) -> DiagnosticCheck {
+    const NAME: &str = "os_updates";
+    match get_pending_os_updates(app.state()).await {
+        Ok(updates) => {
+            let critical: Vec<&str> = updates
+                .iter()
+                .filter(|update| update.critical)
+                .map(|update| update.name.as_str())
+                .collect();
+            if critical.is_empty() {
+                DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, format!("{} pending OS update(s), none critical", updates.len()))
+            } else {
+                DiagnosticCheck::new(
+                    NAME,
+                    DiagnosticStatus::Warning,
+                    format!("Critical OS updates pending: {}. Install them if windows render incorrectly", critical.join(", ")),
+                )
+            }
+        }
+        Err(e) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, e),
+    }
+}
+
+// The saved override wins; detection failures fall back to en-US. The
+// detected locale is kept for building HTTP clients without a subprocess.
+async fn effective_locale(state: &AppState) -> LocaleInfo {
+    let locale = state.config.lock().unwrap().locale.clone();
+    if let Some(info) = locale.as_deref().and_then(LocaleInfo::from_tag) {
+        return info;
+    }
+    let detected = tokio::task::spawn_blocking(system::system_locale)
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result);
+    let info = detected.unwrap_or_else(|e| {
+        warn!("Locale detection failed, using {}: {}", system::DEFAULT_LOCALE, e);
+        LocaleInfo::default()
+    });
+    *state.system_locale.lock().unwrap() = Some(info.clone());
+    info
+}
+
+// Until detection has run at startup, the default locale is assumed
+fn accept_language(state: &AppState) -> String {
+    let (fallbacks, locale) = {
+        let config = state.config.lock().unwrap();
+        (config.accept_language.clone(), config.locale.clone())
+    };
+    locale
+        .as_deref()
+        .and_then(LocaleInfo::from_tag)
+        .or_else(|| state.system_locale.lock().unwrap().clone())
+        .unwrap_or_default()
+        .accept_language_with(fallbacks.as_deref().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod accept_language_tests {
+    use super::{accept_language, AppState};
+
+    #[test]
+    fn header_reflects_the_configured_locale_and_fallbacks() {
+        let state = AppState::default();
+        state.config.lock().unwrap().locale = Some("de-AT".to_string());
+        assert_eq!(accept_language(&state), "de-AT,de;q=0.9");
+        state.config.lock().unwrap().accept_language = Some("en".to_string());
+        assert_eq!(accept_language(&state), "de-AT,de;q=0.9,en;q=0.8");
+    }
+}
+
+#[tauri::command]
+async fn get_accept_language(state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    Ok(accept_language(&state))
+}
+
+// `value` lists fallback languages to send after the locale's, e.g. `fr,es`;
+// `None` sends the locale's alone
+#[tauri::command]
+async fn set_accept_language(app: AppHandle, state: State<'_, AppState>, value: Option<String>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    let value = value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+    if let Some(value) = &value {
+        system::validate_accept_language(value)?;
+    }
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.accept_language = value;
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    reset_http_client(&state);
+    let header = accept_language(&state);
+    info!("Accept-Language set to {}", header);
+    Ok(header)
+}
+
+#[tauri::command]
+async fn get_system_locale(state: State<'_, AppState>) -> Result<LocaleInfo, String> {
+    let _timing = time_command!(state);
+    Ok(effective_locale(&state).await)
+}
+
+// `None` clears the override and goes back to the OS locale
+#[tauri::command]
+async fn set_locale(app: AppHandle, state: State<'_, AppState>, locale: Option<String>) -> Result<LocaleInfo, String> {
+    let _timing = time_command!(state);
+    let locale = match locale.as_deref().map(str::trim).filter(|tag| !tag.is_empty()) {
+        Some(tag) => Some(
+            LocaleInfo::from_tag(tag)
+                .ok_or_else(|| format!("Invalid locale {}; expected a tag such as en-US", tag))?
+                .tag(),
+        ),
+        None => None,
+    };
+    info!("Locale override set to {}", locale.as_deref().unwrap_or("system default"));
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.locale = locale;
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+
+    let info = effective_locale(&state).await;
+    // The shared client carries Accept-Language as a default header
+    reset_http_client(&state);
+    let _ = app.emit("locale-changed", &info);
+    Ok(info)
+}
+
+#[tauri::command]
+async fn benchmark_disk_io(app: AppHandle) -> Result<DiskBenchmark, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let dir = data_dir(&app)?;
+    info!("Benchmarking disk IO in {}", dir.display());
+    let result = tokio::task::spawn_blocking(move || system::benchmark_disk_io(&dir))
+        .await
+        .map_err(|e| format!("Disk benchmark failed: {}", e))??;
+    info!(
+        "Disk benchmark: {:.0} MB/s read, {:.0} MB/s write, {:.0} random read IOPS",
+        result.seq_read_mbps, result.seq_write_mbps, result.random_read_iops
+    );
+    Ok(result)
+}
+
+// Data, config and log directories, in that order
+#[tauri::command]
+async fn check_data_dir_permissions(app: AppHandle) -> Result<Vec<PermissionReport>, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let dirs = vec![
+        data_dir(&app)?,
+        app.path()
+            .app_config_dir()
+            .map_err(|e| format!("Failed to get app config directory: {}", e))?,
+        app.path()
+            .app_log_dir()
+            .map_err(|e| format!("Failed to get app log directory: {}", e))?,
+    ];
+    let reports = tokio::task::spawn_blocking(move || {
+        let mut reports: Vec<PermissionReport> = Vec::new();
+        for dir in dirs {
+            // The config dir is the data dir on macOS and Windows
+            if !reports.iter().any(|report| Path::new(&report.dir) == dir) {
+                reports.push(system::check_dir_permissions(&dir));
+            }
+        }
+        reports
+    })
+    .await
+    .map_err(|e| format!("Permission check failed: {}", e))?;
+    Ok(reports)
+}
+
+async fn permissions_check(app: &AppHandle) -> DiagnosticCheck {
+    const NAME: &str = "directory_permissions";
+    match check_data_dir_permissions(app.clone()).await {
+        Ok(reports) => {
+            let issues: Vec<String> = reports
+                .iter()
+                .filter_map(|report| report.issue.as_ref().map(|issue| format!("{} {}", report.dir, issue)))
+                .collect();
+            if issues.is_empty() {
+                DiagnosticCheck::new(NAME, DiagnosticStatus::Ok, "Data, config and log directories are readable and writable")
+            } else {
+                DiagnosticCheck::new(
+                    NAME,
+                    DiagnosticStatus::Error,
+                    format!("{}; settings and logs may fail to save. Fix the ownership or permissions of these directories", issues.join("; ")),
+                )
+            }
+        }
+        Err(e) => DiagnosticCheck::new(NAME, DiagnosticStatus::Warning, e),
+    }
+}
+
+#[tauri::command]
+async fn get_diagnostics(app: AppHandle) -> Result<Vec<DiagnosticCheck>, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let checks = vec![
+        filesystem_check(&get_data_dir_filesystem(app.clone()).await),
+        elevation_check(),
+        firewall_check(&app).await,
+        permissions_check(&app).await,
+        gpu_driver_check(&app).await,
+        os_updates_check(&app).await,
+        render_check(&app.state::<AppState>()),
+    ];
+
+    for check in &checks {
+        if check.status != DiagnosticStatus::Ok {
+            warn!("Diagnostic {}: {}", check.name, check.message);
+        }
+    }
+    Ok(checks)
+}
+
+// Clipboard
+// Cuts `text` to at most `max_bytes` including the marker, on a char boundary
+fn truncate_for_clipboard(text: &str, max_bytes: usize) -> std::borrow::Cow<'_, str> {
+    if text.len() <= max_bytes {
+        return std::borrow::Cow::Borrowed(text);
+    }
+    let mut end = max_bytes.saturating_sub(CLIPBOARD_TRUNCATION_MARKER.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    std::borrow::Cow::Owned(format!("{}{}", &text[..end], CLIPBOARD_TRUNCATION_MARKER))
+}
+
+#[cfg(test)]
+mod clipboard_tests {
+    use super::{truncate_for_clipboard, CLIPBOARD_TRUNCATION_MARKER};
+
+    #[test]
+    fn short_text_is_copied_as_is() {
+        assert_eq!(truncate_for_clipboard("hello", 100), "hello");
+    }
+
+    #[test]
+    fn long_text_is_cut_to_the_limit_with_a_marker() {
+        let text = "a".repeat(1000);
+        let truncated = truncate_for_clipboard(&text, 100);
+        assert_eq!(truncated.len(), 100);
+        assert!(truncated.ends_with(CLIPBOARD_TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn cuts_on_a_char_boundary() {
+        let text = "é".repeat(100);
+        let limit = CLIPBOARD_TRUNCATION_MARKER.len() + 5;
+        let truncated = truncate_for_clipboard(&text, limit);
+        assert!(truncated.len() <= limit);
+        assert_eq!(truncated.trim_end_matches(CLIPBOARD_TRUNCATION_MARKER), "éé");
+    }
+}
+
+// Every clipboard write goes through here so the size cap always applies.
+// Returns the number of bytes actually copied.
+fn copy_to_clipboard(app: &AppHandle, text: &str) -> Result<usize, String> {
+    let max_bytes = app.state::<AppState>().config.lock().unwrap().max_clipboard_bytes;
+    if text.len() > max_bytes {
+        warn!("Clipboard payload of {} bytes truncated to {} bytes", text.len(), max_bytes);
+    }
+    let text = truncate_for_clipboard(text, max_bytes);
+    app.clipboard()
+        .write_text(text.as_ref())
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
+    Ok(text.len())
+}
+
+// Diagnostics checks plus recent logs as plain text, with secrets redacted
+#[tauri::command]
+async fn copy_diagnostics(app: AppHandle) -> Result<usize, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let mut report = String::from("StratMaster diagnostics\n\n");
+    for check in get_diagnostics(app.clone()).await? {
+        report.push_str(&format!("[{:?}] {}: {}\n", check.status, check.name, check.message));
+    }
+    report.push_str("\nRecent logs\n");
+    for entry in logging::recent_entries(logging::current_level(), 500) {
+        report.push_str(&format!("{} {} {}: {}\n", entry.timestamp_ms, entry.level, entry.target, entry.message));
+    }
+    copy_to_clipboard(&app, &redact::redact_text(&report))
+}
+
+#[tauri::command]
+async fn set_max_clipboard_bytes(app: AppHandle, state: State<'_, AppState>, max_bytes: usize) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if max_bytes <= CLIPBOARD_TRUNCATION_MARKER.len() {
+        return Err(format!("Clipboard limit must be larger than {} bytes", CLIPBOARD_TRUNCATION_MARKER.len()));
+    }
+    info!("Clipboard limit set to {} bytes", max_bytes);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.max_clipboard_bytes = max_bytes;
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+// Webview cache
+// Only the HTTP cache is ours to clear: WebView2 keeps it inside the user
+// data folder Tauri places in the local app data dir, WKWebView and WebKitGTK
+// under the platform cache dir. Site storage (LocalStorage, IndexedDB) lives
+// alongside it and is left alone. Entries are directories except Cache.db on
+// macOS, which is a SQLite file with its journal siblings.
+fn webview_cache_dirs(app: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let profile = app
+            .path()
+            .app_local_data_dir()
+            .map_err(|e| format!("Failed to get local data directory: {}", e))?
+            .join("EBWebView")
+            .join("Default");
+        Ok(vec![profile.join("Cache"), profile.join("Code Cache"), profile.join("GPUCache")])
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let cache_dir = app
+            .path()
+            .app_cache_dir()
+            .map_err(|e| format!("Failed to get cache directory: {}", e))?;
+        #[cfg(target_os = "macos")]
+        let dirs = vec![
+            cache_dir.join("WebKit").join("NetworkCache"),
+            cache_dir.join("WebKit").join("CacheStorage"),
+            cache_dir.join("Cache.db"),
+            cache_dir.join("Cache.db-wal"),
+            cache_dir.join("Cache.db-shm"),
+        ];
+        #[cfg(not(target_os = "macos"))]
+        let dirs = vec![cache_dir.join("WebKitCache"), cache_dir.join("CacheStorage")];
+        Ok(dirs)
+    }
+}
+
+fn cache_entry_size(path: &Path) -> u64 {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        Ok(_) => system::directory_size(path),
+        Err(_) => 0,
+    }
+}
+
+// Returns how many entries could not be removed
+fn clear_cache_entry(path: &Path) -> usize {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => usize::from(std::fs::remove_file(path).is_err()),
+        Ok(_) => system::clear_directory(path),
+        Err(_) => 0,
+    }
+}
+
+#[tauri::command]
+async fn get_webview_cache_size(app: AppHandle) -> Result<u64, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let dirs = webview_cache_dirs(&app)?;
+    tokio::task::spawn_blocking(move || dirs.iter().map(|dir| cache_entry_size(dir)).sum())
+        .await
+        .map_err(|e| format!("Failed to measure webview cache: {}", e))
+}
+
+#[tauri::command]
+async fn clear_webview_cache(app: AppHandle) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let dirs = webview_cache_dirs(&app)?;
+    info!("Clearing webview cache in {:?}", dirs);
+    let locked: usize = tokio::task::spawn_blocking(move || dirs.iter().map(|dir| clear_cache_entry(dir)).sum())
+        .await
+        .map_err(|e| format!("Failed to clear webview cache: {}", e))?;
+    if locked > 0 {
+        // The running webview holds some cache files open; they go on next launch
+        warn!("{} webview cache entries are in use and will be cleared after a restart", locked);
+    }
+    Ok(())
+}
+
+// Maintenance
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+// Let startup I/O settle before the first pass
+const MAINTENANCE_STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+struct MaintenanceReport {
+    logs: Freed,
+    cache: Freed,
+}
+
+async fn run_maintenance(app: &AppHandle) -> Result<MaintenanceReport, String> {
+    let policy = app.state::<AppState>().config.lock().unwrap().maintenance.clone();
+    let log_dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to get app log directory: {}", e))?;
+    let cache_dirs = webview_cache_dirs(app)?;
+
+    let report = tokio::task::spawn_blocking(move || {
+        let max_age = Duration::from_secs(u64::from(policy.max_log_age_days) * 24 * 60 * 60);
+        let active_log = log_dir.join(logging::LOG_FILE_NAME);
+        MaintenanceReport {
+            logs: maintenance::prune_older_than(&log_dir, max_age, &[active_log]),
+            cache: maintenance::evict_lru(&cache_dirs, policy.max_cache_bytes),
+        }
+    })
+    .await
+    .map_err(|e| format!("Maintenance failed: {}", e))?;
+
+    info!(
+        "Maintenance removed {} log files ({} bytes) and {} cache files ({} bytes)",
+        report.logs.files, report.logs.bytes, report.cache.files, report.cache.bytes
+    );
+    let _ = app.emit("maintenance-complete", &report);
+    Ok(report)
+}
+
+async fn run_maintenance_schedule(app: AppHandle) {
+    let run_on_startup = app.state::<AppState>().config.lock().unwrap().maintenance.run_on_startup;
+    tokio::time::sleep(if run_on_startup { MAINTENANCE_STARTUP_DELAY } else { MAINTENANCE_INTERVAL }).await;
+    loop {
+        if let Err(e) = run_maintenance(&app).await {
+            warn!("{}", e);
+        }
+        tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+    }
+}
+
+#[tauri::command]
+async fn set_maintenance_policy(app: AppHandle, state: State<'_, AppState>, policy: MaintenancePolicy) -> Result<(), String> {
+    let _timing = time_command!(state);
+    policy.validate()?;
+    info!("Setting maintenance policy to {:?}", policy);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.maintenance = policy;
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn run_maintenance_now(app: AppHandle) -> Result<MaintenanceReport, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    run_maintenance(&app).await
+}
+
+// Credentials
+#[tauri::command]
+async fn list_credentials(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let _timing = time_command!(state);
+    Ok(state.config.lock().unwrap().credentials.clone())
+}
+
+#[tauri::command]
+async fn set_credential(app: AppHandle, state: State<'_, AppState>, name: String, secret: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    if name.trim().is_empty() {
+        return Err("Credential name must not be empty".to_string());
+    }
+    credentials::store(&name, &secret)?;
+    info!("Stored credential {}", name);
+
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        if !config.credentials.contains(&name) {
+            config.credentials.push(name);
+        }
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn delete_credential(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    credentials::delete(&name)?;
+    info!("Deleted credential {}", name);
+
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.credentials.retain(|stored| stored != &name);
+        config.clone()
+    };
+    persist_config(&app, &config)
+}
+
+#[tauri::command]
+async fn get_credential(state: State<'_, AppState>, name: String) -> Result<String, CredentialError> {
+    let _timing = time_command!(state);
+    let lookup = name.clone();
+    let result = tokio::task::spawn_blocking(move || credentials::read(&lookup))
+        .await
+        .map_err(|e| CredentialError {
+            kind: CredentialErrorKind::Unavailable,
+            name,
+            message: format!("Credential lookup failed: {}", e),
+            guidance: None,
+        })?;
+    if let Err(e) = &result {
+        warn!("{}", e);
+    }
+    result
+}
+
+// The frontend answers credential-reentry-required by asking for the secret again
+#[tauri::command]
+async fn repair_credential(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let key = name.clone();
+    tokio::task::spawn_blocking(move || credentials::repair(&key))
+        .await
+        .map_err(|e| format!("Credential repair failed: {}", e))?
+        .map_err(|e| e.to_string())?;
+    info!("Removed credential {} for re-entry", name);
+
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.credentials.retain(|stored| stored != &name);
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    let _ = app.emit("credential-reentry-required", &name);
+    Ok(())
+}
+
+// Returns the encrypted blob to the caller; nothing is written to disk here
+#[tauri::command]
+async fn export_credentials(state: State<'_, AppState>, passphrase: String) -> Result<String, String> {
+    let _timing = time_command!(state);
+    if passphrase.chars().count() < 8 {
+        return Err("Passphrase must be at least 8 characters".to_string());
+    }
+    let names = state.config.lock().unwrap().credentials.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut secrets = std::collections::BTreeMap::new();
+        for name in names {
+            match credentials::load(&name)? {
+                Some(secret) => {
+                    secrets.insert(name, secret);
+                }
+                None => warn!("Credential {} is missing from the keychain; skipping", name),
+            }
+        }
+        info!("Exporting {} credential(s)", secrets.len());
+        credentials::encrypt_backup(&secrets, &passphrase)
+    })
+    .await
+    .map_err(|e| format!("Credential export failed: {}", e))?
+}
+
+#[tauri::command]
+async fn import_credentials(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    blob: String,
+    passphrase: String,
+) -> Result<usize, String> {
+    let _timing = time_command!(state);
+    let imported = tokio::task::spawn_blocking(move || {
+        let secrets = credentials::decrypt_backup(&blob, &passphrase)?;
+        for (name, secret) in &secrets {
+            credentials::store(name, secret)?;
+        }
+        Ok::<_, String>(secrets.into_keys().collect::<Vec<_>>())
+    })
+    .await
+    .map_err(|e| format!("Credential import failed: {}", e))??;
+    info!("Imported {} credential(s)", imported.len());
+
+    let count = imported.len();
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        for name in imported {
+            if !config.credentials.contains(&name) {
+                config.credentials.push(name);
+            }
+        }
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    Ok(count)
+}
+
+// Support
+#[tauri::command]
+async fn create_support_bundle(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    let data_dir = data_dir(&app)?;
+    let log_dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to get app log directory: {}", e))?;
+
+    let mut config = serde_json::to_value(state.config.lock().unwrap().clone())
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    redact::redact_json(&mut config);
+    let system_info = get_system_info(app.clone(), state.clone()).await?;
+    let health_history: Vec<HealthRecord> = state.health_history.lock().unwrap().iter().cloned().collect();
+
+    let bundle_path = data_dir.join(format!(
+        "stratmaster-support-{}.zip",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    info!("Creating support bundle at: {}", bundle_path.display());
+
+    let path = bundle_path.clone();
+    tokio::task::spawn_blocking(move || {
+        write_support_bundle(&path, &log_dir, &config, &system_info, &health_history)
+    })
+    .await
+    .map_err(|e| format!("Support bundle task failed: {}", e))?
+    .map_err(|e| {
+        let error_msg = format!("Failed to create support bundle: {}", e);
+        error!("{}", error_msg);
+        let _ = std::fs::remove_file(&bundle_path);
+        error_msg
+    })?;
+
+    Ok(bundle_path.to_string_lossy().to_string())
+}
+
+// A link-sized summary, for when a full bundle is more than the question needs
+#[tauri::command]
+async fn create_support_link(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let _timing = time_command!(state);
+    let version = app_version(&app);
+    let mut summary = DiagnosticsSummary::new(version.version, version.commit);
+    summary.os_release = sysinfo::System::kernel_version();
+    summary.backend = {
+        let kind = *state.backend_kind.lock().unwrap();
+        let base_url = state.api_base_url.lock().unwrap().clone();
+        let locality = if is_local_url(&base_url) { "local" } else { "remote" };
+        Some(match kind {
+            Some(kind) => format!("{:?} ({})", kind, locality),
+            None => locality.to_string(),
+        })
+    };
+    summary.service_health = state
+        .health_status
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(service, healthy)| (service.clone(), *healthy))
+        .collect();
+    summary.recent_errors = logging::recent_entries(log::LevelFilter::Error, 20)
+        .into_iter()
+        .map(|entry| entry.message)
+        .collect();
+
+    let link = support::encode_link(summary)?;
+    info!("Created support link ({} characters)", link.len());
+    Ok(link)
+}
+
+#[tauri::command]
+async fn decode_support_link(state: State<'_, AppState>, code: String) -> Result<DiagnosticsSummary, String> {
+    let _timing = time_command!(state);
+    support::decode(&code)
+}
+
+fn write_support_bundle(
+    path: &Path,
+    log_dir: &Path,
+    config: &serde_json::Value,
+    system_info: &SystemInfo,
+    health_history: &[HealthRecord],
+) -> zip::result::ZipResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("config.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, config).map_err(std::io::Error::from)?;
+    zip.start_file("system_info.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, system_info).map_err(std::io::Error::from)?;
+    zip.start_file("health_history.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, health_history).map_err(std::io::Error::from)?;
+
+    // Logs are streamed line by line so secrets can be scrubbed without loading whole files
+    for log_file in logging::log_files(log_dir) {
+        let name = log_file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        zip.start_file(format!("logs/{}", name), options)?;
+        let reader = BufReader::new(std::fs::File::open(&log_file)?);
+        for line in reader.lines() {
+            let line = line?;
+            writeln!(zip, "{}", redact::redact_text(&line))?;
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+// Version and About
+const APP_LICENSE: &str = "Apache-2.0";
+const MENU_ID_ABOUT: &str = "about";
+const TRAY_ID_SHOW: &str = "tray-show";
+const TRAY_ID_ABOUT: &str = "tray-about";
+const TRAY_ID_QUIT: &str = "tray-quit";
+const MENU_ID_ALWAYS_ON_TOP: &str = "always-on-top";
+const TRAY_ID_ALWAYS_ON_TOP: &str = "tray-always-on-top";
+const ALWAYS_ON_TOP_SHORTCUT: &str = "CmdOrCtrl+Shift+T";
+
+fn app_version(app: &AppHandle) -> AppVersion {
+    let package = app.package_info();
+    AppVersion {
+        name: package.name.clone(),
+        version: package.version.to_string(),
+        commit: env!("STRATMASTER_GIT_COMMIT").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        license: APP_LICENSE.to_string(),
+    }
+}
+
+#[tauri::command]
+async fn get_app_version(app: AppHandle) -> Result<AppVersion, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    Ok(app_version(&app))
+}
+
+// The same standard panel, and metadata, as the app menu's About item
+#[cfg(target_os = "macos")]
+fn show_about(app: &AppHandle) {
+    use objc2::rc::Id;
+    use objc2::runtime::AnyObject;
+    use objc2_app_kit::{
+        NSAboutPanelOptionApplicationName, NSAboutPanelOptionApplicationVersion, NSAboutPanelOptionCredits,
+        NSAboutPanelOptionVersion, NSApplication,
+    };
+    use objc2_foundation::{MainThreadMarker, NSAttributedString, NSDictionary, NSString};
+
+    let version = app_version(app);
+    let _ = app.run_on_main_thread(move || {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        let text = |value: &str| -> Id<AnyObject> { Id::into_super(Id::into_super(NSString::from_str(value))) };
+        let credits = NSAttributedString::from_nsstring(&NSString::from_str(&format!("Built with Tauri {}", version.tauri_version)));
+        let keys = unsafe {
+            [
+                NSAboutPanelOptionApplicationName,
+                NSAboutPanelOptionApplicationVersion,
+                NSAboutPanelOptionVersion,
+                NSAboutPanelOptionCredits,
+            ]
+        };
+        let options = NSDictionary::from_vec(
+            &keys,
+            vec![
+                text(&version.name),
+                text(&version.version),
+                text(&version.commit),
+                Id::into_super(Id::into_super(credits)),
+            ],
+        );
+        let application = NSApplication::sharedApplication(mtm);
+        unsafe { application.orderFrontStandardAboutPanelWithOptions(&options) };
+    });
+}
+
+#[cfg(not(target_os = "macos"))]
+fn show_about(app: &AppHandle) {
+    let version = app_version(app);
+    app.dialog()
+        .message(format!(
+            "Version {}\nCommit {}\nTauri {}\nLicense {}",
+            version.version, version.commit, version.tauri_version, version.license
+        ))
+        .title(format!("About {}", version.name))
+        .kind(MessageDialogKind::Info)
+        .show(|_| {});
+}
+
+#[tauri::command]
+async fn show_about_dialog(app: AppHandle) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    show_about(&app);
+    Ok(())
+}
+
+// On macOS the app menu uses the standard About panel; elsewhere a Help menu
+// item opens the same message dialog as `show_about_dialog`.
+fn build_app_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    #[cfg(target_os = "macos")]
+    {
+        let version = app_version(app);
+        let about = PredefinedMenuItem::about(
+            app,
+            Some("About StratMaster"),
+            Some(tauri::menu::AboutMetadata {
+                name: Some(version.name.clone()),
+                version: Some(version.version.clone()),
+                short_version: Some(version.commit.clone()),
+                license: Some(version.license.clone()),
+                credits: Some(format!("Built with Tauri {}", version.tauri_version)),
+                ..Default::default()
+            }),
+        )?;
+        let app_menu = Submenu::with_items(
+            app,
+            "StratMaster",
+            true,
+            &[
+                &about,
+                &PredefinedMenuItem::separator(app)?,
+                &PredefinedMenuItem::hide(app, None)?,
+                &PredefinedMenuItem::quit(app, None)?,
+            ],
+        )?;
+        let edit_menu = Submenu::with_items(
+            app,
+            "Edit",
+            true,
+            &[
+                &PredefinedMenuItem::undo(app, None)?,
+                &PredefinedMenuItem::redo(app, None)?,
+                &PredefinedMenuItem::separator(app)?,
+                &PredefinedMenuItem::cut(app, None)?,
+                &PredefinedMenuItem::copy(app, None)?,
+                &PredefinedMenuItem::paste(app, None)?,
+                &PredefinedMenuItem::select_all(app, None)?,
+            ],
+        )?;
+        let window_menu = Submenu::with_items(app, "Window", true, &[&always_on_top_menu_item(app)?])?;
+        Menu::with_items(app, &[&app_menu, &edit_menu, &window_menu])
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let about = MenuItem::with_id(app, MENU_ID_ABOUT, "About StratMaster", true, None::<&str>)?;
+        let help_menu = Submenu::with_items(app, "Help", true, &[&about])?;
+        let window_menu = Submenu::with_items(app, "Window", true, &[&always_on_top_menu_item(app)?])?;
+        Menu::with_items(app, &[&window_menu, &help_menu])
+    }
+}
+
+// The menu item toggles itself; registering it lets the other copies follow
+fn always_on_top_menu_item(app: &AppHandle) -> tauri::Result<CheckMenuItem<tauri::Wry>> {
+    let item = CheckMenuItem::with_id(app, MENU_ID_ALWAYS_ON_TOP, "Always on Top", true, false, Some(ALWAYS_ON_TOP_SHORTCUT))?;
+    if let Some(state) = app.try_state::<AppState>() {
+        state.always_on_top_items.lock().unwrap().push(item.clone());
+    }
+    Ok(item)
+}
+
+fn build_tray(app: &AppHandle) -> tauri::Result<()> {
+    let state = app.state::<AppState>();
+    let pinned = state.config.lock().unwrap().always_on_top;
+    let always_on_top = CheckMenuItem::with_id(app, TRAY_ID_ALWAYS_ON_TOP, "Always on Top", true, pinned, None::<&str>)?;
+    state.always_on_top_items.lock().unwrap().push(always_on_top.clone());
+    let menu = Menu::with_items(
+        app,
+        &[
+            &MenuItem::with_id(app, TRAY_ID_SHOW, "Show StratMaster", true, None::<&str>)?,
+            &MenuItem::with_id(app, TRAY_ID_ABOUT, "About StratMaster", true, None::<&str>)?,
+            &always_on_top,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, TRAY_ID_QUIT, "Quit", true, None::<&str>)?,
+        ],
+    )?;
+
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip("StratMaster")
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            TRAY_ID_SHOW => focus_main_window(app),
+            TRAY_ID_ABOUT => show_about(app),
+            TRAY_ID_ALWAYS_ON_TOP => toggle_always_on_top(app),
+            TRAY_ID_QUIT => shutdown(app),
+            _ => {}
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
+}
+
+// Always on top
+// Fullscreen windows are never pinned (macOS would pull them out of their
+// Space); the preference is reapplied when a window leaves fullscreen or is
+// restored from the taskbar.
+fn apply_always_on_top(window: &tauri::WebviewWindow, enabled: bool) {
+    let pinned = enabled && !window.is_fullscreen().unwrap_or(false);
+    if window.is_always_on_top().ok() == Some(pinned) {
+        return;
+    }
+    if let Err(e) = window.set_always_on_top(pinned) {
+        warn!("Failed to set always on top for window {}: {}", window.label(), e);
+    }
+}
+
+fn set_always_on_top_everywhere(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.always_on_top = enabled;
+        config.clone()
+    };
+    for window in app.webview_windows().values() {
+        apply_always_on_top(window, enabled);
+    }
+    for item in state.always_on_top_items.lock().unwrap().iter() {
+        let _ = item.set_checked(enabled);
+    }
+    info!("Always on top {}", if enabled { "enabled" } else { "disabled" });
+    persist_config(app, &config)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct SystemInfo {
-    platform: String,
-    arch: String,
-    cpu_count: usize,
-    memory_total: u64,
-    has_gpu: bool,
-    recommended_config: String,
+fn toggle_always_on_top(app: &AppHandle) {
+    let enabled = !app.state::<AppState>().config.lock().unwrap().always_on_top;
+    if let Err(e) = set_always_on_top_everywhere(app, enabled) {
+        warn!("{}", e);
+    }
 }
 
-// Tauri commands for frontend-backend communication
+// The preference covers every window, whichever one asks
+#[tauri::command]
+async fn set_always_on_top(app: AppHandle, window: Window, enabled: bool) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    debug!("Always on top set from window {}", window.label());
+    set_always_on_top_everywhere(&app, enabled)
+}
 
 #[tauri::command]
-async fn get_system_info() -> Result<SystemInfo, String> {
-    info!("Getting system information");
-    
-    let platform = std::env::consts::OS.to_string();
-    let arch = std::env::consts::ARCH.to_string();
-    let cpu_count = num_cpus::get();
-    
-    // Estimate memory (simplified)
-    let memory_total = match sys_info::mem_info() {
-        Ok(mem) => mem.total * 1024, // Convert KB to bytes
-        Err(_) => 8_000_000_000, // Default to 8GB
-    };
-    
-    // GPU detection (simplified - would need platform-specific implementations)
-    let has_gpu = std::env::var("CUDA_VISIBLE_DEVICES").is_ok() || 
-                  std::env::var("GPU_DEVICE").is_ok();
-    
-    // Recommend configuration based on specs
-    let recommended_config = if memory_total > 16_000_000_000 && has_gpu {
-        "high-performance".to_string()
-    } else if memory_total > 8_000_000_000 {
-        "standard".to_string()
-    } else {
-        "lightweight".to_string()
+async fn is_always_on_top(window: Window) -> Result<bool, String> {
+    let _timing = time_command!(window.state::<AppState>());
+    window
+        .is_always_on_top()
+        .map_err(|e| format!("Failed to query always on top: {}", e))
+}
+
+// Window focus and deep links
+fn focus_main_window(app: &AppHandle) {
+    let window = match app.get_webview_window("main") {
+        Some(window) => window,
+        // The main window may have been closed while the tray kept the app alive
+        None => match with_webview_flags(
+            tauri::WebviewWindowBuilder::new(app, "main", tauri::WebviewUrl::default()),
+            &app.state::<AppState>().webview_flags,
+        )
+        .title("StratMaster")
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .build()
+        {
+            Ok(window) => window,
+            Err(e) => {
+                error!("Failed to recreate main window: {}", e);
+                return;
+            }
+        },
     };
-    
-    Ok(SystemInfo {
-        platform,
-        arch,
-        cpu_count,
-        memory_total,
-        has_gpu,
-        recommended_config,
-    })
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
 }
 
-#[tauri::command]
-async fn check_api_health(state: State<'_, AppState>) -> Result<HealthResponse, String> {
-    let base_url = state.api_base_url.lock().unwrap().clone();
-    let health_url = if base_url.is_empty() {
-        "http://localhost:8080/healthz".to_string()
-    } else {
-        format!("{}/healthz", base_url)
+// Validates the link, applies what the backend owns (environment switch) and
+// hands the rest to the frontend. The link is also kept as pending so a
+// frontend that isn't listening yet (cold start) can pick it up.
+fn handle_deep_link(app: &AppHandle, raw: &str) {
+    focus_main_window(app);
+    let link = match deeplink::parse_deep_link(raw) {
+        Ok(link) => link,
+        Err(e) => {
+            warn!("Ignoring deep link: {}", e);
+            return;
+        }
     };
-    
-    info!("Checking API health at: {}", health_url);
-    
-    match reqwest::get(&health_url).await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<HealthResponse>().await {
-                    Ok(health) => {
-                        info!("API health check successful: {:?}", health);
-                        Ok(health)
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse health response: {}", e);
-                        Err(format!("Failed to parse health response: {}", e))
-                    }
-                }
-            } else {
-                let error_msg = format!("API health check failed with status: {}", response.status());
-                warn!("{}", error_msg);
-                Err(error_msg)
+    info!("Handling deep link: {:?}", link);
+
+    if let Some(environment) = &link.environment {
+        if let Err(e) = activate_environment(app, environment) {
+            warn!("Deep link could not switch environment: {}", e);
+        }
+    }
+    if let Some(code) = &link.support_code {
+        match support::decode(code) {
+            Ok(summary) => {
+                let _ = app.emit("support-summary", &summary);
             }
+            Err(e) => warn!("Deep link carried an unreadable support code: {}", e),
         }
-        Err(e) => {
-            let error_msg = format!("Failed to connect to API: {}", e);
-            error!("{}", error_msg);
-            Err(error_msg)
+    }
+    *app.state::<AppState>().pending_deep_link.lock().unwrap() = Some(link.clone());
+    let _ = app.emit("deep-link", &link);
+}
+
+#[cfg(desktop)]
+#[derive(Debug, Clone, Serialize)]
+struct SecondInstance {
+    args: Vec<String>,
+    cwd: String,
+}
+
+// A second launch exits immediately and forwards its arguments here. Scheme
+// URLs among them go through the same path as any other deep link.
+#[cfg(desktop)]
+fn handle_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    info!("Second instance launched with args: {:?}", args);
+    focus_main_window(app);
+
+    let scheme_prefix = format!("{}:", deeplink::URL_SCHEME);
+    for arg in args.iter().skip(1) {
+        if arg.to_ascii_lowercase().starts_with(&scheme_prefix) {
+            handle_deep_link(app, arg);
         }
     }
+    let _ = app.emit("second-instance", SecondInstance { args, cwd });
 }
 
 #[tauri::command]
-async fn set_api_base_url(state: State<'_, AppState>, url: String) -> Result<(), String> {
-    info!("Setting API base URL to: {}", url);
-    *state.api_base_url.lock().unwrap() = url;
-    Ok(())
+async fn take_pending_deep_link(state: State<'_, AppState>) -> Result<Option<DeepLink>, String> {
+    let _timing = time_command!(state);
+    Ok(state.pending_deep_link.lock().unwrap().take())
 }
 
+// Installers register the scheme; this covers dev builds and portable installs.
+// macOS only supports registration through the bundle's Info.plist.
 #[tauri::command]
-async fn get_app_data_dir(app: AppHandle) -> Result<String, String> {
-    match app.path().app_data_dir() {
-        Ok(path) => {
-            let path_str = path.to_string_lossy().to_string();
-            info!("App data directory: {}", path_str);
-            Ok(path_str)
+async fn register_url_scheme(app: AppHandle) -> Result<bool, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    #[cfg(any(windows, target_os = "linux"))]
+    {
+        app.deep_link()
+            .register(deeplink::URL_SCHEME)
+            .map_err(|e| format!("Failed to register {}:// scheme: {}", deeplink::URL_SCHEME, e))?;
+        info!("Registered {}:// URL scheme", deeplink::URL_SCHEME);
+    }
+
+    app.deep_link()
+        .is_registered(deeplink::URL_SCHEME)
+        .map_err(|e| format!("Failed to query {}:// scheme: {}", deeplink::URL_SCHEME, e))
+}
+
+// Fonts
+// Offered when enumeration finds nothing, e.g. in headless or CI environments
+const FALLBACK_FONTS: &[&str] = &["Arial", "Courier New", "Georgia", "Helvetica", "Times New Roman", "Verdana"];
+
+#[tauri::command]
+async fn list_system_fonts(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let _timing = time_command!(state);
+    if let Some(fonts) = state.system_fonts.lock().unwrap().as_ref() {
+        return Ok(fonts.clone());
+    }
+    let mut fonts = tokio::task::spawn_blocking(system::font_families)
+        .await
+        .map_err(|e| format!("Font enumeration failed: {}", e))?;
+    if fonts.is_empty() {
+        warn!("No system fonts found; offering the built-in fallback list");
+        fonts = FALLBACK_FONTS.iter().map(|font| font.to_string()).collect();
+    }
+    info!("Found {} font families", fonts.len());
+    *state.system_fonts.lock().unwrap() = Some(fonts.clone());
+    Ok(fonts)
+}
+
+#[tauri::command]
+async fn set_ui_font(app: AppHandle, state: State<'_, AppState>, font: Option<String>) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let font = font.map(|font| font.trim().to_string()).filter(|font| !font.is_empty());
+    info!("UI font set to {}", font.as_deref().unwrap_or("default"));
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.ui_font = font.clone();
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    let _ = app.emit("font-changed", &font);
+    Ok(())
+}
+
+// Theme
+fn theme_name(theme: tauri::Theme) -> &'static str {
+    match theme {
+        tauri::Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ThemeChange {
+    scheme: String,
+    // Theme the UI should render: the system scheme when configured as "auto"
+    resolved_theme: String,
+}
+
+fn handle_system_theme_change(app: &AppHandle, scheme: &str) {
+    let configured = app.state::<AppState>().config.lock().unwrap().theme.clone();
+    let follows_system = configured.eq_ignore_ascii_case("auto");
+    let change = ThemeChange {
+        scheme: scheme.to_string(),
+        resolved_theme: if follows_system { scheme.to_string() } else { configured },
+    };
+    info!("System color scheme changed to {}", scheme);
+
+    if follows_system {
+        // Let every window track the OS appearance again
+        for window in app.webview_windows().values() {
+            let _ = window.set_theme(None);
         }
-        Err(e) => {
-            let error_msg = format!("Failed to get app data directory: {}", e);
-            error!("{}", error_msg);
-            Err(error_msg)
+    }
+    let _ = app.emit("system-theme-changed", &change);
+}
+
+#[tauri::command]
+async fn get_system_theme(window: Window) -> Result<String, String> {
+    let _timing = time_command!(window.state::<AppState>());
+    window
+        .theme()
+        .map(|theme| theme_name(theme).to_string())
+        .map_err(|e| format!("Failed to read system theme: {}", e))
+}
+
+// GNOME doesn't reliably surface appearance changes to the webview window, so
+// follow the color-scheme setting through gsettings.
+#[cfg(target_os = "linux")]
+fn watch_gnome_color_scheme(app: AppHandle) {
+    std::thread::spawn(move || {
+        let child = std::process::Command::new("gsettings")
+            .args(["monitor", "org.gnome.desktop.interface", "color-scheme"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            return;
+        };
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let scheme = if line.contains("prefer-dark") { "dark" } else { "light" };
+                handle_system_theme_change(&app, scheme);
+            }
         }
+        let _ = child.wait();
+    });
+}
+
+// Accessibility
+// The OS settings apply until the user picks their own; clearing the choice
+// goes back to following the OS.
+#[derive(Debug, Clone, Serialize)]
+struct AccessibilitySettings {
+    #[serde(flatten)]
+    settings: AccessibilityConfig,
+    follows_system: bool,
+}
+
+async fn effective_accessibility(state: &AppState) -> Result<AccessibilitySettings, String> {
+    if let Some(settings) = state.config.lock().unwrap().accessibility {
+        return Ok(AccessibilitySettings { settings, follows_system: false });
     }
+    let settings = tokio::task::spawn_blocking(system::system_accessibility)
+        .await
+        .map_err(|e| format!("Accessibility detection failed: {}", e))?;
+    Ok(AccessibilitySettings { settings, follows_system: true })
 }
 
 #[tauri::command]
-async fn open_external_url(url: String) -> Result<(), String> {
-    info!("Opening external URL: {}", url);
-    
-    match webbrowser::open(&url) {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            let error_msg = format!("Failed to open URL: {}", e);
-            error!("{}", error_msg);
-            Err(error_msg)
+async fn get_accessibility(state: State<'_, AppState>) -> Result<AccessibilitySettings, String> {
+    let _timing = time_command!(state);
+    effective_accessibility(&state).await
+}
+
+// `None` goes back to following the OS
+#[tauri::command]
+async fn set_accessibility(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: Option<AccessibilityConfig>,
+) -> Result<AccessibilitySettings, String> {
+    let _timing = time_command!(state);
+    if let Some(settings) = &settings {
+        if !system::FONT_SCALE_RANGE.contains(&settings.font_scale) {
+            return Err(format!(
+                "Font scale {} is out of range ({}-{})",
+                settings.font_scale,
+                system::FONT_SCALE_RANGE.start(),
+                system::FONT_SCALE_RANGE.end()
+            ));
         }
     }
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.accessibility = settings;
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+
+    let effective = effective_accessibility(&state).await?;
+    info!(
+        "Accessibility: high contrast {}, reduce motion {}, font scale {}{}",
+        effective.settings.high_contrast,
+        effective.settings.reduce_motion,
+        effective.settings.font_scale,
+        if effective.follows_system { " (system)" } else { "" }
+    );
+    let _ = app.emit("accessibility-changed", &effective);
+    Ok(effective)
 }
 
-#[tauri::command] 
-async fn show_file_in_folder(path: String) -> Result<(), String> {
-    info!("Showing file in folder: {}", path);
-    
-    let path_buf = PathBuf::from(&path);
-    if !path_buf.exists() {
-        return Err(format!("File does not exist: {}", path));
+// Window management
+const MIN_WINDOW_SIZE: (u32, u32) = (800, 600);
+const MAX_DEFAULT_WINDOW_SIZE: (u32, u32) = (1920, 1200);
+
+// Default window size as a proportion of the monitor's work area in logical
+// pixels, so fractional scaling doesn't produce a tiny or oversized window.
+fn recommended_window_size(monitor: &tauri::Monitor) -> (u32, u32) {
+    let scale = monitor.scale_factor().max(1.0);
+    let work_area = monitor.work_area().size;
+    let logical_width = (work_area.width as f64 / scale) as u32;
+    let logical_height = (work_area.height as f64 / scale) as u32;
+
+    let width = ((logical_width as f64 * 0.75) as u32)
+        .clamp(MIN_WINDOW_SIZE.0, MAX_DEFAULT_WINDOW_SIZE.0)
+        .min(logical_width.max(MIN_WINDOW_SIZE.0));
+    let height = ((logical_height as f64 * 0.8) as u32)
+        .clamp(MIN_WINDOW_SIZE.1, MAX_DEFAULT_WINDOW_SIZE.1)
+        .min(logical_height.max(MIN_WINDOW_SIZE.1));
+    (width, height)
+}
+
+fn primary_or_first_monitor(app: &AppHandle) -> Option<tauri::Monitor> {
+    match app.primary_monitor() {
+        Ok(Some(monitor)) => Some(monitor),
+        _ => app
+            .available_monitors()
+            .ok()
+            .and_then(|monitors| monitors.into_iter().next()),
     }
-    
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("explorer")
-            .args(["/select,", &path])
-            .spawn()
-            .map_err(|e| format!("Failed to open explorer: {}", e))?;
+}
+
+// UI density
+// Minimal mode turns the main window into a small always-on-top widget. The
+// size it had before is saved so leaving minimal mode puts it back, even
+// after a restart.
+const MINIMAL_WINDOW_SIZE: (u32, u32) = (320, 200);
+
+fn apply_minimal_window(window: &tauri::WebviewWindow) {
+    if window.is_fullscreen().unwrap_or(false) {
+        let _ = window.set_fullscreen(false);
     }
-    
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .args(["-R", &path])
-            .spawn()
-            .map_err(|e| format!("Failed to open finder: {}", e))?;
+    let size = tauri::LogicalSize::new(MINIMAL_WINDOW_SIZE.0, MINIMAL_WINDOW_SIZE.1);
+    let _ = window.set_min_size(Some(size));
+    if let Err(e) = window.set_size(size) {
+        warn!("Failed to shrink window {}: {}", window.label(), e);
     }
-    
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(path_buf.parent().unwrap_or(&path_buf))
-            .spawn()
-            .map_err(|e| format!("Failed to open file manager: {}", e))?;
+    apply_always_on_top(window, true);
+}
+
+fn restore_from_minimal(window: &tauri::WebviewWindow, size: Option<(u32, u32)>, always_on_top: bool) {
+    let _ = window.set_min_size(Some(tauri::LogicalSize::new(MIN_WINDOW_SIZE.0, MIN_WINDOW_SIZE.1)));
+    let (width, height) = size
+        .or_else(|| window.current_monitor().ok().flatten().map(|monitor| recommended_window_size(&monitor)))
+        .unwrap_or(MIN_WINDOW_SIZE);
+    if let Err(e) = window.set_size(tauri::LogicalSize::new(width, height)) {
+        warn!("Failed to restore window {} size: {}", window.label(), e);
     }
-    
+    apply_always_on_top(window, always_on_top);
+}
+
+#[tauri::command]
+async fn set_ui_density(app: AppHandle, state: State<'_, AppState>, mode: UiDensity) -> Result<(), String> {
+    let _timing = time_command!(state);
+    let (previous, always_on_top, mut restore_size) = {
+        let config = state.config.lock().unwrap();
+        (config.ui_density, config.always_on_top, config.minimal_restore_size)
+    };
+    if let Some(window) = app.get_webview_window("main") {
+        match (previous == UiDensity::Minimal, mode == UiDensity::Minimal) {
+            (false, true) => {
+                restore_size = window
+                    .inner_size()
+                    .ok()
+                    .map(|size| size.to_logical::<u32>(window.scale_factor().unwrap_or(1.0)))
+                    .map(|size| (size.width, size.height));
+                apply_minimal_window(&window);
+            }
+            (true, false) => restore_from_minimal(&window, restore_size.take(), always_on_top),
+            _ => {}
+        }
+    }
+    info!("UI density set to {:?}", mode);
+    let config = {
+        let mut config = state.config.lock().unwrap();
+        config.ui_density = mode;
+        config.minimal_restore_size = restore_size;
+        config.clone()
+    };
+    persist_config(&app, &config)?;
+    let _ = app.emit("ui-density-changed", mode);
     Ok(())
 }
 
 #[tauri::command]
-async fn get_local_server_status() -> Result<HashMap<String, bool>, String> {
-    let mut status = HashMap::new();
-    
-    // Check common local services
-    let services = vec![
-        ("api", "http://localhost:8080/healthz"),
-        ("research-mcp", "http://localhost:8081/health"),  
-        ("knowledge-mcp", "http://localhost:8082/health"),
-        ("router-mcp", "http://localhost:8083/health"),
-    ];
-    
-    for (service, url) in services {
-        let is_healthy = match reqwest::get(url).await {
-            Ok(response) => response.status().is_success(),
-            Err(_) => false,
+async fn get_recommended_window_size(app: AppHandle) -> Result<(u32, u32), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let monitor = primary_or_first_monitor(&app).ok_or_else(|| "No monitor detected".to_string())?;
+    let size = recommended_window_size(&monitor);
+    info!(
+        "Recommended window size {}x{} (scale factor {})",
+        size.0,
+        size.1,
+        monitor.scale_factor()
+    );
+    Ok(size)
+}
+
+// Workspace layout
+const WORKSPACE_LAYOUT_FILE: &str = "workspace-layout.json";
+
+// Geometry is kept in physical pixels, matching what monitors report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WindowLayout {
+    label: String,
+    route: String,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+fn workspace_layout_path(app: &AppHandle) -> Result<PathBuf, String> {
+    data_dir(app).map(|dir| dir.join(WORKSPACE_LAYOUT_FILE))
+}
+
+#[tauri::command]
+async fn save_workspace_layout(app: AppHandle) -> Result<(), String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let mut layout: Vec<WindowLayout> = app
+        .webview_windows()
+        .into_iter()
+        .filter_map(|(label, window)| {
+            let position = window.outer_position().ok()?;
+            let size = window.inner_size().ok()?;
+            let route = window
+                .url()
+                .map(|url| {
+                    let mut route = url.path().to_string();
+                    if let Some(query) = url.query() {
+                        route.push('?');
+                        route.push_str(query);
+                    }
+                    if let Some(fragment) = url.fragment() {
+                        route.push('#');
+                        route.push_str(fragment);
+                    }
+                    route
+                })
+                .unwrap_or_else(|_| "/".to_string());
+            Some(WindowLayout {
+                label,
+                route,
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            })
+        })
+        .collect();
+    layout.sort_by(|a, b| a.label.cmp(&b.label));
+
+    let path = workspace_layout_path(&app)?;
+    let contents = serde_json::to_string_pretty(&layout)
+        .map_err(|e| format!("Failed to serialize workspace layout: {}", e))?;
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write workspace layout {}: {}", path.display(), e))?;
+    info!("Saved layout of {} window(s) to {}", layout.len(), path.display());
+    Ok(())
+}
+
+// Moves a window whose saved position is on no connected monitor onto the
+// primary one, shrinking it if it no longer fits.
+fn clamp_to_monitors(app: &AppHandle, layout: &WindowLayout) -> (i32, i32, u32, u32) {
+    let monitors = app.available_monitors().unwrap_or_default();
+    let center = (
+        layout.x as i64 + layout.width as i64 / 2,
+        layout.y as i64 + layout.height as i64 / 2,
+    );
+    let on_screen = monitors.iter().any(|monitor| {
+        let position = monitor.position();
+        let size = monitor.size();
+        center.0 >= position.x as i64
+            && center.0 < position.x as i64 + size.width as i64
+            && center.1 >= position.y as i64
+            && center.1 < position.y as i64 + size.height as i64
+    });
+    if on_screen {
+        return (layout.x, layout.y, layout.width, layout.height);
+    }
+
+    let Some(monitor) = primary_or_first_monitor(app) else {
+        return (layout.x, layout.y, layout.width, layout.height);
+    };
+    let work_area = monitor.work_area();
+    let width = layout.width.min(work_area.size.width);
+    let height = layout.height.min(work_area.size.height);
+    let x = work_area.position.x + ((work_area.size.width - width) / 2) as i32;
+    let y = work_area.position.y + ((work_area.size.height - height) / 2) as i32;
+    info!("Window {} was off-screen; moved onto {:?}", layout.label, monitor.name());
+    (x, y, width, height)
+}
+
+#[tauri::command]
+async fn restore_workspace_layout(app: AppHandle) -> Result<usize, String> {
+    let _timing = time_command!(app.state::<AppState>());
+    let path = workspace_layout_path(&app)?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read workspace layout {}: {}", path.display(), e))?;
+    let layout: Vec<WindowLayout> = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid workspace layout {}: {}", path.display(), e))?;
+
+    let mut restored = 0;
+    for saved in &layout {
+        let (x, y, width, height) = clamp_to_monitors(&app, saved);
+        let window = match app.get_webview_window(&saved.label) {
+            Some(window) => {
+                let target = window.url().ok().and_then(|current| current.join(&saved.route).ok());
+                if let Some(target) = target.filter(|target| window.url().ok().as_ref() != Some(target)) {
+                    let _ = window.navigate(target);
+                }
+                window
+            }
+            None => {
+                let url = tauri::WebviewUrl::App(saved.route.trim_start_matches('/').into());
+                let builder = tauri::WebviewWindowBuilder::new(&app, &saved.label, url);
+                match with_webview_flags(builder, &app.state::<AppState>().webview_flags)
+                    .title("StratMaster")
+                    .min_inner_size(MIN_WINDOW_SIZE.0 as f64, MIN_WINDOW_SIZE.1 as f64)
+                    .visible(false)
+                    .build()
+                {
+                    Ok(window) => window,
+                    Err(e) => {
+                        warn!("Failed to recreate window {}: {}", saved.label, e);
+                        continue;
+                    }
+                }
+            }
         };
-        status.insert(service.to_string(), is_healthy);
+        let _ = window.set_size(tauri::PhysicalSize::new(width, height));
+        let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+        let _ = window.show();
+        restored += 1;
     }
-    
-    Ok(status)
+    info!("Restored {} of {} window(s) from {}", restored, layout.len(), path.display());
+    Ok(restored)
+}
+
+// Timestamped on entry; the frontend compares against its own clock to get IPC overhead
+#[tauri::command]
+async fn bridge_echo(state: State<'_, AppState>, payload: String) -> Result<BridgeEcho, String> {
+    let _timing = time_command!(state);
+    bridge::echo(payload)
 }
 
-// Window management
 #[tauri::command]
 async fn toggle_devtools(window: Window) {
+    let _timing = time_command!(window.state::<AppState>());
     if window.is_devtools_open() {
         let _ = window.close_devtools();
     } else {
@@ -218,37 +8395,442 @@ async fn toggle_devtools(window: Window) {
     }
 }
 
+// Refuses restricted commands before they run while kiosk mode is on
+fn kiosk_guard(
+    handler: impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command();
+        if kiosk::is_restricted(command)
+            && invoke.message.webview().state::<AppState>().config.lock().unwrap().kiosk_mode
+        {
+            warn!("Kiosk mode rejected command {}", command);
+            invoke.resolver.reject(kiosk::KIOSK_ERROR);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
 fn main() {
-    env_logger::init();
+    process_start();
+    logging::init(log::LevelFilter::Info);
+    let logger_init = process_start().elapsed();
     info!("Starting StratMaster Desktop Application");
+
+    let mut context = tauri::generate_context!();
+    let mut app_state = AppState::default();
+    let launch = launch_config(&context.config().identifier);
+    app_state.csp = apply_csp(&mut context, &launch);
+    app_state.webview_flags = apply_webview_flags(&mut context, launch.webview_flags);
+    {
+        let mut startup = app_state.startup.lock().unwrap();
+        startup.logger_init = Some(logger_init);
+        startup.state_init = Some(process_start().elapsed());
+    }
     
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Must be the first plugin so a second launch exits before anything
+    // else (config watcher, services) starts up
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(handle_second_instance));
+
+    builder
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_clipboard_manager::init())
-        .manage(AppState::default())
-        .invoke_handler(tauri::generate_handler![
+        .plugin(tauri_plugin_deep_link::init())
+        .manage(app_state)
+        .on_page_load(|webview, payload| {
+            if payload.event() == tauri::webview::PageLoadEvent::Finished {
+                let state = webview.state::<AppState>();
+                let mut startup = state.startup.lock().unwrap();
+                if startup.first_window_shown.is_none() {
+                    startup.first_window_shown = Some(process_start().elapsed());
+                }
+            }
+        })
+        .menu(build_app_menu)
+        .on_menu_event(|app, event| {
+            if event.id() == MENU_ID_ABOUT {
+                show_about(app);
+            } else if event.id() == MENU_ID_ALWAYS_ON_TOP {
+                toggle_always_on_top(app);
+            }
+        })
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::ThemeChanged(theme) => {
+                handle_system_theme_change(window.app_handle(), theme_name(*theme));
+            }
+            tauri::WindowEvent::Focused(false) => handle_focus_change(window, false),
+            // Covers leaving fullscreen and restoring from minimised
+            tauri::WindowEvent::Focused(true) | tauri::WindowEvent::Resized(_) => {
+                if matches!(event, tauri::WindowEvent::Focused(true)) {
+                    handle_focus_change(window, true);
+                }
+                let app = window.app_handle();
+                let pinned = {
+                    let config = app.state::<AppState>().config.lock().unwrap();
+                    config.always_on_top || (config.ui_density == UiDensity::Minimal && window.label() == "main")
+                };
+                if pinned {
+                    if let Some(window) = app.get_webview_window(window.label()) {
+                        apply_always_on_top(&window, true);
+                    }
+                }
+            }
+            _ => {}
+        })
+        .invoke_handler(kiosk_guard(tauri::generate_handler![
             get_system_info,
+            get_hardware_survey,
+            get_cpu_features,
+            list_gpus,
+            select_gpu,
+            apply_hardware_profile,
             check_api_health,
+            get_backend_warnings,
+            acknowledge_warning,
             set_api_base_url, 
+            set_retry_policy,
+            get_retry_policy,
+            set_log_level,
+            get_logs,
+            follow_log_file,
+            stop_following_log,
+            proxy_api_request,
+            set_fallback_environment,
+            set_request_logging,
+            test_failover,
+            invalidate_cache,
+            get_cache_stats,
+            set_response_cache_config,
+            start_request_recording,
+            stop_request_recording,
+            replay_request_recording,
+            set_max_concurrent_requests,
+            get_inflight_request_count,
+            get_client_id,
+            warm_connection,
+            profile_request,
+            inspect_tls_certificate,
+            resolve_host,
+            flush_dns_cache,
+            inspect_resolved_addresses,
+            get_http_client_config,
+            set_http_client_config,
             get_app_data_dir,
+            relocate_data_dir,
             open_external_url,
+            test_notification,
+            get_data_dir_filesystem,
+            is_elevated,
+            get_machine_fingerprint,
+            check_port_firewall,
+            check_data_dir_permissions,
+            benchmark_disk_io,
+            get_diagnostics,
+            copy_diagnostics,
+            set_max_clipboard_bytes,
+            get_webview_cache_size,
+            clear_webview_cache,
+            list_backends,
+            select_backend,
+            discover_backends_mdns,
+            list_environments,
+            add_environment,
+            check_mixed_content,
+            switch_environment,
+            cancel_all_requests,
+            exit_app,
+            get_effective_csp,
+            get_webview_flags,
+            set_webview_flag,
+            load_app_config,
+            save_app_config,
+            list_config_backups,
+            get_rate_limit_state,
+            diff_configs,
+            diff_config_against_default,
+            restore_config_backup,
+            import_signed_config,
+            export_signed_config,
+            get_managed_config_status,
+            set_environment_headers,
+            set_environment_timeouts,
+            get_feature_flag,
+            list_feature_flags,
+            set_feature_flag,
+            refresh_feature_flags,
+            get_backend_openapi,
+            list_backend_endpoints,
+            fetch_headers,
+            estimate_model_fit,
+            list_models,
+            estimate_request_cost,
+            get_pricing_table,
+            set_model_pricing,
+            list_credentials,
+            get_credential,
+            set_credential,
+            delete_credential,
+            repair_credential,
+            export_credentials,
+            import_credentials,
+            create_support_bundle,
+            create_support_link,
+            decode_support_link,
             show_file_in_folder,
             get_local_server_status,
-            toggle_devtools
-        ])
+            start_health_monitor,
+            stop_health_monitor,
+            list_services,
+            register_service,
+            unregister_service,
+            set_event_batch_interval,
+            open_sse_stream,
+            close_sse_stream,
+            get_metrics_prometheus,
+            get_cpu_temperature,
+            get_power_mode,
+            set_pause_on_blur,
+            get_swap_info,
+            get_command_metrics,
+            report_frame_timing,
+            get_render_stats,
+            get_gpu_driver_info,
+            get_vram_headroom,
+            get_pending_os_updates,
+            verify_update_signature,
+            install_update,
+            get_system_locale,
+            set_locale,
+            get_accept_language,
+            set_accept_language,
+            start_local_services,
+            start_backend_services,
+            stop_local_services,
+            validate_service_definitions,
+            detect_container_runtimes,
+            measure_service_chain_latency,
+            restart_service,
+            set_service_recovery,
+            start_service_container,
+            follow_container_logs,
+            stop_following_container_logs,
+            get_container_log_backfill,
+            identify_port_owner,
+            set_maintenance_policy,
+            run_maintenance_now,
+            set_always_on_top,
+            is_always_on_top,
+            start_perf_trace,
+            stop_perf_trace,
+            dump_perf_trace,
+            connect_health_ws,
+            set_health_transport,
+            get_recommended_window_size,
+            set_ui_density,
+            get_accessibility,
+            set_accessibility,
+            enable_control_socket,
+            disable_control_socket,
+            check_stale_locks,
+            save_workspace_layout,
+            restore_workspace_layout,
+            get_system_theme,
+            list_system_fonts,
+            set_ui_font,
+            get_app_version,
+            show_about_dialog,
+            get_startup_metrics,
+            wait_for_backend_ready,
+            toggle_devtools,
+            bridge_echo,
+            take_pending_deep_link,
+            register_url_scheme,
+            get_kiosk_status,
+            set_kiosk_mode
+        ]))
         .setup(|app| {
             info!("Application setup complete");
             
-            // Set default API base URL
             let state: State<AppState> = app.state();
-            *state.api_base_url.lock().unwrap() = "http://localhost:8080".to_string();
+
+            match acquire_data_dir_lock(app.handle()) {
+                Ok(()) => {}
+                Err(e @ LockError::Held { .. }) => {
+                    // Two instances writing one data dir corrupt it
+                    error!("{}", e);
+                    let handle = app.handle().clone();
+                    app.dialog()
+                        .message(format!("{}\n\nClose the other StratMaster instance and try again.", e))
+                        .title("StratMaster is already running")
+                        .kind(MessageDialogKind::Error)
+                        .show(move |_| handle.exit(1));
+                    return Ok(());
+                }
+                Err(e) => error!("{}", e),
+            }
+
+            // Restore persisted settings
+            let config = match config_path(app.handle()) {
+                Ok(path) => AppConfig::load(&path)
+                    .and_then(|config| config.validate().map(|_| config))
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to load config, using defaults: {}", e);
+                        AppConfig::default()
+                    }),
+                Err(e) => {
+                    // Usually a relocated data dir on a drive that isn't
+                    // mounted; the user must know their data isn't gone
+                    error!("{}", e);
+                    app.dialog()
+                        .message(format!("{}\n\nStratMaster is running with default settings and won't save changes.", e))
+                        .title("Data directory unavailable")
+                        .kind(MessageDialogKind::Warning)
+                        .show(|_| {});
+                    AppConfig::default()
+                }
+            };
+            let mut config = config;
+            // config.json may have been edited or replaced while the app was
+            // closed; the policy file decides what is locked
+            let mut changed = false;
+            if let Some(policy) = managed_policy(app.handle()) {
+                changed = config.managed.as_ref() != Some(&policy);
+                let reset = provisioning::enforce(&mut config, &policy);
+                if !reset.is_empty() {
+                    warn!("Restored managed config fields: {}", reset.join(", "));
+                    changed = true;
+                }
+            }
+            if config.client_id.is_none() {
+                config.client_id = Some(generate_client_id());
+                changed = true;
+            }
+            if changed {
+                if let Err(e) = persist_config(app.handle(), &config) {
+                    warn!("{}", e);
+                }
+            }
+            apply_config(&config);
+            *state.api_base_url.lock().unwrap() = config.api_base_url.clone();
+            if let Some(environment) = config
+                .active_environment
+                .as_ref()
+                .and_then(|name| config.environments.iter().find(|env| &env.name == name))
+            {
+                *state.api_base_url.lock().unwrap() = environment.base_url.clone();
+            } else if let Some(active) = &config.active_backend {
+                if let Some((backend, _)) = known_backends(&config).into_iter().find(|(b, _)| &b.id == active) {
+                    *state.api_base_url.lock().unwrap() = backend.base_url;
+                    *state.backend_kind.lock().unwrap() = Some(backend.kind);
+                }
+            }
+            if let Some(addr) = config.metrics_listen_addr.clone() {
+                tauri::async_runtime::spawn(serve_metrics(app.handle().clone(), addr));
+            }
+            let auto_start_services = config.auto_start_services;
+            *state.config.lock().unwrap() = config;
+            remove_previous_data_dir(app.handle());
+            if let Err(e) = start_config_watcher(app.handle()) {
+                warn!("{}", e);
+            }
+
+            match app.path().app_log_dir() {
+                Ok(log_dir) => match logging::attach_log_file(&log_dir) {
+                    Ok(path) => info!("Writing logs to: {}", path.display()),
+                    Err(e) => warn!("Failed to open log file: {}", e),
+                },
+                Err(e) => warn!("Failed to get app log directory: {}", e),
+            }
+
+            if let Err(e) = build_tray(app.handle()) {
+                warn!("Failed to create tray icon: {}", e);
+            }
+            if state.config.lock().unwrap().always_on_top {
+                if let Err(e) = set_always_on_top_everywhere(app.handle(), true) {
+                    warn!("{}", e);
+                }
+            }
+            if state.config.lock().unwrap().ui_density == UiDensity::Minimal {
+                if let Some(window) = app.get_webview_window("main") {
+                    apply_minimal_window(&window);
+                }
+            }
+
+            // Detect the locale once so Accept-Language needn't wait on it later
+            let locale_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = locale_app.state::<AppState>();
+                let locale = effective_locale(&state).await;
+                debug!("Locale {} detected", locale.tag());
+                reset_http_client(&state);
+            });
+
+            #[cfg(target_os = "linux")]
+            watch_gnome_color_scheme(app.handle().clone());
+
+            // Warn but never block startup when elevated
+            if system::is_elevated().unwrap_or(false) {
+                warn!("{}", ELEVATED_WARNING);
+                let _ = app.emit("security-warning", ELEVATED_WARNING);
+            }
+
+            // Links opened while running arrive here; a link that launched the
+            // app is read once config (and so the environments) is loaded
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    handle_deep_link(&handle, url.as_str());
+                }
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    handle_deep_link(app.handle(), url.as_str());
+                }
+            }
+
+            state.startup.lock().unwrap().setup_complete = Some(process_start().elapsed());
+            tauri::async_runtime::spawn(watch_backend_readiness(app.handle().clone()));
+            tauri::async_runtime::spawn(run_monitoring_loop(app.handle().clone()));
+            tauri::async_runtime::spawn(run_maintenance_schedule(app.handle().clone()));
+            if auto_start_services {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = start_backend_services(handle.clone(), handle.state()).await {
+                        error!("Failed to start services at launch: {}", e);
+                    }
+                });
+            }
+            let power_app = app.handle().clone();
+            power::watch(move |event| {
+                tauri::async_runtime::spawn(handle_power_event(power_app.clone(), event));
+            });
+
+            // No window geometry is persisted yet, so size the main window for the current display
+            if let (Some(window), Some(monitor)) = (
+                app.get_webview_window("main"),
+                primary_or_first_monitor(app.handle()),
+            ) {
+                let (width, height) = recommended_window_size(&monitor);
+                let _ = window.set_size(tauri::LogicalSize::new(width, height));
+                let _ = window.center();
+            }
             
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Every way out (tray, last window, OS logout) ends here, so no
+            // service, lock or socket the app holds outlives it
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                release_resources(app);
+            }
+        });
 }
\ No newline at end of file
@@ -0,0 +1,192 @@
+// Maintenance
+// Keeps long-lived installs from growing without bound: old log files are
+// deleted by age and cache directories are trimmed to a size budget, oldest
+// entries first.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Smallest cache budget accepted; anything lower would evict the webview's
+/// working set on every pass.
+pub const MIN_CACHE_BYTES: u64 = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenancePolicy {
+    /// Log files last written longer ago than this are deleted.
+    pub max_log_age_days: u32,
+    /// Cache directories are trimmed to this total size.
+    pub max_cache_bytes: u64,
+    /// Also run once shortly after launch, not only on the daily schedule.
+    pub run_on_startup: bool,
+}
+
+impl Default for MaintenancePolicy {
+    fn default() -> Self {
+        Self {
+            max_log_age_days: 14,
+            max_cache_bytes: 512 * 1024 * 1024,
+            run_on_startup: true,
+        }
+    }
+}
+
+impl MaintenancePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_log_age_days == 0 {
+            return Err("Logs must be kept for at least one day".to_string());
+        }
+        if self.max_cache_bytes < MIN_CACHE_BYTES {
+            return Err(format!(
+                "Cache limit must be at least {} MB",
+                MIN_CACHE_BYTES / (1024 * 1024)
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Freed {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+struct CacheFile {
+    path: PathBuf,
+    len: u64,
+    last_used: SystemTime,
+}
+
+/// Deletes files directly in `dir` not modified within `max_age`, except `keep`.
+pub fn prune_older_than(dir: &Path, max_age: Duration, keep: &[PathBuf]) -> Freed {
+    let mut freed = Freed::default();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return freed;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if metadata.is_file() && age > max_age && !keep.contains(&path) && std::fs::remove_file(&path).is_ok() {
+            freed.files += 1;
+            freed.bytes += metadata.len();
+        }
+    }
+    freed
+}
+
+/// Removes the least recently used files across `roots` until their combined
+/// size fits `max_bytes`. A root may be a directory or a single file. "Used"
+/// is the later of access and modification time, since many filesystems are
+/// mounted without access-time updates. Files that cannot be removed (on
+/// Windows, those the webview has open) are left and still count.
+pub fn evict_lru(roots: &[PathBuf], max_bytes: u64) -> Freed {
+    let mut files = Vec::new();
+    for root in roots {
+        match std::fs::metadata(root) {
+            Ok(metadata) if metadata.is_file() => files.push(cache_file(root.clone(), &metadata)),
+            Ok(_) => collect_files(root, &mut files),
+            Err(_) => {}
+        }
+    }
+    let mut total: u64 = files.iter().map(|file| file.len).sum();
+    files.sort_by_key(|file| file.last_used);
+
+    let mut freed = Freed::default();
+    for file in files {
+        if total <= max_bytes {
+            break;
+        }
+        if std::fs::remove_file(&file.path).is_ok() {
+            total -= file.len;
+            freed.files += 1;
+            freed.bytes += file.len;
+        }
+    }
+    freed
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<CacheFile>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(&entry.path(), files);
+        } else if metadata.is_file() {
+            files.push(cache_file(entry.path(), &metadata));
+        }
+    }
+}
+
+fn cache_file(path: PathBuf, metadata: &std::fs::Metadata) -> CacheFile {
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let accessed = metadata.accessed().unwrap_or(modified);
+    CacheFile {
+        path,
+        len: metadata.len(),
+        last_used: modified.max(accessed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("stratmaster-maintenance-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn rejects_tiny_cache_limits() {
+        let policy = MaintenancePolicy { max_cache_bytes: 0, ..MaintenancePolicy::default() };
+        assert!(policy.validate().is_err());
+        let policy = MaintenancePolicy { max_cache_bytes: MIN_CACHE_BYTES, ..MaintenancePolicy::default() };
+        assert!(policy.validate().is_ok());
+    }
+
+    #[test]
+    fn evicts_oldest_first_across_file_and_directory_roots() {
+        let temp = TempDir::new("evict");
+        let dir = temp.0.join("NetworkCache");
+        std::fs::create_dir_all(&dir).unwrap();
+        let old = temp.0.join("Cache.db");
+        std::fs::write(&old, vec![0u8; 100]).unwrap();
+        let past = SystemTime::now() - Duration::from_secs(3600);
+        let times = std::fs::FileTimes::new().set_accessed(past).set_modified(past);
+        std::fs::File::options().write(true).open(&old).unwrap().set_times(times).unwrap();
+        let new = dir.join("entry");
+        std::fs::write(&new, vec![0u8; 100]).unwrap();
+
+        let freed = evict_lru(&[old.clone(), dir], 150);
+        assert_eq!(freed.files, 1);
+        assert_eq!(freed.bytes, 100);
+        assert!(!old.exists());
+        assert!(new.exists());
+    }
+}
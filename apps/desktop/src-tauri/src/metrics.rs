@@ -0,0 +1,116 @@
+// Health metrics
+// Renders the desktop's monitoring data in the Prometheus text exposition
+// format so it can be scraped like any other home-lab target.
+
+use std::fmt::Write;
+
+/// Latest health state of one monitored service.
+#[derive(Debug, Clone)]
+pub struct ServiceMetrics {
+    pub service: String,
+    pub up: bool,
+    pub latency_ms: Option<u64>,
+    pub checks_total: u64,
+    pub check_failures_total: u64,
+}
+
+/// A point-in-time view of every exported metric. Names are part of the
+/// scrape contract and must not change:
+///
+/// | metric                                    | type    | labels    |
+/// |-------------------------------------------|---------|-----------|
+/// | `stratmaster_service_up`                  | gauge   | `service` |
+/// | `stratmaster_service_latency_seconds`     | gauge   | `service` |
+/// | `stratmaster_health_checks_total`         | counter | `service` |
+/// | `stratmaster_health_check_failures_total` | counter | `service` |
+/// | `stratmaster_memory_total_bytes`          | gauge   |           |
+/// | `stratmaster_memory_available_bytes`      | gauge   |           |
+/// | `stratmaster_memory_pressure_ratio`       | gauge   |           |
+/// | `stratmaster_swap_total_bytes`            | gauge   |           |
+/// | `stratmaster_swap_used_bytes`             | gauge   |           |
+/// | `stratmaster_requests_total`              | counter |           |
+/// | `stratmaster_requests_inflight`           | gauge   |           |
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub services: Vec<ServiceMetrics>,
+    pub memory_total_bytes: Option<u64>,
+    pub memory_available_bytes: Option<u64>,
+    pub swap_total_bytes: Option<u64>,
+    pub swap_used_bytes: Option<u64>,
+    pub requests_total: u64,
+    pub requests_inflight: u64,
+}
+
+impl MetricsSnapshot {
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        header(&mut out, "stratmaster_service_up", "gauge", "Whether the service's last health check passed (1) or failed (0).");
+        for service in &self.services {
+            sample(&mut out, "stratmaster_service_up", Some(&service.service), if service.up { 1.0 } else { 0.0 });
+        }
+        header(&mut out, "stratmaster_service_latency_seconds", "gauge", "Duration of the service's last health check.");
+        for service in &self.services {
+            if let Some(latency_ms) = service.latency_ms {
+                sample(&mut out, "stratmaster_service_latency_seconds", Some(&service.service), latency_ms as f64 / 1000.0);
+            }
+        }
+        header(&mut out, "stratmaster_health_checks_total", "counter", "Health checks run since the app started.");
+        for service in &self.services {
+            sample(&mut out, "stratmaster_health_checks_total", Some(&service.service), service.checks_total as f64);
+        }
+        header(&mut out, "stratmaster_health_check_failures_total", "counter", "Failed health checks since the app started.");
+        for service in &self.services {
+            sample(&mut out, "stratmaster_health_check_failures_total", Some(&service.service), service.check_failures_total as f64);
+        }
+
+        if let (Some(total), Some(available)) = (self.memory_total_bytes, self.memory_available_bytes) {
+            header(&mut out, "stratmaster_memory_total_bytes", "gauge", "Total system memory.");
+            sample(&mut out, "stratmaster_memory_total_bytes", None, total as f64);
+            header(&mut out, "stratmaster_memory_available_bytes", "gauge", "System memory available to new processes.");
+            sample(&mut out, "stratmaster_memory_available_bytes", None, available as f64);
+            if total > 0 {
+                header(&mut out, "stratmaster_memory_pressure_ratio", "gauge", "Share of system memory in use (0-1).");
+                sample(&mut out, "stratmaster_memory_pressure_ratio", None, 1.0 - available as f64 / total as f64);
+            }
+        }
+
+        if let (Some(total), Some(used)) = (self.swap_total_bytes, self.swap_used_bytes) {
+            header(&mut out, "stratmaster_swap_total_bytes", "gauge", "Total swap space.");
+            sample(&mut out, "stratmaster_swap_total_bytes", None, total as f64);
+            header(&mut out, "stratmaster_swap_used_bytes", "gauge", "Swap space in use.");
+            sample(&mut out, "stratmaster_swap_used_bytes", None, used as f64);
+        }
+
+        header(&mut out, "stratmaster_requests_total", "counter", "Backend requests issued through the desktop since start.");
+        sample(&mut out, "stratmaster_requests_total", None, self.requests_total as f64);
+        header(&mut out, "stratmaster_requests_inflight", "gauge", "Backend requests currently in flight.");
+        sample(&mut out, "stratmaster_requests_inflight", None, self.requests_inflight as f64);
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, service: Option<&str>, value: f64) {
+    match service {
+        Some(service) => {
+            let _ = writeln!(out, "{}{{service=\"{}\"}} {}", name, escape_label(service), value);
+        }
+        None => {
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+    }
+}
+
+// Label values escape backslash, double quote and newline
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,138 @@
+// Backend model catalogue
+// Parses the model lists returned by the different backends we talk to,
+// estimates whether a model fits on this machine and what a request costs.
+
+use crate::ModelPricing;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Memory a model needs beyond its weights (KV cache, runtime buffers)
+const MODEL_MEMORY_OVERHEAD: f64 = 1.2;
+// Share of system RAM we are willing to hand to a model
+const USABLE_MEMORY_FRACTION: f64 = 0.8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    pub context_length: u32,
+    pub description: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub fit: Option<ModelFit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelFit {
+    pub required_mb: u64,
+    pub fits_memory: bool,
+    pub fits_gpu: bool,
+}
+
+/// Accepts the shapes we've seen in the wild:
+/// `{"data": [...]}` (OpenAI-compatible), `{"models": [...]}` (Ollama),
+/// a bare array of objects, or a bare array of model names.
+pub fn parse_model_list(value: &Value) -> Vec<ModelInfo> {
+    let items = match value {
+        Value::Array(items) => items.as_slice(),
+        Value::Object(map) => ["data", "models", "items"]
+            .iter()
+            .find_map(|key| map.get(*key).and_then(Value::as_array))
+            .map(Vec::as_slice)
+            .unwrap_or(&[]),
+        _ => &[],
+    };
+    items.iter().filter_map(parse_model).collect()
+}
+
+fn parse_model(item: &Value) -> Option<ModelInfo> {
+    if let Some(id) = item.as_str() {
+        return Some(ModelInfo {
+            id: id.to_string(),
+            context_length: 0,
+            description: None,
+            size_bytes: None,
+            fit: None,
+        });
+    }
+
+    let id = ["id", "name", "model"]
+        .iter()
+        .find_map(|key| item.get(*key).and_then(Value::as_str))?;
+    let context_length = ["context_length", "context_window", "max_context_length", "n_ctx"]
+        .iter()
+        .find_map(|key| item.get(*key).and_then(Value::as_u64))
+        .unwrap_or(0)
+        .min(u32::MAX as u64) as u32;
+    let description = item
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let size_bytes = ["size_bytes", "size"]
+        .iter()
+        .find_map(|key| item.get(*key).and_then(Value::as_u64));
+
+    Some(ModelInfo {
+        id: id.to_string(),
+        context_length,
+        description,
+        size_bytes,
+        fit: None,
+    })
+}
+
+/// Estimates whether a model of `size_bytes` fits in system memory and, when
+/// the usable VRAM is known, on the GPU.
+pub fn estimate_model_fit(size_bytes: u64, memory_total: u64, vram_mb: Option<u64>) -> ModelFit {
+    let required_mb = ((size_bytes as f64 * MODEL_MEMORY_OVERHEAD) / (1024.0 * 1024.0)).ceil() as u64;
+    let usable_memory_mb = ((memory_total as f64 * USABLE_MEMORY_FRACTION) / (1024.0 * 1024.0)) as u64;
+
+    ModelFit {
+        required_mb,
+        fits_memory: required_mb <= usable_memory_mb,
+        fits_gpu: vram_mb.map(|vram| required_mb <= vram).unwrap_or(false),
+    }
+}
+
+/// Wildcard entry in the pricing table used for models without their own price.
+pub const DEFAULT_PRICING_KEY: &str = "*";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub input_cost: f64,
+    pub output_cost: f64,
+    pub total_cost: f64,
+    pub currency: String,
+}
+
+impl CostEstimate {
+    /// Local backends cost nothing per request.
+    pub fn free() -> Self {
+        Self {
+            input_cost: 0.0,
+            output_cost: 0.0,
+            total_cost: 0.0,
+            currency: "USD".to_string(),
+        }
+    }
+}
+
+/// Prices a request from the table, falling back to the `"*"` entry.
+pub fn estimate_cost(
+    pricing: &HashMap<String, ModelPricing>,
+    model: &str,
+    tokens_in: u32,
+    tokens_out: u32,
+) -> Result<CostEstimate, String> {
+    let price = pricing
+        .get(model)
+        .or_else(|| pricing.get(DEFAULT_PRICING_KEY))
+        .ok_or_else(|| format!("No pricing configured for model {} and no default price", model))?;
+    let input_cost = tokens_in as f64 * price.input_per_million / 1_000_000.0;
+    let output_cost = tokens_out as f64 * price.output_per_million / 1_000_000.0;
+    Ok(CostEstimate {
+        input_cost,
+        output_cost,
+        total_cost: input_cost + output_cost,
+        currency: price.currency.clone(),
+    })
+}
@@ -0,0 +1,403 @@
+// Low-level network diagnostics
+// reqwest hides connection phases and certificate details, so these probes
+// drive DNS, TCP and TLS themselves.
+
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestTiming {
+    pub dns_ms: f64,
+    pub connect_ms: f64,
+    pub tls_ms: f64,
+    pub ttfb_ms: f64,
+    pub download_ms: f64,
+    pub total_ms: f64,
+    pub status: Option<u16>,
+    /// Measured over a pooled keep-alive connection, so setup phases read as zero.
+    pub reused_connection: bool,
+}
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn root_store() -> rustls::RootCertStore {
+    rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned())
+}
+
+fn crypto_provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+pub(crate) fn tls_client_config() -> rustls::ClientConfig {
+    rustls::ClientConfig::builder_with_provider(crypto_provider())
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions")
+        .with_root_certificates(root_store())
+        .with_no_client_auth()
+}
+
+/// Issues a single `GET` over a fresh connection and times every phase.
+pub async fn profile_get(
+    url: &url::Url,
+    headers: &[(String, String)],
+    user_agent: &str,
+    timeout: Duration,
+) -> Result<RequestTiming, String> {
+    tokio::time::timeout(timeout, profile_get_inner(url, headers, user_agent))
+        .await
+        .map_err(|_| format!("Request timed out after {}ms", timeout.as_millis()))?
+}
+
+async fn profile_get_inner(
+    url: &url::Url,
+    headers: &[(String, String)],
+    user_agent: &str,
+) -> Result<RequestTiming, String> {
+    let host = url.host_str().ok_or_else(|| format!("URL has no host: {}", url))?;
+    let lookup_host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| format!("Unknown port for {}", url))?;
+    let started = Instant::now();
+
+    let address = tokio::net::lookup_host((lookup_host, port))
+        .await
+        .map_err(|e| format!("DNS resolution for {} failed: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("{} did not resolve to any address", host))?;
+    let dns_done = Instant::now();
+
+    let tcp = TcpStream::connect(address)
+        .await
+        .map_err(|e| format!("Connecting to {} failed: {}", address, e))?;
+    let _ = tcp.set_nodelay(true);
+    let connected = Instant::now();
+
+    let mut stream: Box<dyn Stream> = if url.scheme() == "https" {
+        let server_name = rustls::pki_types::ServerName::try_from(lookup_host.to_string())
+            .map_err(|e| format!("Invalid TLS server name {}: {}", host, e))?;
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(tls_client_config()));
+        Box::new(
+            connector
+                .connect(server_name, tcp)
+                .await
+                .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))?,
+        )
+    } else {
+        Box::new(tcp)
+    };
+    let tls_done = Instant::now();
+
+    let host_header = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let mut request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: */*\r\nConnection: close\r\n",
+        path, host_header, user_agent
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+
+    let request_sent = Instant::now();
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("Sending request failed: {}", e))?;
+
+    let mut first = [0u8; 8192];
+    let read = stream
+        .read(&mut first)
+        .await
+        .map_err(|e| format!("Reading response failed: {}", e))?;
+    let first_byte = Instant::now();
+    let status = std::str::from_utf8(&first[..read])
+        .ok()
+        .and_then(|head| head.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok());
+
+    // Drain the rest; `Connection: close` makes the server end the stream
+    let mut rest = Vec::new();
+    let _ = stream.read_to_end(&mut rest).await;
+    let finished = Instant::now();
+
+    Ok(RequestTiming {
+        dns_ms: millis(dns_done - started),
+        connect_ms: millis(connected - dns_done),
+        tls_ms: millis(tls_done - connected),
+        ttfb_ms: millis(first_byte - request_sent),
+        download_ms: millis(finished - first_byte),
+        total_ms: millis(finished - started),
+        status,
+        reused_connection: false,
+    })
+}
+
+// Certificate inspection
+const CERT_EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsCertInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// RFC 3339 timestamps.
+    pub not_before: String,
+    pub not_after: String,
+    pub san: Vec<String>,
+    /// Colon-separated uppercase hex, as shown by browsers and `openssl x509 -fingerprint`.
+    pub fingerprint_sha256: String,
+    /// Whether the chain verifies against the bundled web PKI roots for this host.
+    pub trusted: bool,
+    pub trust_error: Option<String>,
+    pub expires_in_days: i64,
+    /// Set when the certificate is expired, not yet valid or close to expiry.
+    pub warning: Option<String>,
+}
+
+// Accepts any certificate so self-signed and otherwise untrusted chains can
+// still be read; trust is evaluated separately afterwards. Handshake
+// signatures are still checked.
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<rustls::crypto::CryptoProvider>);
+
+impl rustls::client::danger::ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Handshakes with `host:port` and describes the leaf certificate it presents.
+pub async fn inspect_certificate(host: &str, port: u16, timeout: Duration) -> Result<TlsCertInfo, String> {
+    tokio::time::timeout(timeout, inspect_certificate_inner(host, port))
+        .await
+        .map_err(|_| format!("TLS handshake with {}:{} timed out", host, port))?
+}
+
+async fn inspect_certificate_inner(host: &str, port: u16) -> Result<TlsCertInfo, String> {
+    use rustls::client::danger::ServerCertVerifier;
+
+    let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+        .map_err(|e| format!("Invalid TLS server name {}: {}", host, e))?;
+    let config = rustls::ClientConfig::builder_with_provider(crypto_provider())
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(crypto_provider())))
+        .with_no_client_auth();
+
+    let tcp = TcpStream::connect((host, port))
+        .await
+        .map_err(|e| format!("Connecting to {}:{} failed: {}", host, port, e))?;
+    let tls = tokio_rustls::TlsConnector::from(Arc::new(config))
+        .connect(server_name.clone(), tcp)
+        .await
+        .map_err(|e| format!("TLS handshake with {}:{} failed: {}", host, port, e))?;
+    let chain: Vec<rustls::pki_types::CertificateDer<'static>> = tls
+        .get_ref()
+        .1
+        .peer_certificates()
+        .map(|certs| certs.iter().map(|cert| cert.clone().into_owned()).collect())
+        .unwrap_or_default();
+    let (leaf, intermediates) = chain
+        .split_first()
+        .ok_or_else(|| format!("{}:{} presented no certificate", host, port))?;
+
+    let verifier = rustls::client::WebPkiServerVerifier::builder_with_provider(Arc::new(root_store()), crypto_provider())
+        .build()
+        .map_err(|e| format!("Failed to build certificate verifier: {}", e))?;
+    let trust_error = verifier
+        .verify_server_cert(leaf, intermediates, &server_name, &[], rustls::pki_types::UnixTime::now())
+        .err()
+        .map(|e| e.to_string());
+
+    let (_, cert) = x509_parser::parse_x509_certificate(leaf.as_ref())
+        .map_err(|e| format!("Failed to parse certificate: {}", e))?;
+    let to_rfc3339 = |time: x509_parser::time::ASN1Time| {
+        chrono::DateTime::from_timestamp(time.timestamp(), 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default()
+    };
+    let san = cert
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|extension| {
+            extension
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    x509_parser::extensions::GeneralName::DNSName(dns) => Some(dns.to_string()),
+                    x509_parser::extensions::GeneralName::IPAddress(bytes) => ip_from_bytes(bytes),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let now = chrono::Utc::now().timestamp();
+    let not_before = cert.validity().not_before.timestamp();
+    let not_after = cert.validity().not_after.timestamp();
+    let expires_in_days = (not_after - now).div_euclid(86_400);
+    let warning = if now > not_after {
+        Some(format!("Certificate expired {} day(s) ago", -expires_in_days))
+    } else if now < not_before {
+        Some("Certificate is not valid yet".to_string())
+    } else if expires_in_days <= CERT_EXPIRY_WARNING_DAYS {
+        Some(format!("Certificate expires in {} day(s)", expires_in_days))
+    } else {
+        None
+    };
+
+    let digest = sha2::Sha256::digest(leaf.as_ref());
+    let fingerprint_sha256 = digest
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":");
+
+    Ok(TlsCertInfo {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        not_before: to_rfc3339(cert.validity().not_before),
+        not_after: to_rfc3339(cert.validity().not_after),
+        san,
+        fingerprint_sha256,
+        trusted: trust_error.is_none(),
+        trust_error,
+        expires_in_days,
+        warning,
+    })
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        4 => Some(std::net::Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).to_string()),
+        16 => Some(std::net::Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).to_string()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolveResult {
+    /// The name actually looked up; international names are punycode-encoded.
+    pub host: String,
+    /// IPv4 and IPv6 addresses, IPv4 first, without duplicates.
+    pub addresses: Vec<String>,
+    /// The first configured nameserver, when the platform exposes it.
+    pub resolver: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Resolves `host` through the system resolver (so `/etc/hosts`, search
+/// domains and VPN split DNS apply, exactly as for real requests). Both A and
+/// AAAA records are requested. Errors carry the resolver's own message.
+pub async fn resolve_host(host: &str, timeout: Duration) -> Result<ResolveResult, String> {
+    let trimmed = host.trim().trim_start_matches('[').trim_end_matches(']');
+    if trimmed.is_empty() {
+        return Err("Host name is empty".to_string());
+    }
+    // IDNA-normalises international names (e.g. bücher.example -> xn--bcher-kva.example)
+    let ascii = match url::Host::parse(trimmed).map_err(|e| format!("Invalid host name {}: {}", trimmed, e))? {
+        url::Host::Domain(domain) => domain,
+        url::Host::Ipv4(address) => address.to_string(),
+        url::Host::Ipv6(address) => address.to_string(),
+    };
+
+    let started = Instant::now();
+    let lookup = tokio::time::timeout(timeout, tokio::net::lookup_host((ascii.as_str(), 0)))
+        .await
+        .map_err(|_| format!("Resolving {} timed out after {}ms", ascii, timeout.as_millis()))?
+        .map_err(|e| format!("Resolving {} failed: {}", ascii, e))?;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    let mut addresses: Vec<std::net::IpAddr> = Vec::new();
+    for address in lookup.map(|socket| socket.ip()) {
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    if addresses.is_empty() {
+        return Err(format!("{} did not resolve to any address", ascii));
+    }
+    addresses.sort_by_key(|address| address.is_ipv6());
+
+    Ok(ResolveResult {
+        host: ascii,
+        addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        resolver: system_resolver(),
+        elapsed_ms,
+    })
+}
+
+#[cfg(unix)]
+fn system_resolver() -> Option<String> {
+    let contents = std::fs::read_to_string("/etc/resolv.conf").ok()?;
+    contents.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next() == Some("nameserver")).then(|| fields.next().map(str::to_string))?
+    })
+}
+
+#[cfg(windows)]
+fn system_resolver() -> Option<String> {
+    let output = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "(Get-DnsClientServerAddress | Where-Object { $_.ServerAddresses } | Select-Object -First 1).ServerAddresses[0]",
+        ])
+        .output()
+        .ok()?;
+    let resolver = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !resolver.is_empty()).then_some(resolver)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn system_resolver() -> Option<String> {
+    None
+}
@@ -0,0 +1,277 @@
+// Power state
+// Reports system sleep and wake: logind's PrepareForSleep signal on Linux,
+// NSWorkspace's will-sleep and did-wake notifications on macOS and a
+// suspend/resume notification on Windows. Everywhere (and whenever the
+// native hook is unavailable) a watchdog also spots the wall clock jumping
+// past a monotonic sleep, which catches wake-ups but cannot announce sleep.
+// Also reads the OS energy mode so background work can back off.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    Sleep,
+    Wake,
+}
+
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(5);
+// More wall-clock time than this beyond the interval means we were suspended
+const WATCHDOG_SLACK: Duration = Duration::from_secs(30);
+// A native wake and the watchdog's can both fire for one resume
+const WAKE_DEDUP_WINDOW: Duration = Duration::from_secs(60);
+
+type Handler = Arc<dyn Fn(PowerEvent) + Send + Sync>;
+
+struct Dispatcher {
+    handler: Handler,
+    sleeping: bool,
+    last_wake: Option<Instant>,
+}
+
+static DISPATCHER: OnceLock<Mutex<Dispatcher>> = OnceLock::new();
+
+fn dispatch(event: PowerEvent) {
+    let Some(dispatcher) = DISPATCHER.get() else {
+        return;
+    };
+    let handler = {
+        let mut dispatcher = dispatcher.lock().unwrap();
+        match event {
+            PowerEvent::Sleep if dispatcher.sleeping => return,
+            PowerEvent::Sleep => dispatcher.sleeping = true,
+            PowerEvent::Wake => {
+                let recent = dispatcher.last_wake.is_some_and(|at| at.elapsed() < WAKE_DEDUP_WINDOW);
+                if recent && !dispatcher.sleeping {
+                    return;
+                }
+                dispatcher.sleeping = false;
+                dispatcher.last_wake = Some(Instant::now());
+            }
+        }
+        dispatcher.handler.clone()
+    };
+    handler(event);
+}
+
+/// Calls `handler` on sleep and wake from background threads. Only the
+/// first call installs a handler.
+pub fn watch(handler: impl Fn(PowerEvent) + Send + Sync + 'static) {
+    let installed = DISPATCHER
+        .set(Mutex::new(Dispatcher {
+            handler: Arc::new(handler),
+            sleeping: false,
+            last_wake: None,
+        }))
+        .is_ok();
+    if !installed {
+        return;
+    }
+    watch_native();
+    std::thread::spawn(run_watchdog);
+}
+
+fn run_watchdog() {
+    let mut last = SystemTime::now();
+    loop {
+        // Monotonic sleep: it stops counting while the machine is suspended
+        std::thread::sleep(WATCHDOG_INTERVAL);
+        let now = SystemTime::now();
+        let elapsed = now.duration_since(last).unwrap_or_default();
+        if elapsed > WATCHDOG_INTERVAL + WATCHDOG_SLACK {
+            dispatch(PowerEvent::Wake);
+        }
+        last = now;
+    }
+}
+
+// `gdbus monitor` prints "... PrepareForSleep (true,)" before suspend and
+// "(false,)" after resume
+#[cfg(target_os = "linux")]
+fn watch_native() {
+    use std::io::BufRead;
+
+    let child = std::process::Command::new("gdbus")
+        .args([
+            "monitor",
+            "--system",
+            "--dest",
+            "org.freedesktop.login1",
+            "--object-path",
+            "/org/freedesktop/login1",
+        ])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        log::debug!("gdbus unavailable; sleep is detected on wake only");
+        return;
+    };
+    let Some(stdout) = child.stdout.take() else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines().map_while(Result::ok) {
+            if !line.contains("PrepareForSleep") {
+                continue;
+            }
+            if line.contains("(true") {
+                dispatch(PowerEvent::Sleep);
+            } else if line.contains("(false") {
+                dispatch(PowerEvent::Wake);
+            }
+        }
+        let _ = child.wait();
+    });
+}
+
+#[cfg(target_os = "windows")]
+fn watch_native() {
+    use windows_sys::Win32::System::Power::{
+        PowerRegisterSuspendResumeNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS, HPOWERNOTIFY,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        DEVICE_NOTIFY_CALLBACK, PBT_APMRESUMEAUTOMATIC, PBT_APMRESUMESUSPEND, PBT_APMSUSPEND,
+    };
+
+    unsafe extern "system" fn on_power_change(
+        _context: *const std::ffi::c_void,
+        kind: u32,
+        _setting: *const std::ffi::c_void,
+    ) -> u32 {
+        match kind {
+            PBT_APMSUSPEND => dispatch(PowerEvent::Sleep),
+            PBT_APMRESUMEAUTOMATIC | PBT_APMRESUMESUSPEND => dispatch(PowerEvent::Wake),
+            _ => {}
+        }
+        0
+    }
+
+    // Registered for the lifetime of the process, so the parameters are leaked
+    let parameters = Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+        Callback: Some(on_power_change),
+        Context: std::ptr::null_mut(),
+    }));
+    let mut registration: HPOWERNOTIFY = unsafe { std::mem::zeroed() };
+    let status = unsafe {
+        PowerRegisterSuspendResumeNotification(
+            DEVICE_NOTIFY_CALLBACK,
+            parameters as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS as _,
+            &mut registration,
+        )
+    };
+    if status != 0 {
+        log::warn!("Failed to register for power notifications (error {}); sleep is detected on wake only", status);
+    }
+}
+
+// NSWorkspace posts both on the main thread, whose run loop Tauri drives
+#[cfg(target_os = "macos")]
+fn watch_native() {
+    use block2::RcBlock;
+    use objc2_app_kit::{NSWorkspace, NSWorkspaceDidWakeNotification, NSWorkspaceWillSleepNotification};
+    use objc2_foundation::{NSNotification, NSNotificationName};
+    use std::ptr::NonNull;
+
+    let observe = |name: &NSNotificationName, event: PowerEvent| {
+        let block = RcBlock::new(move |_: NonNull<NSNotification>| dispatch(event));
+        let center = unsafe { NSWorkspace::sharedWorkspace().notificationCenter() };
+        let observer = unsafe { center.addObserverForName_object_queue_usingBlock(Some(name), None, None, &block) };
+        // Observed for the lifetime of the process
+        std::mem::forget(observer);
+    };
+    observe(unsafe { NSWorkspaceWillSleepNotification }, PowerEvent::Sleep);
+    observe(unsafe { NSWorkspaceDidWakeNotification }, PowerEvent::Wake);
+}
+
+// No native hook; the watchdog covers wake
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn watch_native() {}
+
+// Energy mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerMode {
+    #[default]
+    Normal,
+    LowPower,
+    PerformanceBoost,
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `pmset -g` lists `lowpowermode 1` while Low Power Mode is on; newer
+/// releases also report `powermode 2` for High Power Mode.
+#[cfg(target_os = "macos")]
+pub fn power_mode() -> Result<PowerMode, String> {
+    let output = command_stdout("pmset", &["-g"]).ok_or_else(|| "Failed to run pmset".to_string())?;
+    let setting = |name: &str| {
+        output.lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            (fields.next() == Some(name)).then(|| fields.next().unwrap_or_default().to_string())
+        })
+    };
+    Ok(match (setting("lowpowermode").as_deref(), setting("powermode").as_deref()) {
+        (Some("1"), _) | (_, Some("1")) => PowerMode::LowPower,
+        (_, Some("2")) => PowerMode::PerformanceBoost,
+        _ => PowerMode::Normal,
+    })
+}
+
+// Well-known GUIDs of the built-in plans; OEM plans read as Normal
+#[cfg(target_os = "windows")]
+const POWER_SAVER_SCHEME: &str = "a1841308-3541-4fab-bc81-f71556f20b4a";
+#[cfg(target_os = "windows")]
+const PERFORMANCE_SCHEMES: &[&str] = &[
+    "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c",
+    "e9a42b02-d5df-448d-aa00-03f14749eb61",
+];
+
+#[cfg(target_os = "windows")]
+pub fn power_mode() -> Result<PowerMode, String> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // Battery saver is separate from the plan and wins over it
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } != 0 && status.SystemStatusFlag == 1 {
+        return Ok(PowerMode::LowPower);
+    }
+    let output = command_stdout("powercfg", &["/getactivescheme"])
+        .ok_or_else(|| "Failed to query the active power plan".to_string())?
+        .to_lowercase();
+    Ok(if output.contains(POWER_SAVER_SCHEME) {
+        PowerMode::LowPower
+    } else if PERFORMANCE_SCHEMES.iter().any(|scheme| output.contains(scheme)) {
+        PowerMode::PerformanceBoost
+    } else {
+        PowerMode::Normal
+    })
+}
+
+/// power-profiles-daemon (and tuned's compatible service); without either
+/// the mode is Normal.
+#[cfg(target_os = "linux")]
+pub fn power_mode() -> Result<PowerMode, String> {
+    let Some(profile) = command_stdout("powerprofilesctl", &["get"]) else {
+        return Ok(PowerMode::Normal);
+    };
+    Ok(match profile.trim() {
+        "power-saver" => PowerMode::LowPower,
+        "performance" => PowerMode::PerformanceBoost,
+        _ => PowerMode::Normal,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn power_mode() -> Result<PowerMode, String> {
+    Ok(PowerMode::Normal)
+}
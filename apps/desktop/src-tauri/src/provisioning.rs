@@ -0,0 +1,243 @@
+// Managed configuration
+// Admins distribute a config signed with an Ed25519 key whose public half is
+// pinned into the build (STRATMASTER_ADMIN_PUBLIC_KEY, base64). The signed
+// document names the fields it locks; those keep their signed values however
+// the config is edited afterwards.
+//
+// The locks come from a policy file, not from config.json, which the user can
+// edit or delete while the app is closed: an admin-installed system-wide file
+// when there is one, otherwise the read-only copy written on import.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// The import-time copy of the signed config, kept read-only in the data dir.
+pub const POLICY_FILE_NAME: &str = "managed.json";
+
+/// Base64 Ed25519 public key accepted for signed configs; unset disables import.
+pub const ADMIN_PUBLIC_KEY: Option<&str> = option_env!("STRATMASTER_ADMIN_PUBLIC_KEY");
+
+/// Per-install fields a signed config never sets.
+const UNMANAGED_FIELDS: &[&str] = &["managed", "client_id"];
+
+/// The signed payload: UTF-8 JSON of this shape, signed byte for byte.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedConfig {
+    /// `AppConfig` fields to apply; fields left out keep their local values.
+    pub config: Map<String, Value>,
+    #[serde(default)]
+    pub locked_fields: Vec<String>,
+}
+
+/// Kept in the config so the signature can be re-checked on every load.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagedConfig {
+    pub payload: String,
+    /// Base64 Ed25519 signature over `payload`.
+    pub signature: String,
+    /// Unset in a system policy file, which is the exported payload and signature as-is.
+    #[serde(default)]
+    pub imported_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedStatus {
+    pub managed: bool,
+    pub locked_fields: Vec<String>,
+    pub imported_ms: Option<u64>,
+}
+
+fn decode_key<const N: usize>(encoded: &str, what: &str) -> Result<[u8; N], String> {
+    BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| <[u8; N]>::try_from(bytes).ok())
+        .ok_or_else(|| format!("Invalid {}: expected {} base64-encoded bytes", what, N))
+}
+
+/// Where admins install a machine-wide policy; users can't write here.
+pub fn system_policy_path() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join("StratMaster").join(POLICY_FILE_NAME))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from("/Library/Application Support/StratMaster").join(POLICY_FILE_NAME))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        Some(PathBuf::from("/etc/stratmaster").join(POLICY_FILE_NAME))
+    }
+}
+
+/// The policy in `path`, `None` when there is no file. A file that is
+/// unreadable or whose signature doesn't verify is an error.
+pub fn read_policy(path: &Path) -> Result<Option<ManagedConfig>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read managed policy {}: {}", path.display(), e)),
+    };
+    let policy: ManagedConfig =
+        serde_json::from_str(&contents).map_err(|e| format!("Managed policy {} is not valid: {}", path.display(), e))?;
+    verify(&policy.payload, &policy.signature).map_err(|e| format!("Managed policy {}: {}", path.display(), e))?;
+    Ok(Some(policy))
+}
+
+/// Writes `policy` to `path` and marks the file read-only.
+pub fn write_policy(path: &Path, policy: &ManagedConfig) -> std::io::Result<()> {
+    if let Ok(metadata) = std::fs::metadata(path) {
+        // Replacing our own earlier copy
+        let mut permissions = metadata.permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(path, permissions)?;
+    }
+    let contents = serde_json::to_string_pretty(policy).map_err(std::io::Error::other)?;
+    std::fs::write(path, contents)?;
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(path, permissions)
+}
+
+/// Checks `signature` against the pinned admin key and parses the payload.
+pub fn verify(payload: &str, signature: &str) -> Result<SignedConfig, String> {
+    let public_key = ADMIN_PUBLIC_KEY.ok_or_else(|| "This build has no admin key; signed configs can't be imported".to_string())?;
+    let key = VerifyingKey::from_bytes(&decode_key::<32>(public_key, "admin public key")?)
+        .map_err(|e| format!("Invalid admin public key: {}", e))?;
+    let signature = Signature::from_bytes(&decode_key::<64>(signature, "signature")?);
+    key.verify(payload.as_bytes(), &signature)
+        .map_err(|_| "Config signature does not match; the config is unsigned or has been modified".to_string())?;
+
+    let mut signed: SignedConfig =
+        serde_json::from_str(payload).map_err(|e| format!("Signed config is not valid: {}", e))?;
+    for field in UNMANAGED_FIELDS {
+        signed.config.remove(*field);
+    }
+    if let Some(field) = signed.locked_fields.iter().find(|field| !signed.config.contains_key(field.as_str())) {
+        return Err(format!("Signed config locks {} but does not set it", field));
+    }
+    Ok(signed)
+}
+
+/// Admin side: signs `payload` with a base64 Ed25519 private key.
+pub fn sign(payload: &str, signing_key: &str) -> Result<String, String> {
+    let key = SigningKey::from_bytes(&decode_key::<32>(signing_key, "signing key")?);
+    Ok(BASE64.encode(key.sign(payload.as_bytes()).to_bytes()))
+}
+
+/// Builds the payload that `export_signed_config` signs from a config.
+pub fn payload_for(config: &crate::AppConfig, locked_fields: Vec<String>) -> Result<String, String> {
+    let Value::Object(mut fields) = serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {}", e))? else {
+        return Err("Config did not serialize to an object".to_string());
+    };
+    for field in UNMANAGED_FIELDS {
+        fields.remove(*field);
+    }
+    if let Some(field) = locked_fields.iter().find(|field| !fields.contains_key(field.as_str())) {
+        return Err(format!("Unknown config field {}", field));
+    }
+    serde_json::to_string_pretty(&SignedConfig { config: fields, locked_fields })
+        .map_err(|e| format!("Failed to serialize signed config: {}", e))
+}
+
+/// Overlays the signed fields on `base`, keeping everything else local.
+pub fn apply(base: &crate::AppConfig, signed: &SignedConfig, managed: ManagedConfig) -> Result<crate::AppConfig, String> {
+    let mut value = serde_json::to_value(base).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    if let Value::Object(fields) = &mut value {
+        fields.extend(signed.config.clone());
+    }
+    let mut config: crate::AppConfig =
+        serde_json::from_value(value).map_err(|e| format!("Signed config is not a valid config: {}", e))?;
+    config.managed = Some(managed);
+    Ok(config)
+}
+
+pub fn status(managed: Option<&ManagedConfig>) -> ManagedStatus {
+    let signed = managed.and_then(|managed| verify(&managed.payload, &managed.signature).ok());
+    ManagedStatus {
+        managed: signed.is_some(),
+        locked_fields: signed.map(|signed| signed.locked_fields).unwrap_or_default(),
+        imported_ms: managed.map(|managed| managed.imported_ms),
+    }
+}
+
+/// Resets locked fields that `config` changed to their signed values, under
+/// the signature of `managed`, and returns their names. A record whose
+/// signature no longer verifies locks nothing.
+pub fn enforce(config: &mut crate::AppConfig, managed: &ManagedConfig) -> Vec<String> {
+    let Ok(signed) = verify(&managed.payload, &managed.signature) else {
+        log::warn!("Managed config signature no longer verifies; ignoring its locks");
+        return Vec::new();
+    };
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(&*config) else {
+        return Vec::new();
+    };
+    let mut reset = Vec::new();
+    for field in &signed.locked_fields {
+        let locked = &signed.config[field.as_str()];
+        if fields.get(field.as_str()) != Some(locked) {
+            fields.insert(field.clone(), locked.clone());
+            reset.push(field.clone());
+        }
+    }
+    if !reset.is_empty() || config.managed.as_ref() != Some(managed) {
+        fields.insert("managed".to_string(), serde_json::to_value(managed).unwrap_or(Value::Null));
+        if let Ok(enforced) = serde_json::from_value(Value::Object(fields)) {
+            *config = enforced;
+        }
+    }
+    reset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("stratmaster-policy-{}-{}.json", name, std::process::id()))
+    }
+
+    fn remove(path: &Path) {
+        if let Ok(metadata) = std::fs::metadata(path) {
+            let mut permissions = metadata.permissions();
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            let _ = std::fs::set_permissions(path, permissions);
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn missing_policy_is_not_an_error() {
+        assert_eq!(read_policy(&temp_path("missing")), Ok(None));
+    }
+
+    #[test]
+    fn unsigned_policy_is_rejected() {
+        let path = temp_path("unsigned");
+        let policy = ManagedConfig { payload: "{}".to_string(), signature: "AAAA".to_string(), imported_ms: 0 };
+        write_policy(&path, &policy).unwrap();
+        let result = read_policy(&path);
+        remove(&path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn written_policy_is_read_only_and_can_be_replaced() {
+        let path = temp_path("readonly");
+        let policy = ManagedConfig { payload: "{}".to_string(), signature: String::new(), imported_ms: 1 };
+        write_policy(&path, &policy).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().permissions().readonly());
+        let replaced = write_policy(&path, &ManagedConfig { imported_ms: 2, ..policy });
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        remove(&path);
+        replaced.unwrap();
+        assert!(contents.contains("\"imported_ms\": 2"), "{}", contents);
+    }
+}
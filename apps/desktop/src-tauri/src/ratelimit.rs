@@ -0,0 +1,94 @@
+// Rate limits
+// Reads the throttling signals gateways send (`Retry-After`, the common
+// `X-RateLimit-*` headers and the IETF `RateLimit-*` draft) into a cooldown
+// per host, so the client backs off instead of collecting 429s.
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Cooldown after a 429 that doesn't say how long to wait.
+pub const DEFAULT_COOLDOWN_MS: u64 = 5_000;
+/// Longer waits are capped; a bogus reset header shouldn't stall the app for days.
+pub const MAX_COOLDOWN_MS: u64 = 60 * 60 * 1000;
+// Reset values above this are Unix timestamps rather than delays in seconds
+const EPOCH_SECONDS_THRESHOLD: u64 = 1_000_000_000;
+
+/// What happens to requests made during a cooldown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitMode {
+    /// Wait for the cooldown to end, up to the configured maximum wait.
+    #[default]
+    Queue,
+    /// Fail at once.
+    Reject,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitState {
+    pub host: String,
+    /// Unix time in milliseconds when requests may resume.
+    pub reset_ms: u64,
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// Status of the response that started the cooldown.
+    pub status: u16,
+}
+
+fn header<'a>(headers: &'a HeaderMap, names: &[&str]) -> Option<&'a str> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+}
+
+fn header_number(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    // The draft allows `10, 100;w=60`-style lists; the first entry is the one in force
+    header(headers, names)?.split([',', ';']).next()?.trim().parse().ok()
+}
+
+/// `Retry-After` as seconds or an HTTP date.
+fn retry_after_ms(headers: &HeaderMap, now_ms: u64) -> Option<u64> {
+    let value = header(headers, &["retry-after"])?;
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(now_ms.saturating_add(seconds.saturating_mul(1000)));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    u64::try_from(date.timestamp_millis()).ok()
+}
+
+/// Reset headers as a delay in seconds, or a Unix timestamp in seconds or milliseconds.
+fn reset_ms(headers: &HeaderMap, now_ms: u64) -> Option<u64> {
+    let reset = header_number(headers, &["ratelimit-reset", "x-ratelimit-reset", "x-rate-limit-reset"])?;
+    Some(if reset >= EPOCH_SECONDS_THRESHOLD * 1000 {
+        reset
+    } else if reset >= EPOCH_SECONDS_THRESHOLD {
+        reset.saturating_mul(1000)
+    } else {
+        now_ms.saturating_add(reset.saturating_mul(1000))
+    })
+}
+
+/// The cooldown a response asks for: after a 429 or 503 with `Retry-After`,
+/// or once the remaining quota reaches zero. `None` when requests may go on.
+pub fn from_response(host: &str, status: u16, headers: &HeaderMap, now_ms: u64) -> Option<RateLimitState> {
+    let limit = header_number(headers, &["ratelimit-limit", "x-ratelimit-limit", "x-rate-limit-limit"]);
+    let remaining = header_number(headers, &["ratelimit-remaining", "x-ratelimit-remaining", "x-rate-limit-remaining"]);
+    let retry_after = retry_after_ms(headers, now_ms);
+    let reset_ms = match status {
+        429 => retry_after
+            .or_else(|| reset_ms(headers, now_ms))
+            .unwrap_or(now_ms + DEFAULT_COOLDOWN_MS),
+        503 => retry_after?,
+        _ if remaining == Some(0) => reset_ms(headers, now_ms)?,
+        _ => return None,
+    };
+    (reset_ms > now_ms).then(|| RateLimitState {
+        host: host.to_string(),
+        reset_ms: reset_ms.min(now_ms + MAX_COOLDOWN_MS),
+        limit,
+        remaining,
+        status,
+    })
+}
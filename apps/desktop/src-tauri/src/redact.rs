@@ -0,0 +1,106 @@
+// Secret redaction
+// Used wherever configuration, logs or diagnostics leave the app (support
+// bundles, copied diagnostics) so credentials and tokens are never exported.
+
+use regex::Regex;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+const SENSITIVE_KEY_FRAGMENTS: &[&str] = &[
+    "password",
+    "passphrase",
+    "secret",
+    "token",
+    "api_key",
+    "api-key",
+    "apikey",
+    "authorization",
+    "credential",
+    "cookie",
+    "private_key",
+];
+
+/// Whether a field or header name looks like it holds a secret.
+pub fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEY_FRAGMENTS.iter().any(|fragment| key.contains(fragment))
+}
+
+fn secret_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        vec![
+            (
+                Regex::new(r"(?i)\b(bearer|basic)\s+[A-Za-z0-9\-._~+/]+=*").unwrap(),
+                "$1 [REDACTED]",
+            ),
+            (
+                Regex::new(r#"(?i)\b((?:api[_-]?key|access[_-]?token|token|secret|password|passphrase)["']?\s*[:=]\s*["']?)[^\s"'&,;]+"#)
+                    .unwrap(),
+                "${1}[REDACTED]",
+            ),
+            (Regex::new(r"\bsk-[A-Za-z0-9_\-]{16,}").unwrap(), "[REDACTED]"),
+        ]
+    })
+}
+
+/// Masks bearer tokens, `key=value` style secrets and API keys in free text.
+pub fn redact_text(text: &str) -> Cow<'_, str> {
+    let mut result = Cow::Borrowed(text);
+    for (pattern, replacement) in secret_patterns() {
+        let replaced = match pattern.replace_all(&result, *replacement) {
+            Cow::Owned(replaced) => Some(replaced),
+            Cow::Borrowed(_) => None,
+        };
+        if let Some(replaced) = replaced {
+            result = Cow::Owned(replaced);
+        }
+    }
+    result
+}
+
+/// Recursively replaces values of sensitive keys and scrubs secrets embedded in strings.
+pub fn redact_json(value: &mut Value) {
+    redact_json_by(value, &is_sensitive_key);
+}
+
+/// Whether `key` contains any of `patterns`, ignoring case.
+pub fn matches_any(key: &str, patterns: &[String]) -> bool {
+    let key = key.to_ascii_lowercase();
+    patterns
+        .iter()
+        .any(|pattern| !pattern.is_empty() && key.contains(&pattern.to_ascii_lowercase()))
+}
+
+/// [`redact_json`], additionally masking keys that contain any of `patterns`.
+pub fn redact_json_matching(value: &mut Value, patterns: &[String]) {
+    redact_json_by(value, &|key| is_sensitive_key(key) || matches_any(key, patterns));
+}
+
+fn redact_json_by(value: &mut Value, sensitive: &dyn Fn(&str) -> bool) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if sensitive(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json_by(field, sensitive);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_json_by(item, sensitive)),
+        Value::String(text) => {
+            let redacted = match redact_text(text) {
+                Cow::Owned(redacted) => Some(redacted),
+                Cow::Borrowed(_) => None,
+            };
+            if let Some(redacted) = redacted {
+                *text = redacted;
+            }
+        }
+        _ => {}
+    }
+}
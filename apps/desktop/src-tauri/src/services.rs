@@ -0,0 +1,269 @@
+// Local services
+// Processes the desktop starts on the user's machine (API, MCP servers, a
+// database) and the order they start in. A service starts only once every
+// service it depends on has passed its ready check.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Signal that a started service is actually usable, not just running.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ReadyCheck {
+    /// A `GET` returns a 2xx status.
+    HttpOk { url: String },
+    /// Something accepts TCP connections on this localhost port.
+    TcpOpen { port: u16 },
+    /// A line of stdout or stderr matches this regular expression.
+    LogMatch { pattern: String },
+    /// Fixed wait, for services with no better signal.
+    DelayMs { ms: u64 },
+}
+
+impl Default for ReadyCheck {
+    fn default() -> Self {
+        ReadyCheck::DelayMs { ms: 0 }
+    }
+}
+
+/// A service shown in the local status dashboard, whether or not the app
+/// started it. Healthy while `health_url` answers with a 2xx status.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusEndpoint {
+    pub name: String,
+    pub health_url: String,
+}
+
+/// The API and the bundled MCP servers on their default ports.
+pub fn default_status_endpoints() -> Vec<StatusEndpoint> {
+    [
+        ("api", "http://localhost:8080/healthz"),
+        ("research-mcp", "http://localhost:8081/health"),
+        ("knowledge-mcp", "http://localhost:8082/health"),
+        ("router-mcp", "http://localhost:8083/health"),
+    ]
+    .into_iter()
+    .map(|(name, health_url)| StatusEndpoint {
+        name: name.to_string(),
+        health_url: health_url.to_string(),
+    })
+    .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceDefinition {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Names of services that must be ready before this one starts.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub ready_check: ReadyCheck,
+    /// How long to wait for the ready check before giving up.
+    #[serde(default = "default_ready_timeout_ms")]
+    pub ready_timeout_ms: u64,
+    /// Restart the service when it stays unhealthy; see [`RecoveryPolicy`].
+    #[serde(default)]
+    pub auto_recover: bool,
+}
+
+fn default_ready_timeout_ms() -> u64 {
+    60_000
+}
+
+/// When and how often unhealthy services with `auto_recover` are restarted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecoveryPolicy {
+    /// Consecutive failed checks before a restart.
+    pub unhealthy_threshold: u32,
+    /// Restarts before giving up, until the service has stayed healthy for
+    /// `stable_after_ms`.
+    pub max_attempts: u32,
+    pub base_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub stable_after_ms: u64,
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        Self {
+            unhealthy_threshold: 3,
+            max_attempts: 3,
+            base_backoff_ms: 10_000,
+            max_backoff_ms: 300_000,
+            stable_after_ms: 600_000,
+        }
+    }
+}
+
+impl RecoveryPolicy {
+    /// Wait after restart number `attempt` (from 1) before the next may run.
+    pub fn backoff_ms(&self, attempt: u32) -> u64 {
+        self.base_backoff_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(20))
+            .min(self.max_backoff_ms)
+    }
+}
+
+/// Orders services so each comes after everything it depends on, keeping the
+/// configured order where dependencies allow. Unknown dependencies and cycles
+/// are reported by service name.
+pub fn start_order(services: &[ServiceDefinition]) -> Result<Vec<&ServiceDefinition>, String> {
+    let by_name: HashMap<&str, &ServiceDefinition> =
+        services.iter().map(|service| (service.name.as_str(), service)).collect();
+    for service in services {
+        if let Some(missing) = service.depends_on.iter().find(|dep| !by_name.contains_key(dep.as_str())) {
+            return Err(format!("Service {} depends on unknown service {}", service.name, missing));
+        }
+    }
+
+    let mut ordered: Vec<&ServiceDefinition> = Vec::with_capacity(services.len());
+    let mut visiting: Vec<&str> = Vec::new();
+    fn visit<'a>(
+        service: &'a ServiceDefinition,
+        by_name: &HashMap<&str, &'a ServiceDefinition>,
+        visiting: &mut Vec<&'a str>,
+        ordered: &mut Vec<&'a ServiceDefinition>,
+    ) -> Result<(), String> {
+        if ordered.iter().any(|done| done.name == service.name) {
+            return Ok(());
+        }
+        if visiting.contains(&service.name.as_str()) {
+            visiting.push(&service.name);
+            return Err(format!("Service dependency cycle: {}", visiting.join(" -> ")));
+        }
+        visiting.push(&service.name);
+        for dep in &service.depends_on {
+            visit(by_name[dep.as_str()], by_name, visiting, ordered)?;
+        }
+        visiting.pop();
+        ordered.push(service);
+        Ok(())
+    }
+    for service in services {
+        visit(service, &by_name, &mut visiting, &mut ordered)?;
+    }
+    Ok(ordered)
+}
+
+/// Per-service outcome of starting the configured services.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartResult {
+    pub started: Vec<String>,
+    pub failed: Vec<ServiceFailure>,
+    /// Already running; left alone.
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceFailure {
+    pub name: String,
+    pub reason: String,
+}
+
+/// One service's response time, probed through its ready check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HopLatency {
+    pub service: String,
+    /// `None` when the service was unreachable or its ready check has
+    /// nothing to probe (log match, delay).
+    pub latency_ms: Option<f64>,
+    pub healthy: bool,
+    pub depends_on: Vec<String>,
+}
+
+impl StartResult {
+    /// The first dependency of `service` that failed, directly or through its own dependencies.
+    pub fn failed_dependency<'a>(&self, service: &'a ServiceDefinition) -> Option<&'a str> {
+        service
+            .depends_on
+            .iter()
+            .find(|dep| self.failed.iter().any(|failure| &failure.name == *dep))
+            .map(String::as_str)
+    }
+}
+
+/// A problem that would stop a service from spawning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceIssue {
+    pub service: String,
+    /// The definition field at fault, e.g. `working_dir`.
+    pub field: String,
+    pub message: String,
+}
+
+/// Checks what can be checked without starting anything: the working
+/// directory, that the program can be found, and the env overrides.
+pub fn validate(service: &ServiceDefinition) -> Vec<ServiceIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |field: &str, message: String| {
+        issues.push(ServiceIssue {
+            service: service.name.clone(),
+            field: field.to_string(),
+            message,
+        })
+    };
+
+    if let Some(dir) = &service.working_dir {
+        if !dir.exists() {
+            issue("working_dir", format!("{} does not exist", dir.display()));
+        } else if !dir.is_dir() {
+            issue("working_dir", format!("{} is not a directory", dir.display()));
+        }
+    }
+
+    if service.command.trim().is_empty() {
+        issue("command", "No program is configured".to_string());
+    } else if find_program(&service.command, service.working_dir.as_deref()).is_none() {
+        let program = Path::new(&service.command);
+        if program.components().count() > 1 {
+            issue("command", format!("{} does not exist", service.command));
+        } else {
+            issue("command", format!("{} was not found on PATH", service.command));
+        }
+    }
+
+    for (name, value) in &service.env {
+        if name.is_empty() || name.contains('=') || name.contains('\0') {
+            issue("env", format!("{:?} is not a valid variable name", name));
+        } else if value.contains('\0') {
+            issue("env", format!("The value of {} contains a NUL byte", name));
+        }
+    }
+    issues
+}
+
+/// Where `program` would be spawned from. Bare names are searched on PATH
+/// (with PATHEXT on Windows); relative paths resolve against `working_dir`.
+pub fn find_program(program: &str, working_dir: Option<&Path>) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        let resolved = match working_dir {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path.to_path_buf(),
+        };
+        return resolved.is_file().then_some(resolved);
+    }
+
+    let extensions: Vec<String> = if cfg!(windows) && path.extension().is_none() {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+            .split(';')
+            .map(str::to_string)
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    let search_path = std::env::var_os("PATH")?;
+    std::env::split_paths(&search_path)
+        .flat_map(|dir| extensions.iter().map(move |ext| dir.join(format!("{}{}", program, ext))))
+        .find(|candidate| candidate.is_file())
+}
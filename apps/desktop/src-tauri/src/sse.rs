@@ -0,0 +1,107 @@
+// Server-sent events
+// Parses a `text/event-stream` body as it arrives. Chunks may split lines,
+// and even UTF-8 sequences, anywhere, so bytes are buffered until a full
+// line is in; an event is complete at the blank line that ends it.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SseEvent {
+    /// The `event:` field; `None` means the default `message` type.
+    pub event: Option<String>,
+    /// `data:` lines joined with newlines.
+    pub data: String,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    /// Feeds the next chunk of the body and returns the events it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+            if let Some(event) = self.line(line) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            // Events without data are dropped, as browsers do
+            let event = self.event.take();
+            if self.data.is_empty() {
+                return None;
+            }
+            return Some(SseEvent {
+                event,
+                data: std::mem::take(&mut self.data).join("\n"),
+                id: self.id.clone(),
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            // The last ID persists across events until the server changes it
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_split_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"event: tok").is_empty());
+        assert!(parser.push(b"en\r\ndata: hel").is_empty());
+        let events = parser.push(b"lo\r\n\r\ndata: next\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent { event: Some("token".into()), data: "hello".into(), id: None },
+                SseEvent { event: None, data: "next".into(), id: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_line_data_and_persistent_id() {
+        let mut parser = SseParser::default();
+        let events = parser.push(b"id: 7\ndata: a\ndata:b\n\n: keep-alive\n\ndata: c\n\n");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data, "a\nb");
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+        assert_eq!(events[1].id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn utf8_split_inside_a_character() {
+        let mut parser = SseParser::default();
+        let text = "data: caf\u{e9}\n\n".as_bytes();
+        let split = text.len() - 3;
+        assert!(parser.push(&text[..split]).is_empty());
+        assert_eq!(parser.push(&text[split..])[0].data, "caf\u{e9}");
+    }
+}
@@ -0,0 +1,131 @@
+// Support links
+// A small diagnostics summary packed into a `stratmaster://support?d=...`
+// link that can be pasted into a chat, where a support bundle would be too
+// heavy. Every string is scrubbed of secrets and home paths before encoding,
+// and the encoded form is size-capped by dropping the oldest errors.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// Longest code accepted or produced; chat tools mangle longer links.
+pub const MAX_SUPPORT_CODE_LEN: usize = 2048;
+const SUMMARY_VERSION: u32 = 1;
+const MAX_ERROR_LEN: usize = 200;
+// Expanding a code never allocates more than this, whatever it claims
+const MAX_DECODED_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsSummary {
+    pub version: u32,
+    pub created_ms: u64,
+    pub app_version: String,
+    pub commit: String,
+    pub platform: String,
+    pub arch: String,
+    pub os_release: Option<String>,
+    /// Backend kind or environment locality, never its URL.
+    pub backend: Option<String>,
+    pub service_health: BTreeMap<String, bool>,
+    /// Newest last.
+    pub recent_errors: Vec<String>,
+}
+
+impl DiagnosticsSummary {
+    pub fn new(app_version: String, commit: String) -> Self {
+        DiagnosticsSummary {
+            version: SUMMARY_VERSION,
+            created_ms: crate::unix_time_ms(),
+            app_version,
+            commit,
+            platform: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            os_release: None,
+            backend: None,
+            service_health: BTreeMap::new(),
+            recent_errors: Vec::new(),
+        }
+    }
+}
+
+/// Redacts secrets and replaces the home directory (which often holds the
+/// user name) with `~`, then truncates.
+pub fn scrub(text: &str) -> String {
+    let mut scrubbed = crate::redact::redact_text(text).into_owned();
+    if let Some(home) = dirs::home_dir().map(|home| home.to_string_lossy().to_string()) {
+        if !home.is_empty() {
+            scrubbed = scrubbed.replace(&home, "~");
+        }
+    }
+    if scrubbed.len() > MAX_ERROR_LEN {
+        let mut end = MAX_ERROR_LEN;
+        while !scrubbed.is_char_boundary(end) {
+            end -= 1;
+        }
+        scrubbed.truncate(end);
+        scrubbed.push('…');
+    }
+    scrubbed
+}
+
+fn encode_code(summary: &DiagnosticsSummary) -> Result<String, String> {
+    let json = serde_json::to_vec(summary).map_err(|e| format!("Failed to serialize diagnostics: {}", e))?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(&json)
+        .and_then(|_| encoder.finish())
+        .map(|compressed| URL_SAFE_NO_PAD.encode(compressed))
+        .map_err(|e| format!("Failed to compress diagnostics: {}", e))
+}
+
+/// Builds the link, scrubbing every field and dropping the oldest errors
+/// until it fits [`MAX_SUPPORT_CODE_LEN`].
+pub fn encode_link(mut summary: DiagnosticsSummary) -> Result<String, String> {
+    summary.os_release = summary.os_release.as_deref().map(scrub);
+    summary.backend = summary.backend.as_deref().map(scrub);
+    summary.recent_errors = summary.recent_errors.iter().map(|error| scrub(error)).collect();
+    loop {
+        let code = encode_code(&summary)?;
+        if code.len() <= MAX_SUPPORT_CODE_LEN {
+            return Ok(format!("{}://support?d={}", crate::deeplink::URL_SCHEME, code));
+        }
+        if summary.recent_errors.is_empty() {
+            return Err("Diagnostics summary is too large for a support link; create a support bundle instead".to_string());
+        }
+        summary.recent_errors.remove(0);
+    }
+}
+
+/// Accepts a full link or just its `d` code.
+pub fn decode(code_or_link: &str) -> Result<DiagnosticsSummary, String> {
+    let trimmed = code_or_link.trim();
+    let code = match url::Url::parse(trimmed) {
+        Ok(url) if url.scheme() == crate::deeplink::URL_SCHEME => url
+            .query_pairs()
+            .find(|(key, _)| key == "d")
+            .map(|(_, value)| value.into_owned())
+            .ok_or_else(|| "Support link has no diagnostics code".to_string())?,
+        _ => trimmed.to_string(),
+    };
+    if code.len() > MAX_SUPPORT_CODE_LEN {
+        return Err("Support code is too long".to_string());
+    }
+
+    let invalid = || "Not a StratMaster support code".to_string();
+    let compressed = URL_SAFE_NO_PAD.decode(code.as_bytes()).map_err(|_| invalid())?;
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_DECODED_BYTES)
+        .read_to_end(&mut json)
+        .map_err(|_| invalid())?;
+    let summary: DiagnosticsSummary = serde_json::from_slice(&json).map_err(|_| invalid())?;
+    if summary.version != SUMMARY_VERSION {
+        return Err(format!("Unsupported support code version {}", summary.version));
+    }
+    Ok(summary)
+}
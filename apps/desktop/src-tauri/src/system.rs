@@ -0,0 +1,2386 @@
+// System detection
+// Hardware and OS probing shared by the system-info and diagnostics commands.
+
+use serde::{Deserialize, Serialize};
+
+/// CPU instruction-set extensions relevant to picking an optimized backend build.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CpuFeatures {
+    pub sse4_2: bool,
+    pub avx: bool,
+    pub avx2: bool,
+    pub avx512: bool,
+    pub fma: bool,
+    pub f16c: bool,
+    pub neon: bool,
+    pub sve: bool,
+}
+
+impl CpuFeatures {
+    /// The most capable optimized build this CPU can run: "avx512", "avx2", "neon" or "baseline".
+    pub fn build_tier(&self) -> &'static str {
+        if self.avx512 {
+            "avx512"
+        } else if self.avx2 && self.fma {
+            "avx2"
+        } else if self.neon {
+            "neon"
+        } else {
+            "baseline"
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub fn detect_cpu_features() -> CpuFeatures {
+    CpuFeatures {
+        sse4_2: std::arch::is_x86_feature_detected!("sse4.2"),
+        avx: std::arch::is_x86_feature_detected!("avx"),
+        avx2: std::arch::is_x86_feature_detected!("avx2"),
+        avx512: std::arch::is_x86_feature_detected!("avx512f"),
+        fma: std::arch::is_x86_feature_detected!("fma"),
+        f16c: std::arch::is_x86_feature_detected!("f16c"),
+        neon: false,
+        sve: false,
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn detect_cpu_features() -> CpuFeatures {
+    let neon = std::arch::is_aarch64_feature_detected!("neon");
+    CpuFeatures {
+        // Fused multiply-add is part of Advanced SIMD on AArch64
+        fma: neon,
+        neon,
+        sve: std::arch::is_aarch64_feature_detected!("sve"),
+        ..CpuFeatures::default()
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn detect_cpu_features() -> CpuFeatures {
+    CpuFeatures::default()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesystemInfo {
+    pub fs_type: String,
+    pub is_network: bool,
+    pub is_removable: bool,
+    pub mount_point: Option<String>,
+}
+
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smbfs", "smb3", "afpfs", "webdav", "davfs", "fuse.sshfs", "sshfs", "9p", "afs",
+    "ceph", "glusterfs", "fuse.glusterfs", "lustre",
+];
+
+pub fn is_network_filesystem(fs_type: &str) -> bool {
+    NETWORK_FILESYSTEMS.contains(&fs_type.to_ascii_lowercase().as_str())
+}
+
+// The data dir may not have been created yet; inspect its closest existing ancestor.
+fn existing_ancestor(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    path.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .and_then(|ancestor| std::fs::canonicalize(ancestor).ok())
+}
+
+#[cfg(target_os = "linux")]
+pub fn filesystem_info(path: &std::path::Path) -> Result<FilesystemInfo, String> {
+    let path = existing_ancestor(path).ok_or_else(|| format!("{} does not exist", path.display()))?;
+    let mounts = std::fs::read_to_string("/proc/self/mounts")
+        .map_err(|e| format!("Failed to read mount table: {}", e))?;
+
+    // The longest mount point that prefixes the path is the one it lives on
+    let (device, mount_point, fs_type) = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((device.to_string(), mount_point, fs_type.to_string()))
+        })
+        .filter(|(_, mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(_, mount_point, _)| mount_point.len())
+        .ok_or_else(|| format!("No mount found for {}", path.display()))?;
+
+    Ok(FilesystemInfo {
+        is_network: is_network_filesystem(&fs_type),
+        is_removable: linux_block_device_removable(&device),
+        fs_type,
+        mount_point: Some(mount_point),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn linux_block_device_removable(device: &str) -> bool {
+    let Some(name) = device.strip_prefix("/dev/") else {
+        return false;
+    };
+    // /sys/class/block/sdb1 resolves into .../block/sdb/sdb1; `removable` lives on the whole disk
+    let Ok(sys_path) = std::fs::canonicalize(format!("/sys/class/block/{}", name)) else {
+        return false;
+    };
+    [Some(sys_path.as_path()), sys_path.parent()]
+        .into_iter()
+        .flatten()
+        .filter_map(|dir| std::fs::read_to_string(dir.join("removable")).ok())
+        .any(|value| value.trim() == "1")
+}
+
+#[cfg(target_os = "macos")]
+pub fn filesystem_info(path: &std::path::Path) -> Result<FilesystemInfo, String> {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = existing_ancestor(path).ok_or_else(|| format!("{} does not exist", path.display()))?;
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(format!("statfs failed: {}", std::io::Error::last_os_error()));
+    }
+
+    let fs_type = unsafe { CStr::from_ptr(stat.f_fstypename.as_ptr()) }
+        .to_string_lossy()
+        .to_string();
+    let mount_point = unsafe { CStr::from_ptr(stat.f_mntonname.as_ptr()) }
+        .to_string_lossy()
+        .to_string();
+    let is_local = stat.f_flags & (libc::MNT_LOCAL as u32) != 0;
+
+    // diskutil knows whether the backing device is removable/external media
+    let is_removable = std::process::Command::new("diskutil")
+        .args(["info", &mount_point])
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+                let line = line.trim();
+                (line.starts_with("Removable Media:") && line.ends_with("Removable"))
+                    || (line.starts_with("Device Location:") && line.ends_with("External"))
+            })
+        })
+        .unwrap_or(false);
+
+    Ok(FilesystemInfo {
+        is_network: !is_local || is_network_filesystem(&fs_type),
+        is_removable,
+        fs_type,
+        mount_point: Some(mount_point),
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn filesystem_info(path: &std::path::Path) -> Result<FilesystemInfo, String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetDriveTypeW, GetVolumeInformationW, GetVolumePathNameW, DRIVE_REMOTE, DRIVE_REMOVABLE,
+    };
+
+    let path = existing_ancestor(path).ok_or_else(|| format!("{} does not exist", path.display()))?;
+    let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut volume = [0u16; 261];
+    if unsafe { GetVolumePathNameW(wide_path.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+        return Err(format!("GetVolumePathNameW failed: {}", std::io::Error::last_os_error()));
+    }
+
+    let mut fs_name = [0u16; 261];
+    let ok = unsafe {
+        GetVolumeInformationW(
+            volume.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            fs_name.as_mut_ptr(),
+            fs_name.len() as u32,
+        )
+    };
+    let to_string = |buffer: &[u16]| {
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        String::from_utf16_lossy(&buffer[..len])
+    };
+    let fs_type = if ok != 0 { to_string(&fs_name) } else { "unknown".to_string() };
+    let drive_type = unsafe { GetDriveTypeW(volume.as_ptr()) };
+
+    Ok(FilesystemInfo {
+        is_network: drive_type == DRIVE_REMOTE || is_network_filesystem(&fs_type),
+        is_removable: drive_type == DRIVE_REMOVABLE,
+        fs_type,
+        mount_point: Some(to_string(&volume)),
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn filesystem_info(_path: &std::path::Path) -> Result<FilesystemInfo, String> {
+    Err("Filesystem detection is not supported on this platform".to_string())
+}
+
+/// Total size in bytes of all files under `path`; unreadable entries are skipped.
+pub fn directory_size(path: &std::path::Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => directory_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionReport {
+    pub dir: String,
+    pub readable: bool,
+    pub writable: bool,
+    pub issue: Option<String>,
+}
+
+// Removes the probe file however the check exits
+struct ProbeFile(std::path::PathBuf);
+
+impl Drop for ProbeFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Checks that the current user can list `dir` and create and delete a file
+/// in it. A missing directory is created first, as the app would on save.
+pub fn check_dir_permissions(dir: &std::path::Path) -> PermissionReport {
+    let mut report = PermissionReport {
+        dir: dir.display().to_string(),
+        readable: false,
+        writable: false,
+        issue: None,
+    };
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        report.issue = Some(format!("cannot be created: {}", e));
+        return report;
+    }
+
+    report.readable = std::fs::read_dir(dir).is_ok();
+    let probe = ProbeFile(dir.join(format!(".stratmaster-write-probe-{}", std::process::id())));
+    let write_result = std::fs::write(&probe.0, b"probe").and_then(|_| std::fs::remove_file(&probe.0));
+    report.writable = write_result.is_ok();
+
+    report.issue = match (report.readable, write_result) {
+        (true, Ok(())) => None,
+        (false, Ok(())) => Some("is not readable".to_string()),
+        (true, Err(e)) => Some(format!("is not writable: {}", e)),
+        (false, Err(e)) => Some(format!("is neither readable nor writable: {}", e)),
+    };
+    report
+}
+
+/// Free space in bytes available to this user on the volume holding `path`.
+#[cfg(unix)]
+pub fn available_space(path: &std::path::Path) -> Result<u64, String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| format!("Invalid path: {}", path.display()))?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return Err(format!("statvfs {} failed: {}", path.display(), std::io::Error::last_os_error()));
+    }
+    Ok(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(windows)]
+pub fn available_space(path: &std::path::Path) -> Result<u64, String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut()) };
+    if ok == 0 {
+        return Err(format!("GetDiskFreeSpaceExW {} failed: {}", path.display(), std::io::Error::last_os_error()));
+    }
+    Ok(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn available_space(_path: &std::path::Path) -> Result<u64, String> {
+    Err("Free space detection is not supported on this platform".to_string())
+}
+
+/// Removes everything under `path` (but not `path` itself), returning the
+/// number of entries that could not be removed, e.g. because they are in use.
+pub fn clear_directory(path: &std::path::Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            let path = entry.path();
+            let result = if path.is_dir() {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            };
+            result.is_err()
+        })
+        .count()
+}
+
+/// Installed font family names, deduplicated and sorted. Empty on machines
+/// without fonts (headless CI); enumeration reads every font file, so cache it.
+pub fn font_families() -> Vec<String> {
+    let mut database = fontdb::Database::new();
+    database.load_system_fonts();
+    let families: std::collections::BTreeSet<String> = database
+        .faces()
+        .filter_map(|face| face.families.first().map(|(name, _)| name.trim().to_string()))
+        .filter(|name| !name.is_empty() && !name.starts_with('.'))
+        .collect();
+    families.into_iter().collect()
+}
+
+// CPU temperature
+// Values outside this range are sensor glitches or unpopulated channels
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+const PLAUSIBLE_CELSIUS: std::ops::Range<f64> = 1.0..130.0;
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn hottest(readings: impl Iterator<Item = f64>) -> Option<f64> {
+    readings
+        .filter(|celsius| PLAUSIBLE_CELSIUS.contains(celsius))
+        .fold(None, |max: Option<f64>, celsius| Some(max.map_or(celsius, |max| max.max(celsius))))
+}
+
+/// Hottest CPU sensor in °C, or `None` when this machine exposes none.
+/// Cheap enough to call on a monitoring interval.
+#[cfg(target_os = "linux")]
+pub fn cpu_temperature() -> Result<Option<f64>, String> {
+    const CPU_ZONE_TYPES: &[&str] = &["x86_pkg_temp", "cpu-thermal", "cpu_thermal", "soc_thermal", "acpitz"];
+    const CPU_HWMON_NAMES: &[&str] = &["coretemp", "k10temp", "zenpower", "cpu_thermal"];
+    let read_millidegrees = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .map(|millidegrees| millidegrees / 1000.0)
+    };
+
+    // hwmon drivers report per-core/package sensors; thermal zones are the fallback
+    let hwmon = std::fs::read_dir("/sys/class/hwmon").into_iter().flatten().flatten().filter(|entry| {
+        std::fs::read_to_string(entry.path().join("name"))
+            .map(|name| CPU_HWMON_NAMES.contains(&name.trim()))
+            .unwrap_or(false)
+    });
+    let hwmon_readings = hwmon.flat_map(|entry| {
+        std::fs::read_dir(entry.path())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|input| {
+                let name = input.file_name().to_string_lossy().to_string();
+                name.starts_with("temp") && name.ends_with("_input")
+            })
+            .filter_map(|input| read_millidegrees(input.path()))
+            .collect::<Vec<_>>()
+    });
+    if let Some(celsius) = hottest(hwmon_readings) {
+        return Ok(Some(celsius));
+    }
+
+    let zones = std::fs::read_dir("/sys/class/thermal").into_iter().flatten().flatten().filter(|entry| {
+        std::fs::read_to_string(entry.path().join("type"))
+            .map(|zone_type| CPU_ZONE_TYPES.contains(&zone_type.trim()))
+            .unwrap_or(false)
+    });
+    Ok(hottest(zones.filter_map(|entry| read_millidegrees(entry.path().join("temp")))))
+}
+
+/// Reads the SMC directly: Intel Macs expose `TC0*` keys (sp78 fixed point),
+/// Apple Silicon `Tp*` keys (little-endian f32).
+#[cfg(target_os = "macos")]
+pub fn cpu_temperature() -> Result<Option<f64>, String> {
+    const KEYS: &[&[u8; 4]] = &[
+        b"TC0P", b"TC0D", b"TC0E", b"TC0F", b"Tp09", b"Tp0T", b"Tp01", b"Tp05", b"Tp0D", b"Tp0H",
+    ];
+    let Some(smc) = smc::Connection::open() else {
+        return Ok(None);
+    };
+    Ok(hottest(KEYS.iter().filter_map(|key| smc.read_temperature(key))))
+}
+
+#[cfg(target_os = "macos")]
+mod smc {
+    use std::ffi::{c_char, c_void};
+
+    const KERNEL_INDEX_SMC: u32 = 2;
+    const SMC_CMD_READ_BYTES: u8 = 5;
+    const SMC_CMD_READ_KEYINFO: u8 = 9;
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyDataVers {
+        major: u8,
+        minor: u8,
+        build: u8,
+        reserved: u8,
+        release: u16,
+    }
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyDataPLimit {
+        version: u16,
+        length: u16,
+        cpu_p_limit: u32,
+        gpu_p_limit: u32,
+        mem_p_limit: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyInfo {
+        data_size: u32,
+        data_type: u32,
+        data_attributes: u8,
+    }
+
+    // Mirrors SMCKeyData_t from the AppleSMC user client
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyData {
+        key: u32,
+        vers: KeyDataVers,
+        p_limit_data: KeyDataPLimit,
+        key_info: KeyInfo,
+        result: u8,
+        status: u8,
+        data8: u8,
+        data32: u32,
+        bytes: [u8; 32],
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+        fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+        fn IOServiceOpen(service: u32, owning_task: u32, kind: u32, connection: *mut u32) -> i32;
+        fn IOServiceClose(connection: u32) -> i32;
+        fn IOObjectRelease(object: u32) -> i32;
+        fn IOConnectCallStructMethod(
+            connection: u32,
+            selector: u32,
+            input: *const c_void,
+            input_size: usize,
+            output: *mut c_void,
+            output_size: *mut usize,
+        ) -> i32;
+    }
+
+    extern "C" {
+        static mach_task_self_: u32;
+    }
+
+    pub(super) struct Connection(u32);
+
+    impl Connection {
+        pub(super) fn open() -> Option<Self> {
+            unsafe {
+                let matching = IOServiceMatching(c"AppleSMC".as_ptr());
+                let service = IOServiceGetMatchingService(0, matching);
+                if service == 0 {
+                    return None;
+                }
+                let mut connection = 0;
+                let result = IOServiceOpen(service, mach_task_self_, 0, &mut connection);
+                IOObjectRelease(service);
+                (result == 0).then_some(Self(connection))
+            }
+        }
+
+        fn call(&self, input: &KeyData) -> Option<KeyData> {
+            let mut output = KeyData::default();
+            let mut output_size = std::mem::size_of::<KeyData>();
+            let result = unsafe {
+                IOConnectCallStructMethod(
+                    self.0,
+                    KERNEL_INDEX_SMC,
+                    input as *const KeyData as *const c_void,
+                    std::mem::size_of::<KeyData>(),
+                    &mut output as *mut KeyData as *mut c_void,
+                    &mut output_size,
+                )
+            };
+            (result == 0 && output.result == 0).then_some(output)
+        }
+
+        pub(super) fn read_temperature(&self, key: &[u8; 4]) -> Option<f64> {
+            let key = u32::from_be_bytes(*key);
+            let info = self.call(&KeyData {
+                key,
+                data8: SMC_CMD_READ_KEYINFO,
+                ..KeyData::default()
+            })?;
+            let value = self.call(&KeyData {
+                key,
+                key_info: info.key_info,
+                data8: SMC_CMD_READ_BYTES,
+                ..KeyData::default()
+            })?;
+
+            match &info.key_info.data_type.to_be_bytes() {
+                b"sp78" => Some(i16::from_be_bytes([value.bytes[0], value.bytes[1]]) as f64 / 256.0),
+                b"flt " => Some(f32::from_le_bytes([value.bytes[0], value.bytes[1], value.bytes[2], value.bytes[3]]) as f64),
+                _ => None,
+            }
+        }
+    }
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            unsafe {
+                IOServiceClose(self.0);
+            }
+        }
+    }
+}
+
+/// Queries LibreHardwareMonitor/OpenHardwareMonitor over WMI when one is
+/// running, else the ACPI thermal zone. Windows exposes no sensor API without
+/// those, so after a query finds nothing we stop spawning PowerShell.
+#[cfg(target_os = "windows")]
+pub fn cpu_temperature() -> Result<Option<f64>, String> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    static NO_SENSOR: AtomicBool = AtomicBool::new(false);
+    const QUERY: &str = "$ErrorActionPreference='SilentlyContinue';\
+        foreach ($ns in 'root/LibreHardwareMonitor','root/OpenHardwareMonitor') {\
+          $t = Get-CimInstance -Namespace $ns -ClassName Sensor | Where-Object { $_.SensorType -eq 'Temperature' -and $_.Name -like 'CPU*' };\
+          if ($t) { $t | ForEach-Object { $_.Value }; exit }\
+        };\
+        Get-CimInstance -Namespace root/wmi -ClassName MSAcpi_ThermalZoneTemperature | ForEach-Object { ($_.CurrentTemperature / 10) - 273.15 }";
+
+    if NO_SENSOR.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", QUERY])
+        .output()
+        .map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    let celsius = hottest(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().replace(',', ".").parse::<f64>().ok()),
+    );
+    if celsius.is_none() {
+        NO_SENSOR.store(true, Ordering::Relaxed);
+    }
+    Ok(celsius)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn cpu_temperature() -> Result<Option<f64>, String> {
+    Ok(None)
+}
+
+// Swap
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SwapInfo {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    /// Bytes per second read back from swap since the previous call. `None`
+    /// on the first call and where the OS exposes no paging counters.
+    pub swap_in_rate: Option<f64>,
+    /// Bytes per second written out to swap since the previous call.
+    pub swap_out_rate: Option<f64>,
+}
+
+// Cumulative bytes swapped in and out at the previous call, to derive rates
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn swap_rates(swapped_in: u64, swapped_out: u64) -> (Option<f64>, Option<f64>) {
+    use std::sync::Mutex;
+    use std::time::Instant;
+    static LAST_SAMPLE: Mutex<Option<(Instant, u64, u64)>> = Mutex::new(None);
+
+    let now = Instant::now();
+    let previous = LAST_SAMPLE.lock().unwrap().replace((now, swapped_in, swapped_out));
+    match previous {
+        Some((at, last_in, last_out)) if now > at => {
+            let seconds = now.duration_since(at).as_secs_f64();
+            (
+                Some(swapped_in.saturating_sub(last_in) as f64 / seconds),
+                Some(swapped_out.saturating_sub(last_out) as f64 / seconds),
+            )
+        }
+        _ => (None, None),
+    }
+}
+
+/// Reads `/proc/swaps` for capacity and the `pswpin`/`pswpout` page counters
+/// in `/proc/vmstat` for rates.
+#[cfg(target_os = "linux")]
+pub fn swap_info() -> Result<SwapInfo, String> {
+    let swaps = std::fs::read_to_string("/proc/swaps").map_err(|e| format!("Failed to read /proc/swaps: {}", e))?;
+    let (mut total_kib, mut used_kib) = (0u64, 0u64);
+    // Filename Type Size Used Priority, sizes in KiB
+    for line in swaps.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if let (Some(size), Some(used)) = (fields.get(2), fields.get(3)) {
+            total_kib += size.parse::<u64>().unwrap_or(0);
+            used_kib += used.parse::<u64>().unwrap_or(0);
+        }
+    }
+
+    let vmstat = std::fs::read_to_string("/proc/vmstat").unwrap_or_default();
+    let counter = |name: &str| {
+        vmstat.lines().find_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            (key == name).then(|| value.trim().parse::<u64>().ok())?
+        })
+    };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+    let (swap_in_rate, swap_out_rate) = match (counter("pswpin"), counter("pswpout")) {
+        (Some(pages_in), Some(pages_out)) => swap_rates(pages_in * page_size, pages_out * page_size),
+        _ => (None, None),
+    };
+
+    Ok(SwapInfo {
+        total_bytes: total_kib * 1024,
+        used_bytes: used_kib * 1024,
+        swap_in_rate,
+        swap_out_rate,
+    })
+}
+
+/// `sysctl vm.swapusage` for capacity (macOS grows swap files on demand, so
+/// the total moves) and the `Swapins`/`Swapouts` page counters of `vm_stat`.
+#[cfg(target_os = "macos")]
+pub fn swap_info() -> Result<SwapInfo, String> {
+    // "total = 2048.00M  used = 1126.25M  free = 921.75M  (encrypted)"
+    let usage = command_output("sysctl", &["-n", "vm.swapusage"])
+        .ok_or_else(|| "Failed to query vm.swapusage".to_string())?;
+    let field = |name: &str| -> u64 {
+        let Some(value) = usage
+            .split_whitespace()
+            .skip_while(|token| *token != name)
+            .nth(2)
+        else {
+            return 0;
+        };
+        let (number, unit) = value.split_at(value.len().saturating_sub(1));
+        let multiplier = match unit {
+            "K" => 1024.0,
+            "M" => 1024.0 * 1024.0,
+            "G" => 1024.0 * 1024.0 * 1024.0,
+            _ => 1.0,
+        };
+        (number.parse::<f64>().unwrap_or(0.0) * multiplier) as u64
+    };
+    let (total_bytes, used_bytes) = (field("total"), field("used"));
+
+    // "Mach Virtual Memory Statistics: (page size of 16384 bytes)" then "Swapins:  123."
+    let (swap_in_rate, swap_out_rate) = command_output("vm_stat", &[])
+        .and_then(|vm_stat| {
+            let page_size = vm_stat
+                .split("page size of ")
+                .nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                .and_then(|size| size.parse::<u64>().ok())?;
+            let counter = |name: &str| {
+                vm_stat.lines().find_map(|line| {
+                    let value = line.strip_prefix(name)?.trim().trim_end_matches('.');
+                    value.parse::<u64>().ok()
+                })
+            };
+            Some(swap_rates(counter("Swapins:")? * page_size, counter("Swapouts:")? * page_size))
+        })
+        .unwrap_or((None, None));
+
+    Ok(SwapInfo {
+        total_bytes,
+        used_bytes,
+        swap_in_rate,
+        swap_out_rate,
+    })
+}
+
+/// Sums page file allocation and usage; Windows has no cheap paging-rate
+/// counter outside PDH, so rates are left unset.
+#[cfg(target_os = "windows")]
+pub fn swap_info() -> Result<SwapInfo, String> {
+    // Both values are in MB
+    let output = command_output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-CimInstance Win32_PageFileUsage | ForEach-Object { \"$($_.AllocatedBaseSize) $($_.CurrentUsage)\" }",
+        ],
+    )
+    .ok_or_else(|| "Failed to query page file usage".to_string())?;
+    let (mut total_mb, mut used_mb) = (0u64, 0u64);
+    for line in output.lines() {
+        let mut fields = line.split_whitespace().map(|field| field.parse::<u64>().unwrap_or(0));
+        total_mb += fields.next().unwrap_or(0);
+        used_mb += fields.next().unwrap_or(0);
+    }
+    Ok(SwapInfo {
+        total_bytes: total_mb * 1024 * 1024,
+        used_bytes: used_mb * 1024 * 1024,
+        swap_in_rate: None,
+        swap_out_rate: None,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn swap_info() -> Result<SwapInfo, String> {
+    Err("Swap statistics are not supported on this platform".to_string())
+}
+
+/// Whether the host firewall would block inbound TCP connections to a port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirewallStatus {
+    /// `None` when no firewall could be queried (missing tool, insufficient privileges).
+    pub blocked: Option<bool>,
+    pub detail: String,
+}
+
+impl FirewallStatus {
+    fn known(blocked: bool, detail: impl Into<String>) -> Self {
+        Self { blocked: Some(blocked), detail: detail.into() }
+    }
+
+    fn unknown(detail: impl Into<String>) -> Self {
+        Self { blocked: None, detail: detail.into() }
+    }
+}
+
+// stdout of a successful run; `None` if the tool is missing or refused (often needs root)
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Checks ufw, then firewalld, then raw iptables; the first one that answers wins.
+#[cfg(target_os = "linux")]
+pub fn firewall_status(port: u16) -> FirewallStatus {
+    if let Some(output) = command_output("ufw", &["status", "verbose"]) {
+        if output.contains("Status: inactive") {
+            return FirewallStatus::known(false, "ufw is inactive");
+        }
+        let allowed = output.lines().any(|line| {
+            let mut fields = line.split_whitespace();
+            let target = fields.next().unwrap_or_default();
+            (target == port.to_string() || target == format!("{}/tcp", port)) && line.contains("ALLOW")
+        });
+        if allowed {
+            return FirewallStatus::known(false, format!("ufw allows port {}", port));
+        }
+        let default_deny = output
+            .lines()
+            .any(|line| line.starts_with("Default:") && (line.contains("deny (incoming)") || line.contains("reject (incoming)")));
+        return FirewallStatus::known(
+            default_deny,
+            if default_deny {
+                format!("ufw denies incoming connections and has no rule allowing port {}", port)
+            } else {
+                "ufw allows incoming connections by default".to_string()
+            },
+        );
+    }
+
+    if command_output("firewall-cmd", &["--state"]).is_some() {
+        let query = format!("--query-port={}/tcp", port);
+        // --query-port exits non-zero (and prints "no") when the port isn't open
+        let open = command_output("firewall-cmd", &[&query]).is_some();
+        return FirewallStatus::known(
+            !open,
+            if open {
+                format!("firewalld allows port {}/tcp", port)
+            } else {
+                format!("firewalld is running and port {}/tcp is not open in the active zone", port)
+            },
+        );
+    }
+
+    if let Some(output) = command_output("iptables", &["-S", "INPUT"]) {
+        let dport = format!("--dport {}", port);
+        for line in output.lines().filter(|line| line.contains(&dport)) {
+            if line.contains("-j ACCEPT") {
+                return FirewallStatus::known(false, format!("iptables accepts port {}", port));
+            }
+            if line.contains("-j DROP") || line.contains("-j REJECT") {
+                return FirewallStatus::known(true, format!("iptables rule blocks port {}: {}", port, line.trim()));
+            }
+        }
+        let policy_drops = output.lines().any(|line| line.trim() == "-P INPUT DROP");
+        return FirewallStatus::known(
+            policy_drops,
+            if policy_drops {
+                format!("iptables INPUT policy is DROP and no rule accepts port {}", port)
+            } else {
+                "iptables INPUT policy accepts connections".to_string()
+            },
+        );
+    }
+
+    FirewallStatus::unknown("Could not query ufw, firewalld or iptables (they may require root)")
+}
+
+/// The application firewall filters per app rather than per port, so an
+/// enabled firewall without "block all" can't be resolved to a verdict.
+#[cfg(target_os = "macos")]
+pub fn firewall_status(port: u16) -> FirewallStatus {
+    const SOCKETFILTERFW: &str = "/usr/libexec/ApplicationFirewall/socketfilterfw";
+
+    if let Some(output) = command_output("pfctl", &["-s", "info"]) {
+        if output.contains("Status: Enabled") {
+            return FirewallStatus::unknown(format!(
+                "pf is enabled; check its rules for port {} (`sudo pfctl -sr`)",
+                port
+            ));
+        }
+    }
+
+    let Some(state) = command_output(SOCKETFILTERFW, &["--getglobalstate"]) else {
+        return FirewallStatus::unknown("Could not query the macOS application firewall");
+    };
+    if state.contains("disabled") {
+        return FirewallStatus::known(false, "The macOS application firewall is disabled");
+    }
+    let block_all = command_output(SOCKETFILTERFW, &["--getblockall"])
+        .map(|output| output.to_ascii_lowercase().contains("enabled"))
+        .unwrap_or(false);
+    if block_all {
+        return FirewallStatus::known(true, "The macOS application firewall blocks all incoming connections");
+    }
+    FirewallStatus::unknown("The macOS application firewall is enabled; incoming connections depend on per-app rules")
+}
+
+/// Checks the active profile's state, then explicit inbound rules for the
+/// port, then the profile's default inbound policy. netsh output is parsed
+/// as English; other locales fall back to an unknown verdict.
+#[cfg(target_os = "windows")]
+pub fn firewall_status(port: u16) -> FirewallStatus {
+    let Some(state) = command_output("netsh", &["advfirewall", "show", "currentprofile", "state"]) else {
+        return FirewallStatus::unknown("Could not query Windows Firewall");
+    };
+    let Some(on) = state.lines().find_map(|line| {
+        let line = line.trim();
+        line.starts_with("State").then(|| line.ends_with("ON"))
+    }) else {
+        return FirewallStatus::unknown("Could not parse Windows Firewall state");
+    };
+    if !on {
+        return FirewallStatus::known(false, "Windows Firewall is off for the active profile");
+    }
+
+    if let Some(rules) = command_output("netsh", &["advfirewall", "firewall", "show", "rule", "name=all", "dir=in"]) {
+        let port = port.to_string();
+        // Rules are blank-line separated blocks of "Key: Value" lines
+        for rule in rules.split("\r\n\r\n").flat_map(|block| block.split("\n\n")) {
+            let field = |key: &str| {
+                rule.lines()
+                    .find_map(|line| line.trim().strip_prefix(key).map(|value| value.trim_start_matches(':').trim().to_string()))
+            };
+            let matches_port = field("LocalPort")
+                .map(|ports| ports.split(',').any(|p| p.trim() == port || p.trim() == "Any"))
+                .unwrap_or(false);
+            if !matches_port || field("Enabled").as_deref() != Some("Yes") {
+                continue;
+            }
+            match field("Action").as_deref() {
+                Some("Block") => return FirewallStatus::known(true, format!("An inbound rule blocks port {}", port)),
+                Some("Allow") => return FirewallStatus::known(false, format!("An inbound rule allows port {}", port)),
+                _ => {}
+            }
+        }
+    }
+
+    match command_output("netsh", &["advfirewall", "show", "currentprofile", "firewallpolicy"]) {
+        Some(policy) if policy.contains("BlockInbound") => FirewallStatus::known(
+            true,
+            format!("Windows Firewall blocks inbound connections by default and no rule allows port {}", port),
+        ),
+        Some(policy) if policy.contains("AllowInbound") => {
+            FirewallStatus::known(false, "Windows Firewall allows inbound connections by default")
+        }
+        _ => FirewallStatus::unknown("Could not determine the Windows Firewall inbound policy"),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn firewall_status(_port: u16) -> FirewallStatus {
+    FirewallStatus::unknown("Firewall detection is not supported on this platform")
+}
+
+/// Whether the process runs as root (Unix) or with an elevated token (Windows).
+#[cfg(unix)]
+pub fn is_elevated() -> Result<bool, String> {
+    Ok(unsafe { libc::geteuid() } == 0)
+}
+
+#[cfg(windows)]
+pub fn is_elevated() -> Result<bool, String> {
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token: HANDLE = std::ptr::null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return Err(format!("OpenProcessToken failed: {}", std::io::Error::last_os_error()));
+        }
+        let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+        let mut returned = 0u32;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut _ as *mut std::ffi::c_void,
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut returned,
+        );
+        CloseHandle(token);
+        if ok == 0 {
+            return Err(format!("GetTokenInformation failed: {}", std::io::Error::last_os_error()));
+        }
+        Ok(elevation.TokenIsElevated != 0)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn is_elevated() -> Result<bool, String> {
+    Ok(false)
+}
+
+/// Hardware identifiers that feed the machine fingerprint. Never leaves this module.
+#[derive(Default)]
+struct MachineIds {
+    cpu_model: Option<String>,
+    primary_mac: Option<String>,
+    machine_id: Option<String>,
+}
+
+/// A stable, non-reversible identifier for this machine: a SHA-256 over the
+/// CPU model, the primary interface's MAC and the OS machine ID (machine-id,
+/// IOPlatformUUID or MachineGuid). It survives reinstalling the app but
+/// changes when any of those change, e.g. a new CPU or network card, or an
+/// OS reinstall. The raw identifiers are never returned.
+pub fn machine_fingerprint() -> Result<String, String> {
+    use sha2::Digest;
+
+    let ids = machine_ids();
+    if ids.machine_id.is_none() && ids.primary_mac.is_none() {
+        return Err("No stable hardware identifier is available on this machine".to_string());
+    }
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(b"stratmaster-machine-fingerprint-v1");
+    for id in [&ids.cpu_model, &ids.primary_mac, &ids.machine_id] {
+        // Length-prefix each part so ("ab", "c") and ("a", "bc") hash differently
+        let id = id.as_deref().unwrap_or("").trim().to_ascii_lowercase();
+        hasher.update((id.len() as u64).to_le_bytes());
+        hasher.update(id.as_bytes());
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// Zeroed or broadcast MACs show up on virtual and disconnected interfaces
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn is_usable_mac(mac: &str) -> bool {
+    let hex: String = mac.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    hex.len() == 12 && hex != "000000000000" && !hex.eq_ignore_ascii_case("ffffffffffff")
+}
+
+#[cfg(target_os = "linux")]
+fn machine_ids() -> MachineIds {
+    let cpu_model = std::fs::read_to_string("/proc/cpuinfo").ok().and_then(|cpuinfo| {
+        cpuinfo
+            .lines()
+            .find(|line| line.starts_with("model name") || line.starts_with("Model"))
+            .and_then(|line| line.split_once(':'))
+            .map(|(_, model)| model.trim().to_string())
+    });
+
+    // Physical interfaces have a `device` link; sort so "primary" means the same one every time
+    let mut interfaces: Vec<_> = std::fs::read_dir("/sys/class/net")
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    interfaces.retain(|path| path.join("device").exists());
+    interfaces.sort();
+    let primary_mac = interfaces.iter().find_map(|path| {
+        let mac = std::fs::read_to_string(path.join("address")).ok()?;
+        let mac = mac.trim();
+        is_usable_mac(mac).then(|| mac.to_string())
+    });
+
+    let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty());
+
+    MachineIds { cpu_model, primary_mac, machine_id }
+}
+
+#[cfg(target_os = "macos")]
+fn machine_ids() -> MachineIds {
+    let cpu_model = command_output("sysctl", &["-n", "machdep.cpu.brand_string"])
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty());
+
+    // en0 is the built-in interface on every Mac
+    let primary_mac = command_output("ifconfig", &["en0"]).and_then(|output| {
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("ether "))
+            .map(|mac| mac.trim().to_string())
+            .filter(|mac| is_usable_mac(mac))
+    });
+
+    let machine_id = command_output("ioreg", &["-rd1", "-c", "IOPlatformExpertDevice"]).and_then(|output| {
+        output
+            .lines()
+            .find(|line| line.contains("\"IOPlatformUUID\""))
+            .and_then(|line| line.split('=').nth(1))
+            .map(|uuid| uuid.trim().trim_matches('"').to_string())
+    });
+
+    MachineIds { cpu_model, primary_mac, machine_id }
+}
+
+#[cfg(target_os = "windows")]
+fn machine_ids() -> MachineIds {
+    let registry_value = |key: &str, name: &str| {
+        command_output("reg", &["query", key, "/v", name]).and_then(|output| {
+            output
+                .lines()
+                .find(|line| line.trim_start().starts_with(name))
+                .and_then(|line| line.split("REG_SZ").nth(1))
+                .map(|value| value.trim().to_string())
+        })
+    };
+    let cpu_model = registry_value(r"HKLM\HARDWARE\DESCRIPTION\System\CentralProcessor\0", "ProcessorNameString");
+    let machine_id = registry_value(r"HKLM\SOFTWARE\Microsoft\Cryptography", "MachineGuid");
+
+    // getmac lists physical adapters; lowest address wins for stability
+    let mut macs: Vec<String> = command_output("getmac", &["/fo", "csv", "/nh"])
+        .map(|output| {
+            output
+                .lines()
+                .filter_map(|line| line.split(',').next())
+                .map(|mac| mac.trim().trim_matches('"').to_string())
+                .filter(|mac| is_usable_mac(mac))
+                .collect()
+        })
+        .unwrap_or_default();
+    macs.sort();
+
+    MachineIds { cpu_model, primary_mac: macs.into_iter().next(), machine_id }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn machine_ids() -> MachineIds {
+    MachineIds::default()
+}
+
+// GPU drivers
+/// Oldest NVIDIA driver branch that supports CUDA 12, which current inference runtimes require.
+pub const MIN_NVIDIA_DRIVER: (u32, u32) = (525, 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuDriverInfo {
+    /// `nvidia`, `amd`, `intel` or `apple`.
+    pub vendor: String,
+    pub driver_version: String,
+    pub cuda_version: Option<String>,
+    /// CUDA compute capability (e.g. "8.6") on NVIDIA, the Metal feature set on macOS.
+    pub compute_capability: Option<String>,
+}
+
+impl GpuDriverInfo {
+    /// Why this driver is known to be too old for local inference, if it is.
+    pub fn below_minimum(&self) -> Option<String> {
+        if self.vendor != "nvidia" {
+            return None;
+        }
+        let mut parts = self.driver_version.split('.').map(|part| part.parse::<u32>().ok());
+        let version = (parts.next().flatten()?, parts.next().flatten().unwrap_or(0));
+        (version < MIN_NVIDIA_DRIVER).then(|| {
+            format!(
+                "NVIDIA driver {} is older than {}.{}, the minimum for CUDA 12",
+                self.driver_version, MIN_NVIDIA_DRIVER.0, MIN_NVIDIA_DRIVER.1
+            )
+        })
+    }
+}
+
+// nvidia-smi ships with the driver on Linux and Windows alike
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn nvidia_driver_info() -> Option<GpuDriverInfo> {
+    let query = command_output("nvidia-smi", &["--query-gpu=driver_version,compute_cap", "--format=csv,noheader"])
+        // Drivers before 510 don't know `compute_cap`
+        .or_else(|| command_output("nvidia-smi", &["--query-gpu=driver_version", "--format=csv,noheader"]))?;
+    let mut fields = query.lines().next()?.split(',').map(|field| field.trim().to_string());
+    let driver_version = fields.next().filter(|version| !version.is_empty())?;
+    let compute_capability = fields.next().filter(|cap| !cap.is_empty() && cap != "[N/A]");
+
+    // The banner's "CUDA Version: 12.2" is the newest CUDA the driver supports
+    let cuda_version = command_output("nvidia-smi", &[]).and_then(|banner| {
+        banner
+            .split("CUDA Version:")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(str::to_string)
+    });
+
+    Some(GpuDriverInfo {
+        vendor: "nvidia".to_string(),
+        driver_version,
+        cuda_version,
+        compute_capability,
+    })
+}
+
+/// The primary GPU's driver, or `None` when no GPU is present. NVIDIA is
+/// queried through `nvidia-smi`; amdgpu and i915 ship with the kernel, so
+/// their version is the ROCm driver version when installed, else the
+/// kernel release.
+#[cfg(target_os = "linux")]
+pub fn gpu_driver_info() -> Result<Option<GpuDriverInfo>, String> {
+    if let Some(info) = nvidia_driver_info() {
+        return Ok(Some(info));
+    }
+
+    let mut vendors: Vec<String> = std::fs::read_dir("/sys/class/drm")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| std::fs::read_to_string(entry.path().join("device/vendor")).ok())
+                .map(|vendor| vendor.trim().to_ascii_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    vendors.dedup();
+    let vendor = if vendors.iter().any(|id| id == "0x1002") {
+        "amd"
+    } else if vendors.iter().any(|id| id == "0x8086") {
+        "intel"
+    } else {
+        return Ok(None);
+    };
+
+    let rocm_version = (vendor == "amd")
+        .then(|| command_output("rocm-smi", &["--showdriverversion"]))
+        .flatten()
+        .and_then(|output| {
+            output
+                .lines()
+                .find_map(|line| line.split_once("Driver version:"))
+                .map(|(_, version)| version.trim().to_string())
+        });
+    let driver_version = match rocm_version {
+        Some(version) => version,
+        None => std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .map(|release| release.trim().to_string())
+            .map_err(|e| format!("Failed to read kernel release: {}", e))?,
+    };
+    Ok(Some(GpuDriverInfo {
+        vendor: vendor.to_string(),
+        driver_version,
+        cuda_version: None,
+        compute_capability: None,
+    }))
+}
+
+/// GPU drivers ship with macOS, so the driver version is the OS version and
+/// the capability is the Metal support level `system_profiler` reports.
+#[cfg(target_os = "macos")]
+pub fn gpu_driver_info() -> Result<Option<GpuDriverInfo>, String> {
+    let Some(displays) = command_output("system_profiler", &["SPDisplaysDataType"]) else {
+        return Err("Failed to run system_profiler".to_string());
+    };
+    let field = |name: &str| {
+        displays
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(name))
+            .map(|value| value.trim().to_string())
+    };
+    let Some(chipset) = field("Chipset Model:") else {
+        return Ok(None);
+    };
+    let vendor = if chipset.starts_with("Apple") {
+        "apple"
+    } else if chipset.contains("AMD") || chipset.contains("Radeon") {
+        "amd"
+    } else if chipset.contains("Intel") {
+        "intel"
+    } else if chipset.contains("NVIDIA") {
+        "nvidia"
+    } else {
+        "apple"
+    };
+    let driver_version = command_output("sw_vers", &["-productVersion"])
+        .map(|version| version.trim().to_string())
+        .ok_or_else(|| "Failed to read the macOS version".to_string())?;
+    Ok(Some(GpuDriverInfo {
+        vendor: vendor.to_string(),
+        driver_version,
+        cuda_version: None,
+        // "Metal Support: Metal 3" on current systems, "Metal Family: ..." on older ones
+        compute_capability: field("Metal Support:").or_else(|| field("Metal Family:")),
+    }))
+}
+
+#[cfg(target_os = "windows")]
+pub fn gpu_driver_info() -> Result<Option<GpuDriverInfo>, String> {
+    if let Some(info) = nvidia_driver_info() {
+        return Ok(Some(info));
+    }
+    let output = command_output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-CimInstance Win32_VideoController | ForEach-Object { \"$($_.AdapterCompatibility)|$($_.DriverVersion)\" }",
+        ],
+    )
+    .ok_or_else(|| "Failed to query video controllers".to_string())?;
+
+    // Skip the basic display adapter Windows falls back to without a driver
+    let adapter = output
+        .lines()
+        .filter_map(|line| line.trim().split_once('|'))
+        .find(|(vendor, version)| !vendor.starts_with("Microsoft") && !version.is_empty());
+    Ok(adapter.map(|(vendor, version)| GpuDriverInfo {
+        vendor: if vendor.contains("AMD") || vendor.contains("Advanced Micro") {
+            "amd"
+        } else if vendor.contains("Intel") {
+            "intel"
+        } else {
+            vendor
+        }
+        .to_ascii_lowercase(),
+        driver_version: version.to_string(),
+        cuda_version: None,
+        compute_capability: None,
+    }))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn gpu_driver_info() -> Result<Option<GpuDriverInfo>, String> {
+    Ok(None)
+}
+
+/// Busy percentage of the first GPU that reports one. amdgpu exposes it in
+/// sysfs (a cheap read); NVIDIA needs an `nvidia-smi` run, so sample sparingly.
+pub fn gpu_utilization() -> Option<f32> {
+    #[cfg(target_os = "linux")]
+    {
+        let busy = std::fs::read_dir("/sys/class/drm").ok().and_then(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| std::fs::read_to_string(entry.path().join("device/gpu_busy_percent")).ok())
+                .find_map(|busy| busy.trim().parse::<f32>().ok())
+        });
+        if busy.is_some() {
+            return busy;
+        }
+    }
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    {
+        command_output("nvidia-smi", &["--query-gpu=utilization.gpu", "--format=csv,noheader,nounits"])
+            .and_then(|output| output.lines().next()?.trim().parse::<f32>().ok())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+// GPU enumeration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuDescriptor {
+    pub index: usize,
+    pub name: String,
+    pub vendor: String,
+    /// `None` for GPUs sharing system memory (Apple silicon, most iGPUs).
+    pub vram_mb: Option<u64>,
+    /// Position among GPUs of the same vendor, as its runtime counts them.
+    pub vendor_index: usize,
+}
+
+impl GpuDescriptor {
+    /// Variables restricting a spawned backend to this GPU. Other vendors'
+    /// runtimes don't see it anyway, so only its own runtime is narrowed.
+    pub fn device_env(&self) -> Vec<(&'static str, String)> {
+        let index = self.vendor_index.to_string();
+        match self.vendor.as_str() {
+            "nvidia" => vec![("CUDA_VISIBLE_DEVICES", index)],
+            "amd" => vec![("HIP_VISIBLE_DEVICES", index.clone()), ("ROCR_VISIBLE_DEVICES", index)],
+            "intel" => vec![("ONEAPI_DEVICE_SELECTOR", format!("level_zero:{}", index))],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Dedicated GPUs, or shared-memory ones big enough to matter (Apple).
+    pub fn is_usable(&self) -> bool {
+        match self.vram_mb {
+            Some(vram_mb) => vram_mb >= 2048,
+            None => self.vendor == "apple",
+        }
+    }
+}
+
+// Fills in global and per-vendor indices in discovery order
+fn number_gpus(found: Vec<(String, String, Option<u64>)>) -> Vec<GpuDescriptor> {
+    let mut per_vendor: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    found
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, vendor, vram_mb))| {
+            let counter = per_vendor.entry(vendor.clone()).or_default();
+            let vendor_index = *counter;
+            *counter += 1;
+            GpuDescriptor { index, name, vendor, vram_mb, vendor_index }
+        })
+        .collect()
+}
+
+// "0, NVIDIA GeForce RTX 4070, 12282" in CUDA device order
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn nvidia_gpus() -> Vec<(String, String, Option<u64>)> {
+    command_output("nvidia-smi", &["--query-gpu=name,memory.total", "--format=csv,noheader,nounits"])
+        .map(|output| {
+            output
+                .lines()
+                .filter_map(|line| {
+                    let (name, vram) = line.rsplit_once(',')?;
+                    Some((name.trim().to_string(), "nvidia".to_string(), vram.trim().parse().ok()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// NVIDIA GPUs from `nvidia-smi`, then AMD and Intel DRM cards from sysfs.
+#[cfg(target_os = "linux")]
+pub fn list_gpus() -> Result<Vec<GpuDescriptor>, String> {
+    let mut found = nvidia_gpus();
+    let mut cards: Vec<std::path::PathBuf> = std::fs::read_dir("/sys/class/drm")
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                // "card0", not connectors like "card0-HDMI-A-1"
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .and_then(|name| name.strip_prefix("card"))
+                        .is_some_and(|id| id.chars().all(|c| c.is_ascii_digit()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    cards.sort();
+    for card in cards {
+        let device = card.join("device");
+        let read = |file: &str| std::fs::read_to_string(device.join(file)).ok().map(|value| value.trim().to_string());
+        let vendor = match read("vendor").as_deref() {
+            Some("0x1002") => "amd",
+            Some("0x8086") => "intel",
+            _ => continue,
+        };
+        let name = read("product_name")
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("{} GPU {}", vendor.to_uppercase(), read("device").unwrap_or_default()));
+        // Intel iGPUs report no dedicated pool; small AMD APU carve-outs do
+        let vram_mb = read("mem_info_vram_total")
+            .and_then(|bytes| bytes.parse::<u64>().ok())
+            .map(|bytes| bytes / (1024 * 1024));
+        found.push((name, vendor.to_string(), vram_mb));
+    }
+    Ok(number_gpus(found))
+}
+
+/// Each "Chipset Model" entry of `system_profiler SPDisplaysDataType`.
+#[cfg(target_os = "macos")]
+pub fn list_gpus() -> Result<Vec<GpuDescriptor>, String> {
+    let displays = command_output("system_profiler", &["SPDisplaysDataType"])
+        .ok_or_else(|| "Failed to run system_profiler".to_string())?;
+    let mut found: Vec<(String, String, Option<u64>)> = Vec::new();
+    for line in displays.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("Chipset Model:") {
+            let name = name.trim().to_string();
+            let vendor = if name.starts_with("Apple") {
+                "apple"
+            } else if name.contains("AMD") || name.contains("Radeon") {
+                "amd"
+            } else if name.contains("Intel") {
+                "intel"
+            } else if name.contains("NVIDIA") {
+                "nvidia"
+            } else {
+                "apple"
+            };
+            found.push((name, vendor.to_string(), None));
+        } else if let Some((_, value)) = line.strip_prefix("VRAM (").and_then(|rest| rest.split_once("):")) {
+            let mut parts = value.split_whitespace();
+            let amount = parts.next().and_then(|amount| amount.parse::<u64>().ok());
+            let vram_mb = amount.map(|amount| if parts.next() == Some("GB") { amount * 1024 } else { amount });
+            if let Some(last) = found.last_mut() {
+                last.2 = vram_mb;
+            }
+        }
+    }
+    Ok(number_gpus(found))
+}
+
+/// NVIDIA GPUs from `nvidia-smi`, then other adapters from
+/// `Win32_VideoController`, sized by the driver's 64-bit `qwMemorySize`.
+#[cfg(target_os = "windows")]
+pub fn list_gpus() -> Result<Vec<GpuDescriptor>, String> {
+    let mut found = nvidia_gpus();
+    let nvidia_listed = !found.is_empty();
+    // Sizes are keyed by driver description, which matches the controller name
+    let script = r#"$sizes = @{}; Get-ItemProperty 'HKLM:\SYSTEM\CurrentControlSet\Control\Class\{4d36e968-e325-11ce-bfc1-08002be10318}\0*' -ErrorAction SilentlyContinue | ForEach-Object { $sizes[$_.DriverDesc] = $_.'HardwareInformation.qwMemorySize' }; Get-CimInstance Win32_VideoController | ForEach-Object { "$($_.Name)|$($_.AdapterCompatibility)|$($sizes[$_.Name])" }"#;
+    let output = command_output("powershell", &["-NoProfile", "-NonInteractive", "-Command", script])
+        .ok_or_else(|| "Failed to query video controllers".to_string())?;
+    for line in output.lines() {
+        let mut fields = line.trim().splitn(3, '|');
+        let (Some(name), Some(company)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let vendor = if company.contains("NVIDIA") {
+            // Already listed in CUDA order when nvidia-smi answered
+            if nvidia_listed {
+                continue;
+            }
+            "nvidia"
+        } else if company.contains("AMD") || company.contains("Advanced Micro") {
+            "amd"
+        } else if company.contains("Intel") {
+            "intel"
+        } else {
+            // The basic display adapter Windows falls back to without a driver
+            continue;
+        };
+        let vram_mb = fields
+            .next()
+            .and_then(|bytes| bytes.trim().parse::<u64>().ok())
+            .map(|bytes| bytes / (1024 * 1024))
+            .filter(|vram_mb| *vram_mb > 0);
+        found.push((name.trim().to_string(), vendor.to_string(), vram_mb));
+    }
+    Ok(number_gpus(found))
+}
+
+// VRAM headroom
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VramHeadroom {
+    pub total_mb: u64,
+    pub used_mb: u64,
+    pub free_mb: u64,
+}
+
+impl VramHeadroom {
+    fn from_usage(total_mb: u64, used_mb: u64) -> Self {
+        VramHeadroom {
+            total_mb,
+            used_mb: used_mb.min(total_mb),
+            free_mb: total_mb.saturating_sub(used_mb),
+        }
+    }
+}
+
+// Values are MiB; the first GPU is the one llama.cpp and friends default to
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn nvidia_vram() -> Option<VramHeadroom> {
+    let output = command_output(
+        "nvidia-smi",
+        &["--query-gpu=memory.total,memory.used", "--format=csv,noheader,nounits"],
+    )?;
+    let mut fields = output.lines().next()?.split(',').map(|field| field.trim().parse::<u64>().ok());
+    Some(VramHeadroom::from_usage(fields.next()??, fields.next()??))
+}
+
+/// Current VRAM use of the primary GPU, or `None` without one. Usage moves
+/// with whatever else is on screen, so sample right before deciding.
+/// NVIDIA goes through `nvidia-smi`; amdgpu reports in sysfs.
+#[cfg(target_os = "linux")]
+pub fn vram_headroom() -> Result<Option<VramHeadroom>, String> {
+    if let Some(headroom) = nvidia_vram() {
+        return Ok(Some(headroom));
+    }
+    let Ok(entries) = std::fs::read_dir("/sys/class/drm") else {
+        return Ok(None);
+    };
+    let read_bytes = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path).ok().and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    // Integrated GPUs report a small carve-out too; take the largest pool
+    let headroom = entries
+        .flatten()
+        .filter_map(|entry| {
+            let device = entry.path().join("device");
+            let total = read_bytes(device.join("mem_info_vram_total"))?;
+            let used = read_bytes(device.join("mem_info_vram_used"))?;
+            Some(VramHeadroom::from_usage(total / (1024 * 1024), used / (1024 * 1024)))
+        })
+        .max_by_key(|headroom| headroom.total_mb);
+    Ok(headroom)
+}
+
+/// Apple silicon shares system memory with the GPU, and Metal caps how much
+/// a process may wire: `iogpu.wired_limit_mb` when set, else about 75% of
+/// RAM. Headroom is that cap, bounded by memory not already in use (free,
+/// inactive and purgeable pages per `vm_stat`). Intel Macs only report the
+/// discrete card's size.
+#[cfg(target_os = "macos")]
+pub fn vram_headroom() -> Result<Option<VramHeadroom>, String> {
+    let Some(driver) = gpu_driver_info()? else {
+        return Ok(None);
+    };
+    let mb = 1024 * 1024;
+    if driver.vendor != "apple" {
+        let displays = command_output("system_profiler", &["SPDisplaysDataType"]).unwrap_or_default();
+        let total_mb = displays.lines().find_map(|line| {
+            let (_, value) = line.trim().split_once("VRAM (")?.1.split_once("):")?;
+            let mut parts = value.split_whitespace();
+            let amount = parts.next()?.parse::<u64>().ok()?;
+            Some(if parts.next()? == "GB" { amount * 1024 } else { amount })
+        });
+        return Ok(total_mb.map(|total_mb| VramHeadroom::from_usage(total_mb, 0)));
+    }
+
+    let memory_bytes = command_output("sysctl", &["-n", "hw.memsize"])
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .ok_or_else(|| "Failed to read hw.memsize".to_string())?;
+    let total_mb = command_output("sysctl", &["-n", "iogpu.wired_limit_mb"])
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(memory_bytes / mb * 3 / 4);
+
+    let available_mb = command_output("vm_stat", &[]).and_then(|vm_stat| {
+        let page_size = vm_stat
+            .split("page size of ")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|size| size.parse::<u64>().ok())?;
+        let counter = |name: &str| {
+            vm_stat.lines().find_map(|line| {
+                let value = line.strip_prefix(name)?.trim().trim_end_matches('.');
+                value.parse::<u64>().ok()
+            })
+        };
+        let pages = counter("Pages free:")? + counter("Pages inactive:").unwrap_or(0) + counter("Pages purgeable:").unwrap_or(0);
+        Some(pages * page_size / mb)
+    });
+    let free_mb = available_mb.map_or(total_mb, |available| available.min(total_mb));
+    Ok(Some(VramHeadroom::from_usage(total_mb, total_mb - free_mb)))
+}
+
+/// NVIDIA through `nvidia-smi`; other vendors from the dedicated-memory
+/// performance counters, sized by the driver's 64-bit `qwMemorySize`
+/// (`Win32_VideoController.AdapterRAM` wraps at 4 GB).
+#[cfg(target_os = "windows")]
+pub fn vram_headroom() -> Result<Option<VramHeadroom>, String> {
+    if let Some(headroom) = nvidia_vram() {
+        return Ok(Some(headroom));
+    }
+    let script = "$total = Get-ItemProperty 'HKLM:\\SYSTEM\\CurrentControlSet\\Control\\Class\\{4d36e968-e325-11ce-bfc1-08002be10318}\\0*' -ErrorAction SilentlyContinue | \
+        ForEach-Object { $_.'HardwareInformation.qwMemorySize' } | Measure-Object -Maximum; \
+        $used = (Get-Counter '\\GPU Adapter Memory(*)\\Dedicated Usage' -ErrorAction SilentlyContinue).CounterSamples | Measure-Object -Property CookedValue -Maximum; \
+        \"$($total.Maximum)|$($used.Maximum)\"";
+    let output = command_output("powershell", &["-NoProfile", "-NonInteractive", "-Command", script])
+        .ok_or_else(|| "Failed to query GPU memory".to_string())?;
+    let mb = 1024 * 1024;
+    let parse = |value: &str| value.trim().parse::<f64>().ok().map(|bytes| bytes as u64 / mb);
+    let Some((total, used)) = output.trim().split_once('|') else {
+        return Ok(None);
+    };
+    Ok(parse(total)
+        .filter(|total_mb| *total_mb > 0)
+        .map(|total_mb| VramHeadroom::from_usage(total_mb, parse(used).unwrap_or(0))))
+}
+
+// Pending OS updates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OsUpdate {
+    pub name: String,
+    pub version: Option<String>,
+    /// Security fixes, or updates the platform marks as critical/recommended.
+    pub critical: bool,
+    pub restart_required: bool,
+}
+
+/// `softwareupdate --list`, which asks Apple's servers and can take a while.
+/// WebKit ships with macOS and Safari updates, so those can change rendering.
+#[cfg(target_os = "macos")]
+pub fn pending_os_updates() -> Result<Vec<OsUpdate>, String> {
+    // "* Label: macOS Sonoma 14.5-23F79"
+    // "\tTitle: macOS Sonoma 14.5, Version: 14.5, Size: 3219122KiB, Recommended: YES, Action: restart,"
+    let Some(output) = command_output("softwareupdate", &["--list"]) else {
+        return Ok(Vec::new());
+    };
+    let updates = output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Title:"))
+        .map(|details| {
+            let field = |name: &str| {
+                details
+                    .split(',')
+                    .find_map(|part| part.trim().strip_prefix(name))
+                    .map(|value| value.trim().to_string())
+            };
+            let name = details.split(',').next().unwrap_or_default().trim().to_string();
+            OsUpdate {
+                critical: field("Recommended:").is_some_and(|value| value == "YES")
+                    || name.starts_with("macOS")
+                    || name.starts_with("Safari"),
+                restart_required: field("Action:").is_some_and(|value| value == "restart"),
+                version: field("Version:"),
+                name,
+            }
+        })
+        .collect();
+    Ok(updates)
+}
+
+/// Searches through the Windows Update Agent COM API, which lists pending
+/// updates without elevation (installing them needs it).
+#[cfg(target_os = "windows")]
+pub fn pending_os_updates() -> Result<Vec<OsUpdate>, String> {
+    let script = "$searcher = (New-Object -ComObject Microsoft.Update.Session).CreateUpdateSearcher(); \
+        $searcher.Search('IsInstalled=0 and IsHidden=0').Updates | ForEach-Object { \
+        \"$($_.Title)|$($_.MsrcSeverity)|$($_.RebootRequired)|$(($_.Categories | ForEach-Object { $_.Name }) -join ';')\" }";
+    let Some(output) = command_output("powershell", &["-NoProfile", "-NonInteractive", "-Command", script]) else {
+        return Ok(Vec::new());
+    };
+    let updates = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(4, '|');
+            let name = fields.next().filter(|name| !name.is_empty())?.to_string();
+            let severity = fields.next().unwrap_or_default();
+            let restart_required = fields.next().is_some_and(|reboot| reboot.eq_ignore_ascii_case("true"));
+            let categories = fields.next().unwrap_or_default();
+            Some(OsUpdate {
+                critical: severity == "Critical"
+                    || severity == "Important"
+                    || categories.contains("Critical Updates")
+                    || categories.contains("Security Updates"),
+                restart_required,
+                version: None,
+                name,
+            })
+        })
+        .collect();
+    Ok(updates)
+}
+
+/// Asks the package manager from its local metadata (no refresh, which would
+/// need root): apt, then dnf, then pacman's `checkupdates`. Security-pocket
+/// packages count as critical; kernel, WebKitGTK and Mesa updates need a
+/// restart to take effect.
+#[cfg(target_os = "linux")]
+pub fn pending_os_updates() -> Result<Vec<OsUpdate>, String> {
+    let needs_restart = |name: &str| {
+        ["linux-image", "kernel", "webkit2gtk", "libwebkit2gtk", "mesa", "nvidia-driver"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+    };
+
+    // "libwebkit2gtk-4.1-0/jammy-security 2.44.2-0ubuntu0.22.04.1 amd64 [upgradable from: ...]"
+    if let Some(output) = command_output("apt", &["list", "--upgradable"]) {
+        return Ok(output
+            .lines()
+            .filter(|line| line.contains("[upgradable"))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let (name, pocket) = fields.next()?.split_once('/')?;
+                Some(OsUpdate {
+                    name: name.to_string(),
+                    version: fields.next().map(str::to_string),
+                    critical: pocket.contains("-security"),
+                    restart_required: needs_restart(name),
+                })
+            })
+            .collect());
+    }
+
+    // `dnf check-update` exits 100 when updates exist, so read it directly
+    if let Ok(output) = std::process::Command::new("dnf").args(["check-update", "-q", "-C"]).output() {
+        if matches!(output.status.code(), Some(0) | Some(100)) {
+            let security: Vec<String> = command_output("dnf", &["updateinfo", "list", "--security", "-q", "-C"])
+                .map(|list| list.lines().filter_map(|line| line.split_whitespace().nth(2).map(str::to_string)).collect())
+                .unwrap_or_default();
+            return Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.split_whitespace();
+                    let package = fields.next()?;
+                    let version = fields.next()?;
+                    let name = package.rsplit_once('.').map_or(package, |(name, _arch)| name);
+                    Some(OsUpdate {
+                        critical: security.iter().any(|advisory| advisory.starts_with(name)),
+                        restart_required: needs_restart(name),
+                        name: name.to_string(),
+                        version: Some(version.to_string()),
+                    })
+                })
+                .collect());
+        }
+    }
+
+    // "webkit2gtk 2.44.1-1 -> 2.44.2-1"
+    if let Some(output) = command_output("checkupdates", &[]) {
+        return Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let name = fields.next()?;
+                Some(OsUpdate {
+                    name: name.to_string(),
+                    version: fields.last().map(str::to_string),
+                    critical: false,
+                    restart_required: needs_restart(name),
+                })
+            })
+            .collect());
+    }
+    Ok(Vec::new())
+}
+
+// Port owners
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub process_name: String,
+    /// Unavailable for processes of other users without elevation.
+    pub executable_path: Option<String>,
+}
+
+// A failed bind on loopback means something holds the port, even when we
+// can't see what (processes of other users are hidden without elevation)
+fn port_in_use(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_err()
+}
+
+fn unidentified_owner(port: u16) -> Result<Option<PortOwner>, String> {
+    if port_in_use(port) {
+        Err(format!(
+            "Port {} is in use by a process this user cannot inspect; it likely belongs to another user or a system service",
+            port
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Which process listens on TCP `port`, or `None` when the port is free.
+/// Reads `/proc` directly, so processes of the current user never need root.
+#[cfg(target_os = "linux")]
+pub fn port_owner(port: u16) -> Result<Option<PortOwner>, String> {
+    const TCP_LISTEN: &str = "0A";
+    // local_address is "IP:PORT" in hex; state and inode are columns 3 and 9
+    let mut inodes = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(contents) = std::fs::read_to_string(table) else {
+            continue;
+        };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (Some(local), Some(state), Some(inode)) = (fields.get(1), fields.get(3), fields.get(9)) else {
+                continue;
+            };
+            let local_port = local.rsplit(':').next().and_then(|hex| u16::from_str_radix(hex, 16).ok());
+            if local_port == Some(port) && *state == TCP_LISTEN && *inode != "0" {
+                inodes.push(format!("socket:[{}]", inode));
+            }
+        }
+    }
+    if inodes.is_empty() {
+        return unidentified_owner(port);
+    }
+
+    let processes = std::fs::read_dir("/proc").map_err(|e| format!("Failed to read /proc: {}", e))?;
+    for process in processes.flatten() {
+        let Some(pid) = process.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Unreadable for other users' processes; skip them
+        let Ok(fds) = std::fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        let owns_socket = fds.flatten().any(|fd| {
+            std::fs::read_link(fd.path())
+                .map(|target| inodes.iter().any(|inode| target.as_os_str() == inode.as_str()))
+                .unwrap_or(false)
+        });
+        if owns_socket {
+            let process_name = std::fs::read_to_string(process.path().join("comm"))
+                .map(|name| name.trim().to_string())
+                .unwrap_or_else(|_| pid.to_string());
+            let executable_path = std::fs::read_link(process.path().join("exe"))
+                .ok()
+                .map(|path| path.to_string_lossy().to_string());
+            return Ok(Some(PortOwner { pid, process_name, executable_path }));
+        }
+    }
+    unidentified_owner(port)
+}
+
+#[cfg(target_os = "macos")]
+pub fn port_owner(port: u16) -> Result<Option<PortOwner>, String> {
+    // -F emits one field per line: "p<pid>", "c<command>"
+    let listing = command_output("lsof", &["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fpc"]).unwrap_or_default();
+    let pid = listing
+        .lines()
+        .find_map(|line| line.strip_prefix('p'))
+        .and_then(|pid| pid.parse::<u32>().ok());
+    let Some(pid) = pid else {
+        return unidentified_owner(port);
+    };
+    let process_name = listing
+        .lines()
+        .find_map(|line| line.strip_prefix('c'))
+        .map(str::to_string)
+        .unwrap_or_else(|| pid.to_string());
+    // `comm` is the full executable path on macOS
+    let executable_path = command_output("ps", &["-o", "comm=", "-p", &pid.to_string()])
+        .map(|path| path.trim().to_string())
+        .filter(|path| path.starts_with('/'));
+    Ok(Some(PortOwner { pid, process_name, executable_path }))
+}
+
+/// `netstat -ano` lists listening sockets of every process with their PID
+/// without elevation; the name and image path come from the process itself.
+#[cfg(target_os = "windows")]
+pub fn port_owner(port: u16) -> Result<Option<PortOwner>, String> {
+    let listing = command_output("netstat", &["-ano", "-p", "TCP"]).unwrap_or_default();
+    let suffix = format!(":{}", port);
+    // "  TCP    0.0.0.0:8080    0.0.0.0:0    LISTENING    4242"
+    let pid = listing.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, local, _, state, pid] if local.ends_with(&suffix) && *state == "LISTENING" => pid.parse::<u32>().ok(),
+            _ => None,
+        }
+    });
+    let Some(pid) = pid else {
+        return unidentified_owner(port);
+    };
+
+    // CSV: "name.exe","4242","Console","1","12,345 K"
+    let process_name = command_output("tasklist", &["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .and_then(|output| output.lines().next()?.split(',').next().map(|name| name.trim_matches('"').to_string()))
+        .filter(|name| !name.is_empty() && !name.starts_with("INFO:"))
+        .unwrap_or_else(|| pid.to_string());
+    Ok(Some(PortOwner {
+        pid,
+        process_name,
+        executable_path: windows_process_image(pid),
+    }))
+}
+
+#[cfg(target_os = "windows")]
+fn windows_process_image(pid: u32) -> Option<String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+        let mut buffer = [0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buffer.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        (ok != 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn port_owner(port: u16) -> Result<Option<PortOwner>, String> {
+    unidentified_owner(port)
+}
+
+// Locale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocaleInfo {
+    /// ISO 639 code, e.g. `de`.
+    pub language: String,
+    /// ISO 3166 code, e.g. `AT`.
+    pub region: Option<String>,
+    pub decimal_separator: String,
+    /// Day, month and year order such as `DD.MM.YYYY`.
+    pub date_format_hint: String,
+}
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+// Regions writing 1.234,5; the rest of the world mostly uses a point
+const DECIMAL_COMMA_REGIONS: &[&str] = &[
+    "AR", "AT", "BE", "BR", "CL", "CO", "CZ", "DE", "DK", "ES", "FI", "FR", "GR", "HR", "HU", "ID", "IT", "NL", "NO",
+    "PL", "PT", "RO", "RS", "RU", "SE", "SI", "SK", "TR", "UA", "VN",
+];
+
+impl LocaleInfo {
+    /// Splits a POSIX (`de_AT.UTF-8@euro`) or BCP 47 (`de-AT`) tag. `C`,
+    /// `POSIX` and malformed tags give `None`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let tag = tag.split(['.', '@']).next()?.trim();
+        let mut parts = tag.split(['_', '-']);
+        let language = parts.next()?.to_ascii_lowercase();
+        if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        // Skip a script subtag such as `Hant` in zh-Hant-TW
+        let region = parts
+            .find(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(str::to_ascii_uppercase);
+        let (decimal_separator, date_format_hint) = region_conventions(region.as_deref());
+        Some(LocaleInfo {
+            language,
+            region,
+            decimal_separator: decimal_separator.to_string(),
+            date_format_hint: date_format_hint.to_string(),
+        })
+    }
+
+    pub fn tag(&self) -> String {
+        match &self.region {
+            Some(region) => format!("{}-{}", self.language, region),
+            None => self.language.clone(),
+        }
+    }
+
+    /// `de-AT,de;q=0.9`: the regional tag first, the bare language as fallback.
+    pub fn accept_language(&self) -> String {
+        self.accept_language_with("")
+    }
+
+    /// [`LocaleInfo::accept_language`] followed by the comma-separated
+    /// `fallbacks`, each weighted 0.1 below the one before (down to 0.1), e.g.
+    /// `de-AT,de;q=0.9,en;q=0.8`. Weights given in `fallbacks` are replaced
+    /// and repeated languages dropped.
+    pub fn accept_language_with(&self, fallbacks: &str) -> String {
+        let mut tags = vec![self.tag()];
+        if self.region.is_some() {
+            tags.push(self.language.clone());
+        }
+        for fallback in fallbacks.split(',') {
+            let tag = fallback.split(';').next().unwrap_or_default().trim();
+            if !tag.is_empty() && !tags.iter().any(|listed| listed.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
+            }
+        }
+        let mut header = tags[0].clone();
+        for (index, tag) in tags.iter().enumerate().skip(1) {
+            let q = 10usize.saturating_sub(index).max(1);
+            header.push_str(&format!(",{};q=0.{}", tag, q));
+        }
+        header
+    }
+}
+
+impl Default for LocaleInfo {
+    fn default() -> Self {
+        LocaleInfo::from_tag(DEFAULT_LOCALE).expect("default locale tag is valid")
+    }
+}
+
+/// Checks an `Accept-Language` value such as `en-GB,en;q=0.9`.
+pub fn validate_accept_language(value: &str) -> Result<(), String> {
+    let invalid = |part: &str| format!("Invalid Accept-Language entry {:?}; expected e.g. en-GB,en;q=0.9", part);
+    if value.trim().is_empty() {
+        return Err("Accept-Language must not be empty".to_string());
+    }
+    for part in value.split(',').map(str::trim) {
+        let mut pieces = part.split(';').map(str::trim);
+        let tag = pieces.next().unwrap_or_default();
+        let tag_ok = tag == "*"
+            || (!tag.is_empty()
+                && tag.split('-').all(|sub| (1..=8).contains(&sub.len()) && sub.chars().all(|c| c.is_ascii_alphanumeric())));
+        let weight_ok = pieces.all(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .is_some_and(|q| (0.0..=1.0).contains(&q))
+        });
+        if !tag_ok || !weight_ok {
+            return Err(invalid(part));
+        }
+    }
+    Ok(())
+}
+
+// Used where the OS only reports the locale name, not its formats
+fn region_conventions(region: Option<&str>) -> (&'static str, &'static str) {
+    let Some(region) = region else {
+        return (".", "YYYY-MM-DD");
+    };
+    let decimal = if DECIMAL_COMMA_REGIONS.contains(&region) { "," } else { "." };
+    let date = match region {
+        "US" | "PH" => "MM/DD/YYYY",
+        "CN" | "JP" | "KR" | "TW" | "HU" | "LT" | "SE" | "CA" => "YYYY-MM-DD",
+        "AT" | "CH" | "CZ" | "DE" | "DK" | "FI" | "NO" | "PL" | "RO" | "RU" | "SK" | "TR" | "UA" => "DD.MM.YYYY",
+        "NL" => "DD-MM-YYYY",
+        _ => "DD/MM/YYYY",
+    };
+    (decimal, date)
+}
+
+// strftime (`%d.%m.%Y`) or .NET (`dd.MM.yyyy`) patterns to `DD.MM.YYYY`
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn date_format_hint(pattern: &str) -> Option<String> {
+    let hint = pattern
+        .replace("%d", "DD")
+        .replace("%e", "D")
+        .replace("%m", "MM")
+        .replace("%Y", "YYYY")
+        .replace("%y", "YY")
+        .to_ascii_uppercase();
+    (hint.contains('D') && hint.contains('M') && hint.contains('Y') && !hint.contains('%')).then_some(hint)
+}
+
+// The first of these that is set decides, as in setlocale(3)
+#[cfg(target_os = "linux")]
+fn posix_locale_var(category: &str) -> Option<String> {
+    ["LC_ALL", category, "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+pub fn system_locale() -> Result<LocaleInfo, String> {
+    let tag = posix_locale_var("LC_MESSAGES").ok_or_else(|| "No locale variables are set".to_string())?;
+    let mut info = LocaleInfo::from_tag(&tag).ok_or_else(|| format!("Unrecognized locale {}", tag))?;
+    // `locale` reports what glibc would actually format with
+    if let Some(numeric) = posix_locale_var("LC_NUMERIC").and_then(|tag| LocaleInfo::from_tag(&tag)) {
+        info.decimal_separator = numeric.decimal_separator;
+    }
+    if let Some(point) = command_output("locale", &["decimal_point"]).map(|out| out.trim().to_string()) {
+        if !point.is_empty() {
+            info.decimal_separator = point;
+        }
+    }
+    if let Some(hint) = command_output("locale", &["d_fmt"]).and_then(|out| date_format_hint(out.trim())) {
+        info.date_format_hint = hint;
+    }
+    Ok(info)
+}
+
+#[cfg(target_os = "macos")]
+pub fn system_locale() -> Result<LocaleInfo, String> {
+    // e.g. "en_GB" or "de_DE@currency=EUR"
+    let tag = command_output("defaults", &["read", "-g", "AppleLocale"])
+        .or_else(|| std::env::var("LANG").ok())
+        .ok_or_else(|| "Failed to read AppleLocale".to_string())?;
+    LocaleInfo::from_tag(tag.trim()).ok_or_else(|| format!("Unrecognized locale {}", tag.trim()))
+}
+
+#[cfg(target_os = "windows")]
+pub fn system_locale() -> Result<LocaleInfo, String> {
+    let script = "$c = Get-Culture; \
+        \"$($c.Name)`n$($c.NumberFormat.NumberDecimalSeparator)`n$($c.DateTimeFormat.ShortDatePattern)\"";
+    let output = command_output("powershell", &["-NoProfile", "-NonInteractive", "-Command", script])
+        .ok_or_else(|| "Failed to query the current culture".to_string())?;
+    let mut lines = output.lines().map(str::trim);
+    let tag = lines.next().unwrap_or_default();
+    let mut info = LocaleInfo::from_tag(tag).ok_or_else(|| format!("Unrecognized locale {}", tag))?;
+    if let Some(separator) = lines.next().filter(|separator| !separator.is_empty()) {
+        info.decimal_separator = separator.to_string();
+    }
+    if let Some(hint) = lines.next().and_then(date_format_hint) {
+        info.date_format_hint = hint;
+    }
+    Ok(info)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn system_locale() -> Result<LocaleInfo, String> {
+    Err("Locale detection is not supported on this platform".to_string())
+}
+
+// Disk benchmark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskBenchmark {
+    pub seq_read_mbps: f64,
+    pub seq_write_mbps: f64,
+    /// 4 KiB reads at random offsets.
+    pub random_read_iops: f64,
+    pub test_size_mb: u64,
+}
+
+const BENCHMARK_MAX_BYTES: u64 = 256 * 1024 * 1024;
+const BENCHMARK_MIN_BYTES: u64 = 16 * 1024 * 1024;
+const BENCHMARK_BLOCK: usize = 1024 * 1024;
+const RANDOM_READ_BLOCK: usize = 4096;
+const RANDOM_READ_DURATION: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Asks the OS not to serve the next reads from memory, so they measure the disk
+#[cfg(target_os = "linux")]
+fn drop_cached_pages(file: &std::fs::File) {
+    use std::os::unix::io::AsRawFd;
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn drop_cached_pages(file: &std::fs::File) {
+    use std::os::unix::io::AsRawFd;
+    unsafe {
+        libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1);
+    }
+}
+
+// Unbuffered Windows reads need sector-aligned buffers; cached reads are accepted
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn drop_cached_pages(_file: &std::fs::File) {}
+
+fn mbps(bytes: u64, elapsed: std::time::Duration) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+/// Writes, re-reads and samples random blocks of a temporary file in `dir`.
+/// Uses up to 256 MB, and never more than a tenth of the free space.
+pub fn benchmark_disk_io(dir: &std::path::Path) -> Result<DiskBenchmark, String> {
+    use rand::Rng;
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let block_bytes = BENCHMARK_BLOCK as u64;
+    let size = (available_space(dir)? / 10).min(BENCHMARK_MAX_BYTES) / block_bytes * block_bytes;
+    if size < BENCHMARK_MIN_BYTES {
+        return Err(format!("Not enough free space in {} to run a disk benchmark", dir.display()));
+    }
+
+    let probe = ProbeFile(dir.join(format!(".stratmaster-disk-benchmark-{}", std::process::id())));
+    let io_error = |e: std::io::Error| format!("Disk benchmark failed: {}", e);
+
+    // Random bytes so compressing filesystems can't shortcut the write
+    let mut block = vec![0u8; BENCHMARK_BLOCK];
+    rand::thread_rng().fill(block.as_mut_slice());
+    let started = std::time::Instant::now();
+    let mut file = std::fs::File::create(&probe.0).map_err(io_error)?;
+    for _ in 0..size / block_bytes {
+        file.write_all(&block).map_err(io_error)?;
+    }
+    file.sync_all().map_err(io_error)?;
+    let seq_write_mbps = mbps(size, started.elapsed());
+    drop(file);
+
+    let mut file = std::fs::File::open(&probe.0).map_err(io_error)?;
+    drop_cached_pages(&file);
+    let started = std::time::Instant::now();
+    let mut read = 0u64;
+    loop {
+        let n = file.read(&mut block).map_err(io_error)?;
+        if n == 0 {
+            break;
+        }
+        read += n as u64;
+    }
+    let seq_read_mbps = mbps(read, started.elapsed());
+
+    drop_cached_pages(&file);
+    let mut rng = rand::thread_rng();
+    let mut small = [0u8; RANDOM_READ_BLOCK];
+    let blocks = size / RANDOM_READ_BLOCK as u64;
+    let started = std::time::Instant::now();
+    let mut reads = 0u64;
+    while started.elapsed() < RANDOM_READ_DURATION {
+        let offset = rng.gen_range(0..blocks) * RANDOM_READ_BLOCK as u64;
+        file.seek(SeekFrom::Start(offset)).map_err(io_error)?;
+        file.read_exact(&mut small).map_err(io_error)?;
+        reads += 1;
+    }
+    let random_read_iops = reads as f64 / started.elapsed().as_secs_f64();
+
+    Ok(DiskBenchmark {
+        seq_read_mbps,
+        seq_write_mbps,
+        random_read_iops,
+        test_size_mb: size / (1024 * 1024),
+    })
+}
+
+// Hardware survey
+/// The parts of the hardware that only change when the machine does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareInventory {
+    /// [`machine_fingerprint`] when surveyed; a different one means new hardware.
+    pub fingerprint: Option<String>,
+    pub surveyed_ms: u64,
+    pub cpu_model: Option<String>,
+    pub physical_cores: Option<usize>,
+    pub logical_cores: usize,
+    pub cpu_features: CpuFeatures,
+    pub memory_total_bytes: u64,
+    pub gpus: Vec<GpuDescriptor>,
+}
+
+/// Runs every static probe once; slow (GPU tools, fingerprint), so call it
+/// off the async runtime.
+pub fn survey_hardware() -> HardwareInventory {
+    let mut sys = sysinfo::System::new();
+    sys.refresh_cpu_list(sysinfo::CpuRefreshKind::new());
+    sys.refresh_memory();
+    let cpu_model = sys
+        .cpus()
+        .first()
+        .map(|cpu| cpu.brand().trim().to_string())
+        .filter(|brand| !brand.is_empty());
+    let gpus = list_gpus().unwrap_or_else(|e| {
+        log::warn!("GPU detection failed during hardware survey: {}", e);
+        Vec::new()
+    });
+    HardwareInventory {
+        fingerprint: machine_fingerprint().ok(),
+        surveyed_ms: crate::unix_time_ms(),
+        cpu_model,
+        physical_cores: sys.physical_core_count(),
+        logical_cores: num_cpus::get(),
+        cpu_features: detect_cpu_features(),
+        memory_total_bytes: sys.total_memory(),
+        gpus,
+    }
+}
+
+// Accessibility
+/// Font scales accepted from the OS or the user; OS readings outside it are
+/// treated as misreads.
+pub const FONT_SCALE_RANGE: std::ops::RangeInclusive<f64> = 0.5..=3.0;
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn font_scale(scale: Option<f64>) -> f64 {
+    scale.filter(|scale| FONT_SCALE_RANGE.contains(scale)).unwrap_or(1.0)
+}
+
+/// GNOME's a11y settings; other desktops read as the defaults.
+#[cfg(target_os = "linux")]
+pub fn system_accessibility() -> crate::AccessibilityConfig {
+    let setting = |schema: &str, key: &str| command_output("gsettings", &["get", schema, key]).map(|value| value.trim().to_string());
+    crate::AccessibilityConfig {
+        high_contrast: setting("org.gnome.desktop.a11y.interface", "high-contrast").as_deref() == Some("true"),
+        reduce_motion: setting("org.gnome.desktop.interface", "enable-animations").as_deref() == Some("false"),
+        font_scale: font_scale(setting("org.gnome.desktop.interface", "text-scaling-factor").and_then(|value| value.parse().ok())),
+    }
+}
+
+/// Increase Contrast and Reduce Motion; macOS has no global text scale.
+#[cfg(target_os = "macos")]
+pub fn system_accessibility() -> crate::AccessibilityConfig {
+    let enabled = |key: &str| {
+        command_output("defaults", &["read", "com.apple.universalaccess", key]).is_some_and(|value| value.trim() == "1")
+    };
+    crate::AccessibilityConfig {
+        high_contrast: enabled("increaseContrast"),
+        reduce_motion: enabled("reduceMotion"),
+        font_scale: 1.0,
+    }
+}
+
+/// High Contrast's HCF_HIGHCONTRASTON flag, animations turned off under
+/// "Show animations in Windows", and the "Make text bigger" percentage.
+#[cfg(target_os = "windows")]
+pub fn system_accessibility() -> crate::AccessibilityConfig {
+    let value = |key: &str, name: &str| {
+        command_output("reg", &["query", key, "/v", name]).and_then(|output| {
+            output
+                .lines()
+                .find(|line| line.trim_start().starts_with(name))
+                .and_then(|line| line.split_whitespace().nth(2))
+                .map(str::to_string)
+        })
+    };
+    let high_contrast_flags = value(r"HKCU\Control Panel\Accessibility\HighContrast", "Flags")
+        .and_then(|flags| flags.parse::<u32>().ok())
+        .unwrap_or(0);
+    let text_scale_percent = value(r"HKCU\Software\Microsoft\Accessibility", "TextScaleFactor")
+        .and_then(|percent| u32::from_str_radix(percent.trim_start_matches("0x"), 16).ok());
+    crate::AccessibilityConfig {
+        high_contrast: high_contrast_flags & 0x1 != 0,
+        reduce_motion: value(r"HKCU\Control Panel\Desktop\WindowMetrics", "MinAnimate").as_deref() == Some("0"),
+        font_scale: font_scale(text_scale_percent.map(|percent| percent as f64 / 100.0)),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn system_accessibility() -> crate::AccessibilityConfig {
+    crate::AccessibilityConfig::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every x86_64 and AArch64 CI runner has at least SSE4.2 or NEON
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    #[test]
+    fn detects_at_least_one_feature() {
+        let features = detect_cpu_features();
+        assert!(features.sse4_2 || features.avx || features.neon, "{:?}", features);
+    }
+
+    #[test]
+    fn elevation_check_succeeds() {
+        assert!(is_elevated().is_ok());
+    }
+
+    // CI runs unprivileged; inside a root container this checks the other branch
+    #[cfg(unix)]
+    #[test]
+    fn unprivileged_process_is_not_elevated() {
+        let root = unsafe { libc::geteuid() } == 0;
+        assert_eq!(is_elevated(), Ok(root));
+    }
+
+    #[test]
+    fn fingerprint_is_stable_within_a_run() {
+        let first = machine_fingerprint();
+        assert_eq!(first, machine_fingerprint());
+        if let Ok(fingerprint) = first {
+            assert_eq!(fingerprint.len(), 64);
+            assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()), "{}", fingerprint);
+        }
+    }
+
+    #[test]
+    fn accept_language_follows_the_locale() {
+        assert_eq!(LocaleInfo::from_tag("de-AT").unwrap().accept_language(), "de-AT,de;q=0.9");
+        assert_eq!(LocaleInfo::from_tag("fr").unwrap().accept_language(), "fr");
+    }
+
+    #[test]
+    fn accept_language_appends_fallbacks_after_the_locale() {
+        let locale = LocaleInfo::from_tag("en-GB").unwrap();
+        assert_eq!(locale.accept_language_with("fr, de;q=0.5, en"), "en-GB,en;q=0.9,fr;q=0.8,de;q=0.7");
+        let many: Vec<String> = (0..12).map(|index| format!("x{}", index)).collect();
+        assert!(locale.accept_language_with(&many.join(",")).ends_with(",x11;q=0.1"));
+    }
+
+    #[test]
+    fn build_tier_prefers_the_widest_vectors() {
+        let avx512 = CpuFeatures { avx512: true, avx2: true, fma: true, ..CpuFeatures::default() };
+        assert_eq!(avx512.build_tier(), "avx512");
+        let avx2_without_fma = CpuFeatures { avx2: true, ..CpuFeatures::default() };
+        assert_eq!(avx2_without_fma.build_tier(), "baseline");
+        let neon = CpuFeatures { neon: true, fma: true, ..CpuFeatures::default() };
+        assert_eq!(neon.build_tier(), "neon");
+    }
+}
@@ -0,0 +1,148 @@
+// Update signatures
+// Update artifacts are signed with minisign (the format the Tauri updater
+// uses) and checked against a public key pinned into the build
+// (STRATMASTER_UPDATE_PUBLIC_KEY, the base64 line of a minisign .pub file).
+// Nothing is offered for install unless both the file signature and the
+// signed trusted comment verify.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use std::io::Read;
+
+/// Minisign public key accepted for updates; unset refuses every update.
+pub const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("STRATMASTER_UPDATE_PUBLIC_KEY");
+
+// Signature algorithms: the file itself, or its BLAKE2b-512 digest
+const ALG_LEGACY: &[u8; 2] = b"Ed";
+const ALG_PREHASHED: &[u8; 2] = b"ED";
+
+struct PublicKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+
+struct MinisignSignature {
+    algorithm: [u8; 2],
+    key_id: [u8; 8],
+    signature: Signature,
+    trusted_comment: String,
+    global_signature: Signature,
+}
+
+/// Key IDs as minisign prints them: the little-endian u64 in hex.
+fn key_id_hex(key_id: &[u8; 8]) -> String {
+    format!("{:016X}", u64::from_le_bytes(*key_id))
+}
+
+fn parse_public_key(encoded: &str) -> Result<PublicKey, String> {
+    let bytes = BASE64
+        .decode(encoded.trim())
+        .map_err(|_| "Invalid update public key: not base64".to_string())?;
+    if bytes.len() != 42 || &bytes[..2] != ALG_LEGACY {
+        return Err("Invalid update public key: not a minisign Ed25519 key".to_string());
+    }
+    let key = VerifyingKey::from_bytes(bytes[10..].try_into().unwrap_or(&[0; 32]))
+        .map_err(|e| format!("Invalid update public key: {}", e))?;
+    Ok(PublicKey {
+        key_id: bytes[2..10].try_into().unwrap_or_default(),
+        key,
+    })
+}
+
+// Accepts the .sig file's text, or that text base64-encoded as the Tauri
+// updater ships it
+fn parse_signature(signature: &str) -> Result<MinisignSignature, String> {
+    let text = match BASE64.decode(signature.trim()) {
+        Ok(decoded) if decoded.starts_with(b"untrusted comment:") => String::from_utf8(decoded)
+            .map_err(|_| "Update signature is not valid UTF-8".to_string())?,
+        _ => signature.to_string(),
+    };
+    let invalid = |what: &str| format!("Invalid update signature: {}", what);
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    if !lines.next().is_some_and(|line| line.starts_with("untrusted comment:")) {
+        return Err(invalid("missing untrusted comment"));
+    }
+    let bytes = lines
+        .next()
+        .and_then(|line| BASE64.decode(line).ok())
+        .filter(|bytes| bytes.len() == 74)
+        .ok_or_else(|| invalid("malformed signature line"))?;
+    let trusted_comment = lines
+        .next()
+        .and_then(|line| line.strip_prefix("trusted comment:"))
+        .map(|comment| comment.trim_start().to_string())
+        .ok_or_else(|| invalid("missing trusted comment"))?;
+    let global = lines
+        .next()
+        .and_then(|line| BASE64.decode(line).ok())
+        .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+        .ok_or_else(|| invalid("malformed trusted comment signature"))?;
+
+    let algorithm: [u8; 2] = bytes[..2].try_into().unwrap_or_default();
+    if &algorithm != ALG_LEGACY && &algorithm != ALG_PREHASHED {
+        return Err(invalid("unsupported algorithm"));
+    }
+    Ok(MinisignSignature {
+        algorithm,
+        key_id: bytes[2..10].try_into().unwrap_or_default(),
+        signature: Signature::from_bytes(bytes[10..].try_into().unwrap_or(&[0; 64])),
+        trusted_comment,
+        global_signature: Signature::from_bytes(&global),
+    })
+}
+
+fn signed_message(path: &std::path::Path, algorithm: &[u8; 2]) -> Result<Vec<u8>, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let read_error = |e: std::io::Error| format!("Failed to read {}: {}", path.display(), e);
+    if algorithm == ALG_LEGACY {
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(read_error)?;
+        return Ok(contents);
+    }
+    let mut hasher = Blake2b512::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(read_error)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Verifies `path` against a minisign `signature` and the pinned key. Any
+/// failure is an error naming the expected key ID.
+pub fn verify_file(path: &std::path::Path, signature: &str) -> Result<(), String> {
+    let encoded_key = UPDATE_PUBLIC_KEY.ok_or_else(|| "This build has no update key; updates can't be verified".to_string())?;
+    let public_key = parse_public_key(encoded_key)?;
+    let expected = key_id_hex(&public_key.key_id);
+    if signature.trim().is_empty() {
+        return Err(format!("Update has no signature (expected key {})", expected));
+    }
+    let signature = parse_signature(signature).map_err(|e| format!("{} (expected key {})", e, expected))?;
+    if signature.key_id != public_key.key_id {
+        return Err(format!(
+            "Update is signed with key {}, expected key {}",
+            key_id_hex(&signature.key_id),
+            expected
+        ));
+    }
+
+    let message = signed_message(path, &signature.algorithm)?;
+    public_key
+        .key
+        .verify(&message, &signature.signature)
+        .map_err(|_| format!("Update signature does not match {} (expected key {})", path.display(), expected))?;
+    // The trusted comment is signed together with the file signature
+    let mut global = signature.signature.to_bytes().to_vec();
+    global.extend_from_slice(signature.trusted_comment.as_bytes());
+    public_key
+        .key
+        .verify(&global, &signature.global_signature)
+        .map_err(|_| format!("Update signature's trusted comment has been modified (expected key {})", expected))?;
+    log::info!("Verified update {} ({})", path.display(), signature.trusted_comment);
+    Ok(())
+}
@@ -0,0 +1,124 @@
+// Webview flags
+// A fixed allowlist of engine tweaks for chasing rendering problems. Each
+// flag maps to platform switches chosen here, so no caller-supplied string
+// ever reaches the webview command line. Flags take effect when windows are
+// created, so changing one needs a restart.
+
+use serde::{Deserialize, Serialize};
+
+pub const REMOTE_DEBUGGING_PORT: u16 = 9222;
+pub const FLAG_NAMES: &[&str] = &["disable_gpu", "remote_debugging", "ignore_certificate_errors"];
+
+// Tauri's own WebView2 arguments; setting ours replaces them, so keep them
+#[cfg(target_os = "windows")]
+const WEBVIEW2_DEFAULT_ARGS: &str = "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebviewFlags {
+    /// Software rendering, for GPU driver glitches.
+    pub disable_gpu: bool,
+    /// Exposes the inspector on `127.0.0.1:9222`.
+    pub remote_debugging: bool,
+    /// Accepts self-signed certificates; for local development only.
+    pub ignore_certificate_errors: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebviewFlagState {
+    pub name: String,
+    pub enabled: bool,
+    /// Whether this platform's webview honours the flag at all.
+    pub supported: bool,
+    /// The saved value differs from the one the running windows were created with.
+    pub restart_required: bool,
+}
+
+impl WebviewFlags {
+    pub fn get(&self, name: &str) -> Option<bool> {
+        match name {
+            "disable_gpu" => Some(self.disable_gpu),
+            "remote_debugging" => Some(self.remote_debugging),
+            "ignore_certificate_errors" => Some(self.ignore_certificate_errors),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: bool) -> Result<(), String> {
+        let flag = match name {
+            "disable_gpu" => &mut self.disable_gpu,
+            "remote_debugging" => &mut self.remote_debugging,
+            "ignore_certificate_errors" => &mut self.ignore_certificate_errors,
+            _ => {
+                return Err(format!(
+                    "Unknown webview flag {}; allowed flags are {}",
+                    name,
+                    FLAG_NAMES.join(", ")
+                ))
+            }
+        };
+        *flag = value;
+        Ok(())
+    }
+
+    /// Every allowlisted flag with its saved value, compared against `applied`.
+    pub fn states(&self, applied: &WebviewFlags) -> Vec<WebviewFlagState> {
+        FLAG_NAMES
+            .iter()
+            .map(|name| WebviewFlagState {
+                name: name.to_string(),
+                enabled: self.get(name).unwrap_or(false),
+                supported: is_supported(name),
+                restart_required: self.get(name) != applied.get(name),
+            })
+            .collect()
+    }
+
+    /// WebView2 command-line switches; `None` leaves Tauri's defaults alone.
+    #[cfg(target_os = "windows")]
+    pub fn browser_args(&self) -> Option<String> {
+        let mut args = vec![WEBVIEW2_DEFAULT_ARGS.to_string()];
+        if self.disable_gpu {
+            args.push("--disable-gpu".to_string());
+        }
+        if self.remote_debugging {
+            args.push(format!("--remote-debugging-port={}", REMOTE_DEBUGGING_PORT));
+        }
+        if self.ignore_certificate_errors {
+            args.push("--ignore-certificate-errors".to_string());
+        }
+        (args.len() > 1).then(|| args.join(" "))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn browser_args(&self) -> Option<String> {
+        None
+    }
+
+    /// Variables WebKitGTK reads when the first webview starts.
+    #[cfg(target_os = "linux")]
+    pub fn environment(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        if self.disable_gpu {
+            vars.push(("WEBKIT_DISABLE_COMPOSITING_MODE", "1".to_string()));
+            vars.push(("WEBKIT_DISABLE_DMABUF_RENDERER", "1".to_string()));
+        }
+        if self.remote_debugging {
+            vars.push(("WEBKIT_INSPECTOR_SERVER", format!("127.0.0.1:{}", REMOTE_DEBUGGING_PORT)));
+        }
+        vars
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn environment(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+fn is_supported(name: &str) -> bool {
+    match name {
+        "disable_gpu" | "remote_debugging" => cfg!(any(target_os = "windows", target_os = "linux")),
+        "ignore_certificate_errors" => cfg!(target_os = "windows"),
+        _ => false,
+    }
+}
@@ -16,6 +16,8 @@ from .models import (
     CompletionResponse,
     EmbeddingRequest,
     EmbeddingResponse,
+    HardwareProfileRequest,
+    HardwareProfileResponse,
     InfoResponse,
     RerankRequest,
     RerankResponse,
@@ -102,6 +104,12 @@ def create_app() -> FastAPI:
         """Route query to appropriate specialist agents - Sprint 1."""
         return service.route_agents(payload)
 
+    @tools.post("/hardware-profile", response_model=HardwareProfileResponse)
+    async def hardware_profile(payload: HardwareProfileRequest) -> HardwareProfileResponse:
+        """Apply the host's hardware limits to model selection."""
+        service.set_hardware_profile(payload)
+        return HardwareProfileResponse(status="applied", profile=payload)
+
     # Model recommendation with bandit selection per SCRATCH.md Phase 1
     @tools.post("/models/recommend")
     async def recommend_model(request: dict) -> JSONResponse:
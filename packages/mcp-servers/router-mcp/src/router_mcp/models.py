@@ -122,3 +122,20 @@ class AgentRouteResponse(BaseModel):
     confidence: float
     routing_metadata: dict[str, Any]
     tenant_id: str
+
+
+class HardwareProfileRequest(BaseModel):
+    """Resolved hardware limits pushed by the desktop app."""
+    model_config = ConfigDict(extra="forbid")
+
+    max_memory_mb: int = Field(gt=0)
+    cpu_threads: int = Field(gt=0)
+    enable_gpu: bool
+    model_preference: str
+
+
+class HardwareProfileResponse(BaseModel):
+    model_config = ConfigDict(extra="forbid")
+
+    status: str
+    profile: HardwareProfileRequest
@@ -62,3 +62,13 @@ class InfoResponse(BaseModel):
     version: str
     capabilities: list[str]
     service: ServiceInfo
+
+class HardwareProfileRequest(BaseModel):
+    max_memory_mb: int
+    cpu_threads: int
+    enable_gpu: bool
+    model_preference: str
+
+class HardwareProfileResponse(BaseModel):
+    status: str
+    profile: HardwareProfileRequest
@@ -20,6 +20,7 @@ from .models import (
     EmbeddingRequest,
     EmbeddingResponse,
     EmbeddingVector,
+    HardwareProfileRequest,
     RerankRequest,
     RerankResponse,
     RerankResult,
@@ -36,6 +37,7 @@ class RouterService:
         self.policy = config.policy
         self.decoding_cfg = config.structured_decoding
         self._provider_cache: dict[tuple[str, str, str, str], ProviderAdapter] = {}
+        self.hardware_profile: HardwareProfileRequest | None = None
         
         # Initialize model recommender if V2 is enabled
         self.model_recommender = None
@@ -200,6 +202,10 @@ class RouterService:
                 )
         return response
 
+    def set_hardware_profile(self, profile: HardwareProfileRequest) -> None:
+        """Record the host's hardware limits for later routing decisions."""
+        self.hardware_profile = profile
+
     async def shutdown(self) -> None:
         """Release any async resources held by the service."""
         if self.model_recommender:
@@ -17,6 +17,33 @@ def test_info(client=client()):
     assert "router-mcp" in resp.json()["name"]
 
 
+def test_hardware_profile_endpoint(client=client()):
+    profile = {
+        "max_memory_mb": 8192,
+        "cpu_threads": 4,
+        "enable_gpu": True,
+        "model_preference": "medium",
+    }
+    resp = client.post("/tools/hardware-profile", json=profile)
+    assert resp.status_code == 200
+    body = resp.json()
+    assert body["status"] == "applied"
+    assert body["profile"] == profile
+
+
+def test_hardware_profile_rejects_zero_threads(client=client()):
+    resp = client.post(
+        "/tools/hardware-profile",
+        json={
+            "max_memory_mb": 8192,
+            "cpu_threads": 0,
+            "enable_gpu": False,
+            "model_preference": "small",
+        },
+    )
+    assert resp.status_code == 422
+
+
 def test_complete_endpoint(client=client()):
     resp = client.post(
         "/tools/complete",